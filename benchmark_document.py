@@ -148,6 +148,14 @@ def benchmark_rust_document():
     deserialization_time = end_time - start_time
     print(f"  Rust Deserialization: {deserialization_time:.6f} seconds")
 
+    # 4. Bulk round trip through one JSON Lines string
+    docs = [doc_instance] * N_ITERATIONS
+    start_time = time.perf_counter()
+    data = rust_document.documents_to_json(docs, lines=True)
+    rust_document.documents_from_json(data, lines=True)
+    end_time = time.perf_counter()
+    print(f"  Rust Bulk Round Trip: {end_time - start_time:.6f} seconds")
+
     return creation_time, serialization_time, deserialization_time
 
 if __name__ == "__main__":
@@ -38,11 +38,12 @@ class Document:
     @classmethod
     def from_dict(cls, document: Dict[str, Any]) -> "Document":
         """Returns a Document object from a dictionary representation"""
-        return cls(**document)
+        # The Rust document bindings write a schema_version key, which is not a field
+        return cls(**{key: value for key, value in document.items() if key != "schema_version"})
 
     @classmethod
     def from_json(cls, document: str) -> "Document":
         """Returns a Document object from a json string representation"""
         import json
 
-        return cls(**json.loads(document))
+        return cls.from_dict(json.loads(document))
@@ -0,0 +1,16 @@
+import json
+
+from agno.document.base import Document
+
+
+def test_from_json_ignores_schema_version():
+    # Output of the Rust document bindings, which carries a schema_version key
+    data = {"schema_version": 2, "content": "Hello", "name": "greeting", "meta_data": {"page": 1}}
+
+    document = Document.from_json(json.dumps(data))
+
+    assert document.content == "Hello"
+    assert document.name == "greeting"
+    assert document.meta_data == {"page": 1}
+    assert Document.from_dict(data) == document
+    assert "schema_version" in data
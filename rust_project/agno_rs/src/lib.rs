@@ -0,0 +1,38 @@
+use pyo3::prelude::*;
+
+type Register = fn(&Bound<'_, PyModule>) -> PyResult<()>;
+
+/// Adds `name` as a submodule of `parent`, filled by `register`.
+///
+/// The submodule is named after its full path and registered in
+/// `sys.modules`, so that `import agno_rs.knowledge` and
+/// `from agno_rs.knowledge import ...` work, and pickle finds its classes.
+fn add_submodule(parent: &Bound<'_, PyModule>, name: &str, register: Register) -> PyResult<()> {
+    let py = parent.py();
+    let qualified_name = format!("{}.{}", parent.name()?, name);
+    let module = PyModule::new_bound(py, &qualified_name)?;
+    register(&module)?;
+    parent.add(name, &module)?;
+    py.import_bound("sys")?
+        .getattr("modules")?
+        .set_item(qualified_name, &module)?;
+    Ok(())
+}
+
+/// Python module bundling the Rust core: `document`, `vectordb`,
+/// `knowledge`, `memory`, `embedder` and `reranker` as submodules of one
+/// extension.
+///
+/// Building them into a single library means they share one `Document`
+/// class, so documents flow between the submodules (and Python vector dbs)
+/// without conversion.
+#[pymodule]
+fn agno_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    add_submodule(m, "document", document::register)?;
+    add_submodule(m, "vectordb", vectordb::python::register)?;
+    add_submodule(m, "knowledge", knowledge::python::register)?;
+    add_submodule(m, "memory", memory::register)?;
+    add_submodule(m, "embedder", embedder::python::register)?;
+    add_submodule(m, "reranker", reranker::python::register)?;
+    Ok(())
+}
@@ -0,0 +1,6 @@
+from . import document as document
+from . import embedder as embedder
+from . import knowledge as knowledge
+from . import memory as memory
+from . import reranker as reranker
+from . import vectordb as vectordb
@@ -0,0 +1,164 @@
+import os
+from datetime import datetime
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+_Path = Union[str, os.PathLike[str]]
+
+DOCUMENT_SCHEMA_VERSION: int
+
+class DocumentDict(Dict[str, Any]): ...
+
+class Document:
+    content: str
+    id: Optional[str]
+    name: Optional[str]
+    @property
+    def meta_data(self) -> DocumentDict: ...
+    @meta_data.setter
+    def meta_data(self, value: Dict[str, Any]) -> None: ...
+    embedding: Optional[List[float]]
+    @property
+    def usage(self) -> Optional[DocumentDict]: ...
+    @usage.setter
+    def usage(self, value: Optional[Dict[str, Any]]) -> None: ...
+    reranking_score: Optional[float]
+    def __init__(
+        self,
+        content: str,
+        id: Optional[str] = None,
+        name: Optional[str] = None,
+        reranking_score: Optional[float] = None,
+        meta_data: Optional[Dict[str, Any]] = None,
+        usage: Optional[Dict[str, Any]] = None,
+        embedding: Optional[List[float]] = None,
+    ) -> None: ...
+    def __eq__(self, other: object) -> bool: ...
+    def __hash__(self) -> int: ...
+    def __copy__(self) -> Document: ...
+    def __deepcopy__(self, memo: Dict[int, Any]) -> Document: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+    @staticmethod
+    def from_dict(document: Dict[str, Any]) -> Document: ...
+    def to_bytes(self, format: str = "msgpack") -> bytes: ...
+    @staticmethod
+    def from_bytes(data: bytes) -> Document: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json_str: str) -> Document: ...
+    @property
+    def provenance(self) -> Optional[ChunkProvenance]: ...
+    def get_path(self, path: str, default: Any = None) -> Any: ...
+    def get_str(self, path: str) -> Optional[str]: ...
+    def get_i64(self, path: str) -> Optional[int]: ...
+    def get_f64(self, path: str) -> Optional[float]: ...
+    def get_bool(self, path: str) -> Optional[bool]: ...
+    def get_datetime(self, path: str) -> Optional[datetime]: ...
+    def set_path(self, path: str, value: Any) -> Any: ...
+    def set_datetime(self, path: str, value: datetime) -> None: ...
+    def remove_path(self, path: str) -> Any: ...
+    def merge_meta_data(self, patch: Dict[str, Any]) -> None: ...
+    def validate_meta_data(self, schema: MetaDataSchema) -> None: ...
+    def get_meta_data_as_json(self) -> str: ...
+    def set_meta_data_from_json(self, json_str: str) -> None: ...
+
+class MetaDataSchema:
+    def __init__(
+        self, fields: Dict[str, str], required: Optional[List[str]] = None, allow_unknown: bool = True
+    ) -> None: ...
+    def violations(self, document: Document) -> List[str]: ...
+    def validate(self, document: Document) -> None: ...
+
+class ChunkProvenance:
+    @property
+    def parent_id(self) -> Optional[str]: ...
+    @property
+    def chunk_index(self) -> int: ...
+    @property
+    def total_chunks(self) -> int: ...
+    @property
+    def byte_start(self) -> int: ...
+    @property
+    def byte_end(self) -> int: ...
+    @property
+    def char_start(self) -> int: ...
+    @property
+    def char_end(self) -> int: ...
+    @property
+    def skipped_before(self) -> str: ...
+    @property
+    def skipped_after(self) -> str: ...
+
+class CodeChunking:
+    def __init__(
+        self,
+        max_chunk_size: int = 5000,
+        window_lines: int = 50,
+        overlap_lines: int = 5,
+        language: Optional[str] = None,
+    ) -> None: ...
+    def chunk(self, document: Document) -> List[Document]: ...
+
+def reassemble_chunks(chunks: List[Document]) -> str: ...
+def chunk_documents(
+    strategy: CodeChunking, documents: List[Document], num_threads: Optional[int] = None
+) -> List[Union[List[Document], ValueError]]: ...
+
+def documents_to_json(documents: List[Document], lines: bool = False) -> str: ...
+def documents_from_json(data: str, lines: bool = False) -> List[Document]: ...
+def write_documents(path: _Path, documents: List[Document], lines: Optional[bool] = None) -> None: ...
+def read_documents(path: _Path, lines: Optional[bool] = None) -> List[Document]: ...
+
+class DocumentBatch:
+    @property
+    def num_rows(self) -> int: ...
+    @property
+    def column_names(self) -> List[str]: ...
+    def __len__(self) -> int: ...
+    def to_documents(self) -> List[Document]: ...
+    def __arrow_c_schema__(self) -> object: ...
+    def __arrow_c_array__(self, requested_schema: Optional[object] = None) -> Tuple[object, object]: ...
+    def __arrow_c_stream__(self, requested_schema: Optional[object] = None) -> object: ...
+
+def documents_to_arrow(documents: List[Document], meta_data: str = "json") -> DocumentBatch: ...
+def documents_from_arrow(data: object) -> List[Document]: ...
+def write_parquet(path: _Path, documents: List[Document], meta_data: str = "json") -> None: ...
+def read_parquet(path: _Path) -> List[Document]: ...
+
+class _Reader:
+    def read(self, path: _Path) -> List[Document]: ...
+    def read_bytes(self, data: bytes, file_name: str) -> List[Document]: ...
+
+class TextReader(_Reader):
+    def __init__(
+        self, lines_per_document: Optional[int] = None, chunking_strategy: Optional[CodeChunking] = None
+    ) -> None: ...
+
+class MarkdownReader(_Reader):
+    def __init__(self, split_on_headings: bool = False, chunking_strategy: Optional[CodeChunking] = None) -> None: ...
+
+class JsonReader(_Reader):
+    def __init__(self, split_objects: bool = False, chunking_strategy: Optional[CodeChunking] = None) -> None: ...
+
+class CsvReader(_Reader):
+    def __init__(
+        self,
+        delimiter: str = ",",
+        quotechar: str = '"',
+        rows_per_document: Optional[int] = None,
+        chunking_strategy: Optional[CodeChunking] = None,
+    ) -> None: ...
+
+class PdfReader(_Reader):
+    def __init__(
+        self,
+        password: Optional[str] = None,
+        detect_columns: bool = True,
+        chunking_strategy: Optional[CodeChunking] = None,
+    ) -> None: ...
+    def extract(self, data: bytes, file_name: str) -> Tuple[List[Document], Dict[int, str]]: ...
+
+class DocxReader(_Reader):
+    def __init__(self, split_on_headings: bool = False, chunking_strategy: Optional[CodeChunking] = None) -> None: ...
+
+class HtmlReader(_Reader):
+    def __init__(self, split_on_headings: bool = False, chunking_strategy: Optional[CodeChunking] = None) -> None: ...
@@ -0,0 +1,61 @@
+import os
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+from .document import Document
+
+class EmbeddingReport:
+    @property
+    def embedded(self) -> int: ...
+    @property
+    def cached(self) -> int: ...
+    @property
+    def requests(self) -> int: ...
+    @property
+    def retries(self) -> int: ...
+    @property
+    def prompt_tokens(self) -> int: ...
+    @property
+    def total_tokens(self) -> int: ...
+
+class HttpEmbedder:
+    def __init__(
+        self,
+        id: str,
+        base_url: Optional[str] = None,
+        api_key: Optional[str] = None,
+        dimensions: Optional[int] = None,
+        auth_header: str = "Authorization",
+        timeout: float = 60.0,
+    ) -> None: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def base_url(self) -> str: ...
+    @property
+    def dimensions(self) -> Optional[int]: ...
+    def get_embedding(self, text: str) -> List[float]: ...
+    def get_embedding_and_usage(self, text: str) -> Tuple[List[float], Optional[Dict[str, int]]]: ...
+
+class LocalEmbedder:
+    def __init__(self, path: Union[str, os.PathLike[str]], normalize: bool = True) -> None: ...
+    @property
+    def id(self) -> str: ...
+    @property
+    def dimensions(self) -> Optional[int]: ...
+    def get_embedding(self, text: str) -> List[float]: ...
+    def get_embedding_and_usage(self, text: str) -> Tuple[List[float], Optional[Dict[str, int]]]: ...
+
+class EmbeddingPipeline:
+    def __init__(
+        self,
+        embedder: Any,
+        batch_size: int = 100,
+        concurrency: int = 4,
+        requests_per_minute: Optional[int] = None,
+        tokens_per_minute: Optional[int] = None,
+        max_retries: int = 3,
+        cache_dir: Optional[Union[str, os.PathLike[str]]] = None,
+    ) -> None: ...
+    def embed_documents(self, documents: List[Document]) -> List[Document]: ...
+    @property
+    def last_report(self) -> Optional[EmbeddingReport]: ...
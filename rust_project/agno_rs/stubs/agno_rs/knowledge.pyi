@@ -0,0 +1,38 @@
+import os
+from typing import Any, Dict, List, Optional, Union
+
+from .document import Document
+from .vectordb import VectorDb
+
+class DocumentKnowledgeBase:
+    documents: List[Document]
+    def __init__(self, documents: Optional[List[Document]] = None) -> None: ...
+    def add_document(self, document: Document) -> None: ...
+    def document_lists(self) -> List[List[Document]]: ...
+    def __len__(self) -> int: ...
+
+class KnowledgeBase:
+    num_documents: int
+    optimize_on: Optional[int]
+    def __init__(
+        self,
+        source: Union[DocumentKnowledgeBase, List[Document], str, os.PathLike[str]],
+        vector_db: VectorDb,
+        num_documents: int = 5,
+        optimize_on: Optional[int] = 1000,
+        reranker: Optional[Any] = None,
+    ) -> None: ...
+    @property
+    def vector_db(self) -> VectorDb: ...
+    @property
+    def reranker(self) -> Optional[Any]: ...
+    @property
+    def valid_metadata_filters(self) -> List[str]: ...
+    def document_lists(self) -> List[List[Document]]: ...
+    def load(self, recreate: bool = False, upsert: bool = False, skip_existing: bool = True) -> int: ...
+    def initialize_valid_filters(self) -> None: ...
+    def search(
+        self, query: str, num_documents: Optional[int] = None, filters: Optional[Dict[str, Any]] = None
+    ) -> List[Document]: ...
+    def exists(self) -> bool: ...
+    def delete(self) -> bool: ...
@@ -0,0 +1,25 @@
+from typing import ClassVar, Dict, Optional
+
+MEMORY_SCHEMA_VERSION: int
+
+class MemoryRetrieval:
+    last_n: ClassVar[MemoryRetrieval]
+    first_n: ClassVar[MemoryRetrieval]
+    semantic: ClassVar[MemoryRetrieval]
+    @property
+    def value(self) -> str: ...
+    @staticmethod
+    def from_value(value: str) -> MemoryRetrieval: ...
+
+class Memory:
+    memory: str
+    id: Optional[str]
+    topic: Optional[str]
+    input: Optional[str]
+    def __init__(
+        self, memory: str, id: Optional[str] = None, topic: Optional[str] = None, input: Optional[str] = None
+    ) -> None: ...
+    def to_dict(self) -> Dict[str, str]: ...
+    def to_json(self) -> str: ...
+    @staticmethod
+    def from_json(json_str: str) -> Memory: ...
@@ -0,0 +1,25 @@
+from typing import List, Optional
+
+from .document import Document
+
+class LexicalReranker:
+    def __init__(self, top_n: Optional[int] = None, k1: float = 1.2, b: float = 0.75) -> None: ...
+    def rerank(self, query: str, documents: List[Document]) -> List[Document]: ...
+
+class HttpReranker:
+    def __init__(
+        self,
+        model: str,
+        base_url: Optional[str] = None,
+        api_key: Optional[str] = None,
+        top_n: Optional[int] = None,
+        auth_header: str = "Authorization",
+        timeout: float = 60.0,
+    ) -> None: ...
+    @property
+    def model(self) -> str: ...
+    @property
+    def base_url(self) -> str: ...
+    @property
+    def top_n(self) -> Optional[int]: ...
+    def rerank(self, query: str, documents: List[Document]) -> List[Document]: ...
@@ -0,0 +1,24 @@
+from typing import Any, Dict, List, Optional
+
+from .document import Document
+
+class VectorDb:
+    """Base class for vector dbs written in Python; override what the backend supports."""
+
+    def __init__(self, *args: Any, **kwargs: Any) -> None: ...
+    def create(self) -> None: ...
+    def doc_exists(self, document: Document) -> bool: ...
+    def name_exists(self, name: str) -> bool: ...
+    def id_exists(self, id: str) -> bool: ...
+    def insert(self, documents: List[Document], filters: Optional[Dict[str, Any]] = None) -> None: ...
+    def upsert_available(self) -> bool: ...
+    def upsert(self, documents: List[Document], filters: Optional[Dict[str, Any]] = None) -> None: ...
+    def search(self, query: str, limit: int = 5, filters: Optional[Dict[str, Any]] = None) -> List[Document]: ...
+    def vector_search(self, query: str, limit: int = 5) -> List[Document]: ...
+    def keyword_search(self, query: str, limit: int = 5) -> List[Document]: ...
+    def hybrid_search(self, query: str, limit: int = 5) -> List[Document]: ...
+    def drop(self) -> None: ...
+    def exists(self) -> bool: ...
+    def optimize(self) -> None: ...
+    def delete(self) -> bool: ...
+    def delete_by_id(self, id: str) -> bool: ...
@@ -0,0 +1,291 @@
+//! Compact binary encodings of a `Document`, for caches and inter-process
+//! transfer where JSON spends most of its bytes on embedding floats.
+//!
+//! Every encoding starts with a two-byte header: the layout version, then the
+//! format. Decoding reads the header, so callers only pick a format when
+//! encoding, and documents written by an older layout stay readable.
+
+use crate::Document;
+use pyo3::exceptions::PyValueError;
+use pyo3::PyErr;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The layout version written into the header of every encoding.
+///
+/// - 1: the original layouts.
+/// - 2: bincode also stores the unknown fields of `Document::extra`, with
+///   the schema version of a document from a newer version. MessagePack is
+///   unchanged, since it carries the JSON schema version.
+pub const BINARY_VERSION: u8 = 2;
+
+const HEADER_LEN: usize = 2;
+
+// Define a custom error type for binary encoding
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryError {
+    Truncated,
+    UnsupportedVersion(u8),
+    UnknownFormat(String),
+    Encode(String),
+    Decode(String),
+}
+
+impl fmt::Display for BinaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinaryError::Truncated => write!(f, "Encoded document is shorter than its header"),
+            BinaryError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported encoding version {} (this build reads up to {})", version, BINARY_VERSION)
+            }
+            BinaryError::UnknownFormat(format) => write!(f, "Unknown binary format: {}", format),
+            BinaryError::Encode(msg) => write!(f, "Encoding error: {}", msg),
+            BinaryError::Decode(msg) => write!(f, "Decoding error: {}", msg),
+        }
+    }
+}
+
+impl Error for BinaryError {}
+
+impl From<BinaryError> for PyErr {
+    fn from(err: BinaryError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// The binary encodings of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryFormat {
+    /// MessagePack with named fields: self-describing, so documents with
+    /// fields added later still decode.
+    MessagePack,
+    /// Bincode: the smallest and fastest, with `meta_data` and `usage`
+    /// stored as JSON text and the embedding as raw floats.
+    Bincode,
+}
+
+impl BinaryFormat {
+    /// The format tag written into the header.
+    pub fn tag(self) -> u8 {
+        match self {
+            BinaryFormat::MessagePack => 0,
+            BinaryFormat::Bincode => 1,
+        }
+    }
+
+    /// The format with the given header tag.
+    pub fn from_tag(tag: u8) -> Result<Self, BinaryError> {
+        match tag {
+            0 => Ok(BinaryFormat::MessagePack),
+            1 => Ok(BinaryFormat::Bincode),
+            _ => Err(BinaryError::UnknownFormat(format!("tag {}", tag))),
+        }
+    }
+
+    /// The name Python passes to `Document.to_bytes`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BinaryFormat::MessagePack => "msgpack",
+            BinaryFormat::Bincode => "bincode",
+        }
+    }
+}
+
+impl FromStr for BinaryFormat {
+    type Err = BinaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "msgpack" | "messagepack" => Ok(BinaryFormat::MessagePack),
+            "bincode" => Ok(BinaryFormat::Bincode),
+            _ => Err(BinaryError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// The bincode layout of a document. Bincode is not self-describing, so it
+/// cannot carry `serde_json::Value` or skipped fields; the JSON maps are
+/// stored as text instead.
+#[derive(Serialize, Deserialize)]
+struct BincodeDocument {
+    content: String,
+    id: Option<String>,
+    name: Option<String>,
+    meta_data: String,
+    embedding: Option<Vec<f32>>,
+    usage: Option<String>,
+    reranking_score: Option<f64>,
+    extra: String,
+}
+
+/// The bincode layout of version 1, without `extra`.
+#[derive(Deserialize)]
+struct BincodeDocumentV1 {
+    content: String,
+    id: Option<String>,
+    name: Option<String>,
+    meta_data: String,
+    embedding: Option<Vec<f32>>,
+    usage: Option<String>,
+    reranking_score: Option<f64>,
+}
+
+impl From<BincodeDocumentV1> for BincodeDocument {
+    fn from(document: BincodeDocumentV1) -> Self {
+        BincodeDocument {
+            content: document.content,
+            id: document.id,
+            name: document.name,
+            meta_data: document.meta_data,
+            embedding: document.embedding,
+            usage: document.usage,
+            reranking_score: document.reranking_score,
+            extra: "{}".to_string(),
+        }
+    }
+}
+
+impl BincodeDocument {
+    fn new(document: &Document) -> Result<Self, BinaryError> {
+        let to_json = |value| serde_json::to_string(value).map_err(|e| BinaryError::Encode(e.to_string()));
+        Ok(BincodeDocument {
+            content: document.content.clone(),
+            id: document.id.clone(),
+            name: document.name.clone(),
+            meta_data: to_json(&document.meta_data)?,
+            embedding: document.embedding.clone(),
+            usage: document.usage.as_ref().map(to_json).transpose()?,
+            reranking_score: document.reranking_score,
+            extra: to_json(&document.versioned_extra())?,
+        })
+    }
+
+    fn into_document(self) -> Result<Document, BinaryError> {
+        let from_json = |text: &str| serde_json::from_str(text).map_err(|e| BinaryError::Decode(e.to_string()));
+        let mut document = Document {
+            meta_data: from_json(&self.meta_data)?,
+            usage: self.usage.as_deref().map(from_json).transpose()?,
+            content: self.content,
+            id: self.id,
+            name: self.name,
+            embedding: self.embedding,
+            reranking_score: self.reranking_score,
+            ..Default::default()
+        };
+        document
+            .set_versioned_extra(from_json(&self.extra)?)
+            .map_err(|e| BinaryError::Decode(e.to_string()))?;
+        Ok(document)
+    }
+}
+
+impl Document {
+    /// Encodes the document in `format`, behind the version header.
+    pub fn to_bytes(&self, format: BinaryFormat) -> Result<Vec<u8>, BinaryError> {
+        let mut bytes = vec![BINARY_VERSION, format.tag()];
+        match format {
+            BinaryFormat::MessagePack => rmp_serde::encode::write_named(&mut bytes, self)
+                .map_err(|e| BinaryError::Encode(e.to_string()))?,
+            BinaryFormat::Bincode => bincode::serialize_into(&mut bytes, &BincodeDocument::new(self)?)
+                .map_err(|e| BinaryError::Encode(e.to_string()))?,
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a document encoded by [`Document::to_bytes`], in any format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BinaryError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(BinaryError::Truncated);
+        }
+        let (version, format, payload) = (bytes[0], bytes[1], &bytes[HEADER_LEN..]);
+        if version == 0 || version > BINARY_VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+        match BinaryFormat::from_tag(format)? {
+            BinaryFormat::MessagePack => {
+                rmp_serde::from_slice(payload).map_err(|e| BinaryError::Decode(e.to_string()))
+            }
+            BinaryFormat::Bincode => {
+                let document = match version {
+                    1 => bincode::deserialize::<BincodeDocumentV1>(payload).map(BincodeDocument::from),
+                    _ => bincode::deserialize::<BincodeDocument>(payload),
+                };
+                document.map_err(|e| BinaryError::Decode(e.to_string()))?.into_document()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn document() -> Document {
+        let mut meta_data = HashMap::new();
+        meta_data.insert("page".to_string(), serde_json::json!(3));
+        meta_data.insert("tags".to_string(), serde_json::json!(["a", {"b": null}]));
+        let mut usage = HashMap::new();
+        usage.insert("tokens".to_string(), serde_json::json!(12));
+        Document {
+            content: "Binary content".to_string(),
+            id: Some("doc-1".to_string()),
+            meta_data,
+            embedding: Some((0..256).map(|i| i as f32 / 7.0).collect()),
+            usage: Some(usage),
+            reranking_score: Some(0.5),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip_is_smaller_than_json() {
+        let document = document();
+        let json_len = serde_json::to_vec(&document).unwrap().len();
+        for format in [BinaryFormat::MessagePack, BinaryFormat::Bincode] {
+            let bytes = document.to_bytes(format).unwrap();
+            assert_eq!(&bytes[..2], &[BINARY_VERSION, format.tag()]);
+            assert!(bytes.len() < json_len, "{:?}: {} vs {}", format, bytes.len(), json_len);
+            assert_eq!(Document::from_bytes(&bytes).unwrap(), document);
+            assert_eq!(format.as_str().parse::<BinaryFormat>().unwrap(), format);
+        }
+
+        let minimal = Document { embedding: None, usage: None, meta_data: HashMap::new(), ..document };
+        for format in [BinaryFormat::MessagePack, BinaryFormat::Bincode] {
+            assert_eq!(Document::from_bytes(&minimal.to_bytes(format).unwrap()).unwrap(), minimal);
+        }
+    }
+
+    #[test]
+    fn test_version_1_bincode_still_decodes() {
+        /// Bincode payload of `{content: "v1", meta_data: {}}` in layout version 1.
+        fn v1_bytes() -> Vec<u8> {
+            #[derive(Serialize)]
+            struct V1<'a>(&'a str, Option<()>, Option<()>, &'a str, Option<()>, Option<()>, Option<()>);
+            let mut bytes = vec![1, BinaryFormat::Bincode.tag()];
+            bincode::serialize_into(&mut bytes, &V1("v1", None, None, "{}", None, None, None)).unwrap();
+            bytes
+        }
+        let document = Document::from_bytes(&v1_bytes()).unwrap();
+        assert_eq!(document.content, "v1");
+        assert!(document.meta_data.is_empty() && document.extra.is_empty());
+
+        let mut newer = document.clone();
+        newer.extra.insert("created_at".to_string(), serde_json::json!(1700000000));
+        newer.schema_version = Some(9);
+        for format in [BinaryFormat::MessagePack, BinaryFormat::Bincode] {
+            assert_eq!(Document::from_bytes(&newer.to_bytes(format).unwrap()).unwrap(), newer);
+        }
+    }
+
+    #[test]
+    fn test_bad_headers() {
+        assert_eq!(Document::from_bytes(&[1]), Err(BinaryError::Truncated));
+        assert_eq!(Document::from_bytes(&[9, 0]), Err(BinaryError::UnsupportedVersion(9)));
+        assert!(matches!(Document::from_bytes(&[1, 7]), Err(BinaryError::UnknownFormat(_))));
+        assert!(matches!(Document::from_bytes(&[1, 0, 0xc1]), Err(BinaryError::Decode(_))));
+        assert!(matches!("json".parse::<BinaryFormat>(), Err(BinaryError::UnknownFormat(_))));
+    }
+}
@@ -0,0 +1,321 @@
+//! Serialization of whole document batches, as a JSON array or as JSON Lines
+//! (one document per line), in one call instead of one per document.
+
+use crate::Document;
+use pyo3::exceptions::{PyFileNotFoundError, PyIOError, PyValueError};
+use pyo3::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// Define a custom error type for bulk serialization
+#[derive(Debug, Clone, PartialEq)]
+pub enum BulkError {
+    FileNotFound(String),
+    Io(String),
+    Serialize(String),
+    /// Bad input at a 1-based line and column.
+    Parse { line: usize, column: usize, message: String },
+}
+
+impl fmt::Display for BulkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulkError::FileNotFound(path) => write!(f, "Could not find file: {}", path),
+            BulkError::Io(msg) => write!(f, "I/O error: {}", msg),
+            BulkError::Serialize(msg) => write!(f, "Serialization error: {}", msg),
+            BulkError::Parse { line, column, message } => {
+                write!(f, "Parse error at line {}, column {}: {}", line, column, message)
+            }
+        }
+    }
+}
+
+impl Error for BulkError {}
+
+impl From<io::Error> for BulkError {
+    fn from(err: io::Error) -> Self {
+        BulkError::Io(err.to_string())
+    }
+}
+
+impl From<BulkError> for PyErr {
+    fn from(err: BulkError) -> PyErr {
+        match err {
+            BulkError::FileNotFound(_) => PyFileNotFoundError::new_err(err.to_string()),
+            BulkError::Io(_) => PyIOError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// How a batch of documents is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// A single JSON array of documents.
+    Json,
+    /// One JSON document per line; blank lines are ignored when parsing.
+    JsonLines,
+}
+
+impl DocumentFormat {
+    /// Picks the format from a file extension: `.jsonl` and `.ndjson` are
+    /// JSON Lines, anything else a JSON array.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("ndjson") => {
+                DocumentFormat::JsonLines
+            }
+            _ => DocumentFormat::Json,
+        }
+    }
+
+    fn from_lines_flag(lines: Option<bool>, path: &Path) -> Self {
+        match lines {
+            Some(true) => DocumentFormat::JsonLines,
+            Some(false) => DocumentFormat::Json,
+            None => DocumentFormat::from_path(path),
+        }
+    }
+}
+
+fn parse_error(err: serde_json::Error, line: usize) -> BulkError {
+    // serde_json appends " at line L column C" to its messages; the position
+    // is reported separately, and JSON Lines count lines across the whole input.
+    let message = err.to_string();
+    let message = match message.rfind(" at line ") {
+        Some(end) => message[..end].to_string(),
+        None => message,
+    };
+    BulkError::Parse { line, column: err.column(), message }
+}
+
+/// Writes `documents` to `writer` in `format`.
+pub fn write_to<W: Write>(writer: W, documents: &[Document], format: DocumentFormat) -> Result<(), BulkError> {
+    let mut writer = BufWriter::new(writer);
+    match format {
+        DocumentFormat::Json => {
+            serde_json::to_writer(&mut writer, documents).map_err(|e| BulkError::Serialize(e.to_string()))?
+        }
+        DocumentFormat::JsonLines => {
+            for document in documents {
+                serde_json::to_writer(&mut writer, document).map_err(|e| BulkError::Serialize(e.to_string()))?;
+                writer.write_all(b"\n")?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads documents in `format` from `reader`.
+///
+/// Parse errors carry the line and column of the bad input.
+pub fn read_from<R: Read>(reader: R, format: DocumentFormat) -> Result<Vec<Document>, BulkError> {
+    let mut reader = BufReader::new(reader);
+    match format {
+        DocumentFormat::Json => {
+            let mut data = String::new();
+            reader.read_to_string(&mut data)?;
+            from_str(&data, format)
+        }
+        DocumentFormat::JsonLines => {
+            let mut documents = Vec::new();
+            for (index, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                documents.push(serde_json::from_str(&line).map_err(|e| parse_error(e, index + 1))?);
+            }
+            Ok(documents)
+        }
+    }
+}
+
+/// Serializes `documents` to a string in `format`.
+pub fn to_string(documents: &[Document], format: DocumentFormat) -> Result<String, BulkError> {
+    let mut buffer = Vec::new();
+    write_to(&mut buffer, documents, format)?;
+    // serde_json only writes valid UTF-8.
+    String::from_utf8(buffer).map_err(|e| BulkError::Serialize(e.to_string()))
+}
+
+/// Parses documents in `format` from `data`.
+pub fn from_str(data: &str, format: DocumentFormat) -> Result<Vec<Document>, BulkError> {
+    match format {
+        DocumentFormat::Json => serde_json::from_str(data).map_err(|e| {
+            let line = e.line();
+            parse_error(e, line)
+        }),
+        DocumentFormat::JsonLines => read_from(data.as_bytes(), format),
+    }
+}
+
+/// Writes `documents` to the file at `path` in `format`, replacing it.
+pub fn write_file(path: &Path, documents: &[Document], format: DocumentFormat) -> Result<(), BulkError> {
+    write_to(File::create(path)?, documents, format)
+}
+
+/// Reads the documents of the file at `path` in `format`.
+pub fn read_file(path: &Path, format: DocumentFormat) -> Result<Vec<Document>, BulkError> {
+    let file = File::open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => BulkError::FileNotFound(path.display().to_string()),
+        _ => BulkError::from(e),
+    })?;
+    read_from(file, format)
+}
+
+fn lines_format(lines: bool) -> DocumentFormat {
+    if lines {
+        DocumentFormat::JsonLines
+    } else {
+        DocumentFormat::Json
+    }
+}
+
+/// Serializes many documents in one call, releasing the GIL while working.
+///
+/// Args:
+///     documents (List[Document]): The documents to serialize.
+///     lines (bool): If True, writes JSON Lines instead of a JSON array. Defaults to False.
+///
+/// Returns:
+///     str: The serialized documents.
+///
+/// Raises:
+///     PyValueError: If serialization fails.
+#[pyfunction]
+#[pyo3(name = "documents_to_json", signature = (documents, lines=false))]
+pub fn documents_to_json_py(py: Python<'_>, documents: Vec<Document>, lines: bool) -> PyResult<String> {
+    Ok(py.allow_threads(|| to_string(&documents, lines_format(lines)))?)
+}
+
+/// Parses many documents in one call, releasing the GIL while working.
+///
+/// Args:
+///     data (str): A JSON array of documents, or JSON Lines.
+///     lines (bool): If True, parses JSON Lines instead of a JSON array. Defaults to False.
+///
+/// Returns:
+///     List[Document]: The parsed documents.
+///
+/// Raises:
+///     PyValueError: If the input is malformed; the message names the line and column.
+#[pyfunction]
+#[pyo3(name = "documents_from_json", signature = (data, lines=false))]
+pub fn documents_from_json_py(py: Python<'_>, data: &str, lines: bool) -> PyResult<Vec<Document>> {
+    Ok(py.allow_threads(|| from_str(data, lines_format(lines)))?)
+}
+
+/// Writes many documents to a file, releasing the GIL while working.
+///
+/// Args:
+///     path (Union[str, os.PathLike]): The file to write; it is replaced if it exists.
+///     documents (List[Document]): The documents to write.
+///     lines (Optional[bool]): If True, writes JSON Lines; if False, a JSON array. Defaults to
+///         JSON Lines for `.jsonl` and `.ndjson` files and a JSON array otherwise.
+///
+/// Raises:
+///     PyIOError: If the file cannot be written.
+///     PyValueError: If serialization fails.
+#[pyfunction]
+#[pyo3(name = "write_documents", signature = (path, documents, lines=None))]
+pub fn write_documents_py(
+    py: Python<'_>,
+    path: std::path::PathBuf,
+    documents: Vec<Document>,
+    lines: Option<bool>,
+) -> PyResult<()> {
+    let format = DocumentFormat::from_lines_flag(lines, &path);
+    Ok(py.allow_threads(|| write_file(&path, &documents, format))?)
+}
+
+/// Reads many documents from a file, releasing the GIL while working.
+///
+/// Args:
+///     path (Union[str, os.PathLike]): The file to read.
+///     lines (Optional[bool]): If True, reads JSON Lines; if False, a JSON array. Defaults to
+///         JSON Lines for `.jsonl` and `.ndjson` files and a JSON array otherwise.
+///
+/// Returns:
+///     List[Document]: The documents of the file.
+///
+/// Raises:
+///     PyFileNotFoundError: If the file does not exist.
+///     PyIOError: If the file cannot be read.
+///     PyValueError: If the file is malformed; the message names the line and column.
+#[pyfunction]
+#[pyo3(name = "read_documents", signature = (path, lines=None))]
+pub fn read_documents_py(py: Python<'_>, path: std::path::PathBuf, lines: Option<bool>) -> PyResult<Vec<Document>> {
+    let format = DocumentFormat::from_lines_flag(lines, &path);
+    Ok(py.allow_threads(|| read_file(&path, format))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn doc(id: &str) -> Document {
+        let mut meta_data = HashMap::new();
+        meta_data.insert("page".to_string(), serde_json::json!(1));
+        Document {
+            content: format!("content of {}\nsecond line", id),
+            id: Some(id.to_string()),
+            meta_data,
+            embedding: Some(vec![0.5, 1.0]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_round_trip_both_formats() {
+        let documents = vec![doc("a"), doc("b")];
+        for format in [DocumentFormat::Json, DocumentFormat::JsonLines] {
+            let data = to_string(&documents, format).unwrap();
+            assert_eq!(from_str(&data, format).unwrap(), documents);
+        }
+        let lines = to_string(&documents, DocumentFormat::JsonLines).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert_eq!(from_str("", DocumentFormat::JsonLines).unwrap(), vec![]);
+        assert_eq!(to_string(&[], DocumentFormat::Json).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_line() {
+        let data = "{\"content\":\"a\"}\n\n{\"content\":\"b\",}\n";
+        match from_str(data, DocumentFormat::JsonLines).unwrap_err() {
+            BulkError::Parse { line, column, message } => {
+                assert_eq!((line, column), (3, 16));
+                assert!(!message.contains("at line"), "{}", message);
+            }
+            err => panic!("unexpected error {:?}", err),
+        }
+
+        let data = "[\n  {\"content\": \"a\"},\n  {\"id\": \"b\"}\n]";
+        let err = from_str(data, DocumentFormat::Json).unwrap_err();
+        assert!(matches!(err, BulkError::Parse { line: 3, .. }), "{:?}", err);
+        assert!(err.to_string().contains("missing field `content`"), "{}", err);
+    }
+
+    #[test]
+    fn test_files_and_format_from_path() {
+        let dir = std::env::temp_dir().join(format!("document_bulk_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("docs.jsonl");
+        assert_eq!(DocumentFormat::from_path(&path), DocumentFormat::JsonLines);
+        assert_eq!(DocumentFormat::from_path(&dir.join("docs.json")), DocumentFormat::Json);
+
+        let documents = vec![doc("a"), doc("b"), doc("c")];
+        write_file(&path, &documents, DocumentFormat::JsonLines).unwrap();
+        assert_eq!(read_file(&path, DocumentFormat::JsonLines).unwrap(), documents);
+        assert!(matches!(
+            read_file(&dir.join("missing.json"), DocumentFormat::Json),
+            Err(BulkError::FileNotFound(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use super::{ChunkingError, ChunkingStrategy, CodeChunking};
+use crate::Document;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// The outcome of chunking one document of a batch.
+pub type ChunkResult = Result<Vec<Document>, ChunkingError>;
+
+/// Chunks many documents in parallel on the global rayon thread pool.
+///
+/// Results are returned in input order, one per document. A document that
+/// fails (or whose strategy panics) yields an `Err` without affecting the
+/// rest of the batch.
+pub fn chunk_batch<S>(strategy: &S, documents: &[Document]) -> Vec<ChunkResult>
+where
+    S: ChunkingStrategy + Sync + ?Sized,
+{
+    documents
+        .par_iter()
+        .map(|document| chunk_one(strategy, document))
+        .collect()
+}
+
+/// Like `chunk_batch`, but on a dedicated pool of `num_threads` threads.
+pub fn chunk_batch_with_threads<S>(
+    strategy: &S,
+    documents: &[Document],
+    num_threads: usize,
+) -> Result<Vec<ChunkResult>, ChunkingError>
+where
+    S: ChunkingStrategy + Sync + ?Sized,
+{
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| ChunkingError::InvalidParameters(e.to_string()))?;
+    Ok(pool.install(|| chunk_batch(strategy, documents)))
+}
+
+fn chunk_one<S>(strategy: &S, document: &Document) -> ChunkResult
+where
+    S: ChunkingStrategy + ?Sized,
+{
+    panic::catch_unwind(AssertUnwindSafe(|| strategy.chunk(document))).unwrap_or_else(|payload| {
+        Err(ChunkingError::ChunkingFailed(format!(
+            "strategy panicked on document {:?}: {}",
+            document.id,
+            panic_message(payload.as_ref())
+        )))
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// Chunks many documents in parallel, releasing the GIL while working.
+///
+/// Args:
+///     strategy (CodeChunking): The chunking strategy to apply.
+///     documents (List[Document]): The documents to chunk.
+///     num_threads (Optional[int]): Size of a dedicated thread pool. Defaults to the shared pool.
+///
+/// Returns:
+///     List[Union[List[Document], ValueError]]: One entry per input document, in
+///     input order: its chunks, or the error raised while chunking it.
+///
+/// Raises:
+///     PyValueError: If the thread pool cannot be created.
+#[pyfunction]
+#[pyo3(name = "chunk_documents", signature = (strategy, documents, num_threads=None))]
+pub fn chunk_documents_py(
+    py: Python<'_>,
+    strategy: &CodeChunking,
+    documents: Vec<Document>,
+    num_threads: Option<usize>,
+) -> PyResult<Vec<PyObject>> {
+    let results = py.allow_threads(|| match num_threads {
+        Some(num_threads) => chunk_batch_with_threads(strategy, &documents, num_threads),
+        None => Ok(chunk_batch(strategy, &documents)),
+    })?;
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(chunks) => chunks.into_py(py),
+            Err(err) => PyErr::from(err).into_value(py).into_py(py),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::doc;
+
+    /// Splits on spaces; fails on empty content and panics on "panic".
+    struct WordChunking;
+
+    impl ChunkingStrategy for WordChunking {
+        fn chunk(&self, document: &Document) -> Result<Vec<Document>, ChunkingError> {
+            if document.content.is_empty() {
+                return Err(ChunkingError::ChunkingFailed("empty document".to_string()));
+            }
+            if document.content == "panic" {
+                panic!("boom");
+            }
+            Ok(document
+                .content
+                .split(' ')
+                .map(|word| Document {
+                    content: word.to_string(),
+                    id: document.id.clone(),
+                    ..Default::default()
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_chunk_batch_preserves_order() {
+        let documents: Vec<Document> = (0..200).map(|i| doc(&i.to_string(), &format!("doc {} words", i))).collect();
+        let results = chunk_batch(&WordChunking, &documents);
+        assert_eq!(results.len(), documents.len());
+        for (i, result) in results.into_iter().enumerate() {
+            let chunks = result.unwrap();
+            assert_eq!(chunks.len(), 3);
+            assert_eq!(chunks[1].content, i.to_string());
+            assert_eq!(chunks[0].id, Some(i.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_chunk_batch_reports_per_document_errors() {
+        // The panic of document 2 is printed to stderr by the default hook.
+        let documents = vec![doc("0", "a b"), doc("1", ""), doc("2", "panic"), doc("3", "c")];
+        let results = chunk_batch_with_threads(&WordChunking, &documents, 2).unwrap();
+
+        assert_eq!(results[0].as_ref().unwrap().len(), 2);
+        assert_eq!(results[1], Err(ChunkingError::ChunkingFailed("empty document".to_string())));
+        match &results[2] {
+            Err(ChunkingError::ChunkingFailed(msg)) => assert!(msg.contains("boom")),
+            other => panic!("expected a chunking failure, got {:?}", other),
+        }
+        assert_eq!(results[3].as_ref().unwrap()[0].content, "c");
+    }
+
+    #[test]
+    fn test_chunk_batch_with_code_chunking() {
+        let documents = vec![
+            Document {
+                name: Some("a.py".to_string()),
+                ..doc("0", "def a():\n    pass\n\ndef b():\n    pass\n")
+            },
+            Document {
+                name: Some("b.rs".to_string()),
+                ..doc("1", "fn c() {}\n")
+            },
+        ];
+        let results = chunk_batch(&CodeChunking::default(), &documents);
+        assert_eq!(results[0].as_ref().unwrap().len(), 2);
+        assert_eq!(results[1].as_ref().unwrap()[0].content, "fn c() {}");
+        assert!(chunk_batch_with_threads(&CodeChunking::default(), &documents, 1).is_ok());
+    }
+}
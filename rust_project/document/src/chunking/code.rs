@@ -0,0 +1,1317 @@
+use super::{ChunkBuilder, ChunkingError, ChunkingStrategy};
+use crate::Document;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Programming languages understood by the structural parser of `CodeChunking`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Java,
+}
+
+impl Language {
+    /// Returns the lowercase name stored in `meta_data["language"]`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Go => "go",
+            Language::Java => "java",
+        }
+    }
+
+    /// Parses a language name or common alias (e.g. `"rs"`, `"py"`, `"ts"`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(Language::Rust),
+            "python" | "py" => Some(Language::Python),
+            "javascript" | "js" => Some(Language::JavaScript),
+            "typescript" | "ts" => Some(Language::TypeScript),
+            "go" | "golang" => Some(Language::Go),
+            "java" => Some(Language::Java),
+            _ => None,
+        }
+    }
+
+    /// Maps a file extension (without the leading dot) to a language.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            "go" => Some(Language::Go),
+            "java" => Some(Language::Java),
+            _ => None,
+        }
+    }
+
+    /// Detects the language from a file name or path.
+    pub fn from_path(path: &str) -> Option<Self> {
+        Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Language::from_extension)
+    }
+
+    fn symbol_separator(&self) -> &'static str {
+        match self {
+            Language::Rust => "::",
+            _ => ".",
+        }
+    }
+
+    fn syntax(&self) -> Syntax {
+        match self {
+            Language::Rust => Syntax {
+                line_comment: "//",
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"'],
+                backtick_escapes: None,
+                multiline_quotes: true,
+                triple_quotes: false,
+                rust_literals: true,
+            },
+            Language::Python => Syntax {
+                line_comment: "#",
+                block_comment: None,
+                quotes: &['"', '\''],
+                backtick_escapes: None,
+                multiline_quotes: false,
+                triple_quotes: true,
+                rust_literals: false,
+            },
+            Language::JavaScript | Language::TypeScript => Syntax {
+                line_comment: "//",
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"', '\''],
+                backtick_escapes: Some(true),
+                multiline_quotes: false,
+                triple_quotes: false,
+                rust_literals: false,
+            },
+            Language::Go => Syntax {
+                line_comment: "//",
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"', '\''],
+                backtick_escapes: Some(false),
+                multiline_quotes: false,
+                triple_quotes: false,
+                rust_literals: false,
+            },
+            Language::Java => Syntax {
+                line_comment: "//",
+                block_comment: Some(("/*", "*/")),
+                quotes: &['"', '\''],
+                backtick_escapes: None,
+                multiline_quotes: false,
+                triple_quotes: true,
+                rust_literals: false,
+            },
+        }
+    }
+}
+
+/// Chunking strategy for source code.
+///
+/// Splits a source file along its top-level definitions (functions, classes,
+/// impl blocks, ...) using a lightweight structural parser that tracks
+/// brackets, strings and comments (or indentation for Python). Leading doc
+/// comments, attributes and decorators stay with the definition they precede.
+/// Definitions larger than `max_chunk_size` are split along their members
+/// when they are containers (classes, impl blocks, traits), otherwise by lines.
+///
+/// Files in unknown languages, or files the parser cannot balance, fall back
+/// to line-based windows of `window_lines` lines overlapping by `overlap_lines`.
+///
+/// Each chunk is an exact slice of the source and records `language`,
+/// `symbol`, `symbol_kind`, `start_line` and `end_line` (1-based, inclusive)
+/// in its `meta_data`, alongside its `ChunkProvenance` and the `chunk` and
+/// `chunk_size` keys written by the Python strategies.
+///
+/// The language is taken from the strategy itself, then from
+/// `meta_data["language"]`, then from the extension of the document name.
+#[pyclass(name = "CodeChunking")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeChunking {
+    max_chunk_size: usize,
+    window_lines: usize,
+    overlap_lines: usize,
+    language: Option<Language>,
+}
+
+impl Default for CodeChunking {
+    fn default() -> Self {
+        CodeChunking {
+            max_chunk_size: 5000,
+            window_lines: 50,
+            overlap_lines: 5,
+            language: None,
+        }
+    }
+}
+
+impl CodeChunking {
+    /// Creates a new `CodeChunking` strategy.
+    ///
+    /// Returns an error if any size is zero or if `overlap_lines` is not
+    /// smaller than `window_lines`.
+    pub fn new(max_chunk_size: usize, window_lines: usize, overlap_lines: usize) -> Result<Self, ChunkingError> {
+        if max_chunk_size == 0 || window_lines == 0 {
+            return Err(ChunkingError::InvalidParameters(
+                "max_chunk_size and window_lines must be greater than zero".to_string(),
+            ));
+        }
+        if overlap_lines >= window_lines {
+            return Err(ChunkingError::InvalidParameters(format!(
+                "overlap_lines ({}) must be less than window_lines ({})",
+                overlap_lines, window_lines
+            )));
+        }
+        Ok(CodeChunking {
+            max_chunk_size,
+            window_lines,
+            overlap_lines,
+            language: None,
+        })
+    }
+
+    /// Forces the language instead of detecting it from the document.
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    pub fn max_chunk_size(&self) -> usize {
+        self.max_chunk_size
+    }
+
+    pub fn window_lines(&self) -> usize {
+        self.window_lines
+    }
+
+    pub fn overlap_lines(&self) -> usize {
+        self.overlap_lines
+    }
+
+    /// The language forced by [`CodeChunking::with_language`], if any.
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
+    /// Resolves the language of `document`, if any is known.
+    pub fn detect_language(&self, document: &Document) -> Option<Language> {
+        self.language
+            .or_else(|| {
+                document
+                    .meta_data
+                    .get("language")
+                    .and_then(|value| value.as_str())
+                    .and_then(Language::from_name)
+            })
+            .or_else(|| document.name.as_deref().and_then(Language::from_path))
+    }
+
+    fn structural_segments(&self, language: Language, lines: &[&str]) -> Option<Vec<Segment>> {
+        let parser = Parser::new(language, lines, self.max_chunk_size)?;
+        Some(parser.segments())
+    }
+
+    fn window_segments(&self, line_count: usize) -> Vec<Segment> {
+        let step = self.window_lines - self.overlap_lines;
+        let mut segments = Vec::new();
+        let mut start = 0;
+        while start < line_count {
+            let end = (start + self.window_lines).min(line_count) - 1;
+            segments.push(Segment::glue(start, end, None));
+            if end + 1 >= line_count {
+                break;
+            }
+            start += step;
+        }
+        segments
+    }
+
+    /// Splits an oversized segment into consecutive line runs of at most
+    /// `max_chunk_size` characters each (a single longer line is kept whole).
+    fn split_by_size(&self, lines: &[&str], start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut parts = Vec::new();
+        let mut part_start = start;
+        let mut size = 0;
+        for (index, line) in lines.iter().enumerate().take(end + 1).skip(start) {
+            let line_size = line.chars().count() + 1;
+            if index > part_start && size + line_size > self.max_chunk_size {
+                parts.push((part_start, index - 1));
+                part_start = index;
+                size = 0;
+            }
+            size += line_size;
+        }
+        parts.push((part_start, end));
+        parts
+    }
+}
+
+impl ChunkingStrategy for CodeChunking {
+    fn chunk(&self, document: &Document) -> Result<Vec<Document>, ChunkingError> {
+        let spans = line_spans(&document.content);
+        let lines: Vec<&str> = spans.iter().map(|&(start, end)| &document.content[start..end]).collect();
+        let language = self.detect_language(document);
+        let segments = language
+            .and_then(|language| self.structural_segments(language, &lines))
+            .unwrap_or_else(|| self.window_segments(lines.len()));
+
+        let mut builder = ChunkBuilder::new(document);
+        for segment in segments {
+            let Some((start, end)) = trim_blank_lines(&lines, segment.start, segment.end) else {
+                continue;
+            };
+            let parts = self.split_by_size(&lines, start, end);
+            let part_count = parts.len();
+            for (part_index, (part_start, part_end)) in parts.into_iter().enumerate() {
+                let mut meta_data = HashMap::new();
+                if let Some(language) = language {
+                    meta_data.insert("language".to_string(), json!(language.as_str()));
+                }
+                if let Some(symbol) = &segment.symbol {
+                    meta_data.insert("symbol".to_string(), json!(symbol));
+                }
+                if let Some(kind) = segment.kind {
+                    meta_data.insert("symbol_kind".to_string(), json!(kind));
+                }
+                if part_count > 1 {
+                    meta_data.insert("part".to_string(), json!(part_index + 1));
+                }
+                meta_data.insert("start_line".to_string(), json!(part_start + 1));
+                meta_data.insert("end_line".to_string(), json!(part_end + 1));
+                builder.push(spans[part_start].0, spans[part_end].1, meta_data);
+            }
+        }
+        Ok(builder.finish())
+    }
+}
+
+#[pymethods]
+impl CodeChunking {
+    /// Creates a new `CodeChunking` strategy.
+    ///
+    /// Args:
+    ///     max_chunk_size (int): Maximum number of characters per chunk. Defaults to 5000.
+    ///     window_lines (int): Lines per window when falling back to line-based chunking. Defaults to 50.
+    ///     overlap_lines (int): Lines shared by consecutive fallback windows. Defaults to 5.
+    ///     language (Optional[str]): Forces the language (e.g. "rust", "python") instead of detecting it.
+    ///
+    /// Raises:
+    ///     PyValueError: If the sizes are invalid or the language is unknown.
+    #[new]
+    #[pyo3(signature = (max_chunk_size=5000, window_lines=50, overlap_lines=5, language=None))]
+    fn py_new(
+        max_chunk_size: usize,
+        window_lines: usize,
+        overlap_lines: usize,
+        language: Option<&str>,
+    ) -> PyResult<Self> {
+        let mut strategy = CodeChunking::new(max_chunk_size, window_lines, overlap_lines)?;
+        if let Some(name) = language {
+            let language = Language::from_name(name).ok_or_else(|| {
+                ChunkingError::InvalidParameters(format!("unsupported language: {}", name))
+            })?;
+            strategy = strategy.with_language(language);
+        }
+        Ok(strategy)
+    }
+
+    /// Splits a document into chunks along its top-level definitions.
+    ///
+    /// Args:
+    ///     document (Document): The document to chunk.
+    ///
+    /// Returns:
+    ///     List[Document]: The chunks, in source order.
+    #[pyo3(name = "chunk")]
+    fn chunk_py(&self, document: &Document) -> PyResult<Vec<Document>> {
+        Ok(self.chunk(document)?)
+    }
+}
+
+/// Byte ranges of each line of `content`, excluding the `\n` or `\r\n` terminator
+/// (the same lines as `str::lines`).
+fn line_spans(content: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for line in content.split_inclusive('\n') {
+        let text = line.strip_suffix('\n').unwrap_or(line);
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        spans.push((start, start + text.len()));
+        start += line.len();
+    }
+    spans
+}
+
+/// Returns the `(start, end)` range with leading and trailing blank lines removed,
+/// or `None` if every line in the range is blank.
+fn trim_blank_lines(lines: &[&str], mut start: usize, mut end: usize) -> Option<(usize, usize)> {
+    while start <= end && lines[start].trim().is_empty() {
+        start += 1;
+    }
+    while end > start && lines[end].trim().is_empty() {
+        end -= 1;
+    }
+    (start <= end).then_some((start, end))
+}
+
+// --- Structural parser ---
+
+/// Lexical rules needed to find brackets outside strings and comments.
+struct Syntax {
+    line_comment: &'static str,
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    /// `Some(escapes)` if backtick strings exist (JS templates, Go raw strings).
+    backtick_escapes: Option<bool>,
+    /// Whether `quotes` strings may span several lines.
+    multiline_quotes: bool,
+    triple_quotes: bool,
+    rust_literals: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScanState {
+    Code,
+    BlockComment,
+    Str { delim: char, escapes: bool, multiline: bool },
+    RawStr { hashes: usize },
+    TripleStr(char),
+}
+
+#[derive(Debug, Clone, Default)]
+struct LineInfo {
+    /// Bracket depth at the start of the line.
+    depth_start: usize,
+    /// Bracket depth at the end of the line.
+    depth_end: usize,
+    /// The line starts inside a block comment or a multi-line string.
+    literal_at_start: bool,
+    /// Smallest depth at which a `{` was opened on this line.
+    brace_open_depth: Option<usize>,
+    /// Last character of code (outside strings and comments) on the line.
+    last_code_char: Option<char>,
+}
+
+/// A contiguous range of lines (inclusive) that becomes one chunk.
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    start: usize,
+    end: usize,
+    symbol: Option<String>,
+    kind: Option<&'static str>,
+    /// Code between definitions (imports, module docs, container headers).
+    glue: bool,
+}
+
+impl Segment {
+    fn glue(start: usize, end: usize, parent: Option<&Symbol>) -> Self {
+        Segment {
+            start,
+            end,
+            symbol: parent.map(|symbol| symbol.name.clone()),
+            kind: parent.map(|symbol| symbol.kind),
+            glue: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Symbol {
+    name: String,
+    kind: &'static str,
+}
+
+impl Symbol {
+    fn new(kind: &'static str, name: &str) -> Option<Self> {
+        (!name.is_empty()).then(|| Symbol {
+            name: name.to_string(),
+            kind,
+        })
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self.kind, "impl" | "trait" | "module" | "class" | "namespace" | "interface" | "record")
+    }
+}
+
+struct Parser<'a> {
+    language: Language,
+    lines: &'a [&'a str],
+    info: Vec<LineInfo>,
+    max_chunk_size: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Scans the source, returning `None` if brackets, strings or comments are unbalanced.
+    fn new(language: Language, lines: &'a [&'a str], max_chunk_size: usize) -> Option<Self> {
+        let info = scan(lines, &language.syntax())?;
+        Some(Parser {
+            language,
+            lines,
+            info,
+            max_chunk_size,
+        })
+    }
+
+    fn segments(&self) -> Vec<Segment> {
+        let segments = self.segment(0, self.lines.len(), 0, None);
+        self.merge_glue(segments)
+    }
+
+    fn is_blank(&self, line: usize) -> bool {
+        self.lines[line].trim().is_empty()
+    }
+
+    fn indent(&self, line: usize) -> usize {
+        let text = self.lines[line];
+        text.len() - text.trim_start().len()
+    }
+
+    fn is_comment(&self, line: usize) -> bool {
+        let trimmed = self.lines[line].trim_start();
+        match self.language {
+            Language::Python => trimmed.starts_with('#'),
+            _ => trimmed.starts_with("//") || trimmed.starts_with("/*") || trimmed.starts_with('*'),
+        }
+    }
+
+    /// Lines that stay attached to the definition that follows them.
+    fn is_attachable(&self, line: usize) -> bool {
+        let trimmed = self.lines[line].trim_start();
+        if self.is_comment(line) {
+            return true;
+        }
+        match self.language {
+            Language::Rust => trimmed.starts_with("#["),
+            Language::Python | Language::Java | Language::JavaScript | Language::TypeScript => {
+                trimmed.starts_with('@')
+            }
+            Language::Go => false,
+        }
+    }
+
+    fn segment_size(&self, start: usize, end: usize) -> usize {
+        self.lines[start..=end].iter().map(|line| line.chars().count() + 1).sum()
+    }
+
+    /// Returns the definition starting on `line` at nesting `level`
+    /// (bracket depth, or indentation width for Python).
+    fn definition_at(&self, line: usize, level: usize, nested: bool) -> Option<Symbol> {
+        let info = &self.info[line];
+        if info.literal_at_start || self.is_blank(line) {
+            return None;
+        }
+        let top_level = match self.language {
+            Language::Python => info.depth_start == 0 && self.indent(line) == level,
+            _ => info.depth_start == level,
+        };
+        if !top_level {
+            return None;
+        }
+        detect_definition(self.language, self.lines[line].trim(), nested)
+    }
+
+    fn segment(&self, from: usize, to: usize, level: usize, parent: Option<&Symbol>) -> Vec<Segment> {
+        let mut segments = Vec::new();
+        let mut glue_start = from;
+        let mut line = from;
+        while line < to {
+            let Some(definition) = self.definition_at(line, level, parent.is_some()) else {
+                line += 1;
+                continue;
+            };
+            let end = self.definition_end(line, to, level);
+            let start = self.attach_leading(line, glue_start);
+            if start > glue_start {
+                segments.push(Segment::glue(glue_start, start - 1, parent));
+            }
+            let symbol = self.qualify(parent, definition);
+            if symbol.is_container() && self.segment_size(start, end) > self.max_chunk_size {
+                segments.extend(self.expand(start, line, end, level, &symbol));
+            } else {
+                segments.push(Segment {
+                    start,
+                    end,
+                    symbol: Some(symbol.name),
+                    kind: Some(symbol.kind),
+                    glue: false,
+                });
+            }
+            line = end + 1;
+            glue_start = line;
+        }
+        if glue_start < to {
+            segments.push(Segment::glue(glue_start, to - 1, parent));
+        }
+        segments
+    }
+
+    fn qualify(&self, parent: Option<&Symbol>, symbol: Symbol) -> Symbol {
+        match parent {
+            Some(parent) => Symbol {
+                name: format!("{}{}{}", parent.name, self.language.symbol_separator(), symbol.name),
+                kind: match (symbol.kind, parent.kind) {
+                    ("function", "module" | "namespace") => "function",
+                    ("function", _) => "method",
+                    (kind, _) => kind,
+                },
+            },
+            None => symbol,
+        }
+    }
+
+    /// Walks back from a definition over doc comments, attributes and decorators.
+    fn attach_leading(&self, definition: usize, floor: usize) -> usize {
+        let mut start = definition;
+        let mut line = definition;
+        while line > floor {
+            line -= 1;
+            if self.is_blank(line) {
+                break;
+            }
+            if self.is_attachable(line) {
+                start = line;
+                continue;
+            }
+            // Continuation lines of a multi-line attribute or decorator.
+            if self.info[line].depth_start > self.info[definition].depth_start {
+                let mut head = line;
+                while head > floor && self.info[head].depth_start > self.info[definition].depth_start {
+                    head -= 1;
+                }
+                if self.is_attachable(head) && !self.is_blank(head) {
+                    start = head;
+                    line = head;
+                    continue;
+                }
+            }
+            break;
+        }
+        start
+    }
+
+    fn definition_end(&self, line: usize, to: usize, level: usize) -> usize {
+        if self.language == Language::Python {
+            return self.python_definition_end(line, to, level);
+        }
+        let mut opened_brace = false;
+        for current in line..to {
+            let info = &self.info[current];
+            if info.brace_open_depth.is_some_and(|depth| depth <= level) {
+                opened_brace = true;
+            }
+            if info.depth_end > level {
+                continue;
+            }
+            let next_is_break = current + 1 >= to
+                || self.is_blank(current + 1)
+                || self.definition_at(current + 1, level, level > 0).is_some();
+            if info.depth_end < level || opened_brace || info.last_code_char == Some(';') || next_is_break {
+                return current;
+            }
+        }
+        to - 1
+    }
+
+    fn python_definition_end(&self, line: usize, to: usize, level: usize) -> usize {
+        let mut last = line;
+        for current in line + 1..to {
+            if self.is_blank(current) {
+                continue;
+            }
+            let info = &self.info[current];
+            if !info.literal_at_start
+                && info.depth_start == 0
+                && self.indent(current) <= level
+                && !self.is_comment(current)
+            {
+                break;
+            }
+            last = current;
+        }
+        // Comments right before the next definition belong to it.
+        while last > line
+            && (self.is_blank(last) || (self.is_comment(last) && self.indent(last) <= level))
+        {
+            last -= 1;
+        }
+        last
+    }
+
+    /// Splits an oversized container into its header and its members.
+    fn expand(&self, start: usize, line: usize, end: usize, level: usize, symbol: &Symbol) -> Vec<Segment> {
+        let body = match self.language {
+            Language::Python => self.python_body(line, end),
+            _ => self.brace_body(line, end, level),
+        };
+        let Some((header_end, body_start, body_end, body_level)) = body else {
+            return vec![Segment {
+                start,
+                end,
+                symbol: Some(symbol.name.clone()),
+                kind: Some(symbol.kind),
+                glue: false,
+            }];
+        };
+        let mut segments = vec![Segment::glue(start, header_end, Some(symbol))];
+        segments.extend(self.segment(body_start, body_end, body_level, Some(symbol)));
+        // The closing line of a brace container stays with its last member.
+        if let Some(last) = segments.last_mut() {
+            last.end = end;
+        }
+        segments
+    }
+
+    /// Returns `(header_end, body_start, body_end, body_level)` for a brace container.
+    fn brace_body(&self, line: usize, end: usize, level: usize) -> Option<(usize, usize, usize, usize)> {
+        let open = (line..=end).find(|&current| {
+            self.info[current]
+                .brace_open_depth
+                .is_some_and(|depth| depth <= level)
+        })?;
+        (open < end).then_some((open, open + 1, end, level + 1))
+    }
+
+    /// Returns `(header_end, body_start, body_end, body_level)` for a Python class.
+    fn python_body(&self, line: usize, end: usize) -> Option<(usize, usize, usize, usize)> {
+        let header_end = (line..=end).find(|&current| {
+            let info = &self.info[current];
+            info.depth_end == 0 && info.last_code_char == Some(':')
+        })?;
+        let first = (header_end + 1..=end)
+            .find(|&current| !self.is_blank(current) && !self.info[current].literal_at_start)?;
+        let body_level = self.indent(first);
+        (body_level > self.indent(line)).then_some((header_end, header_end + 1, end + 1, body_level))
+    }
+
+    /// Merges adjacent glue segments of the same container while they fit.
+    fn merge_glue(&self, segments: Vec<Segment>) -> Vec<Segment> {
+        let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+        for segment in segments {
+            if let Some(last) = merged.last_mut() {
+                if last.glue
+                    && segment.glue
+                    && last.symbol == segment.symbol
+                    && last.end + 1 == segment.start
+                    && self.segment_size(last.start, segment.end) <= self.max_chunk_size
+                {
+                    last.end = segment.end;
+                    continue;
+                }
+            }
+            merged.push(segment);
+        }
+        merged
+    }
+}
+
+fn starts_with_at(chars: &[char], index: usize, pattern: &str) -> bool {
+    (index..)
+        .zip(pattern.chars())
+        .all(|(offset, expected)| chars.get(offset) == Some(&expected))
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Tracks bracket depth line by line, skipping strings and comments.
+fn scan(lines: &[&str], syntax: &Syntax) -> Option<Vec<LineInfo>> {
+    let mut infos = Vec::with_capacity(lines.len());
+    let mut state = ScanState::Code;
+    let mut depth: usize = 0;
+    for line in lines {
+        let chars: Vec<char> = line.chars().collect();
+        let mut info = LineInfo {
+            depth_start: depth,
+            literal_at_start: state != ScanState::Code,
+            ..Default::default()
+        };
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            match state {
+                ScanState::BlockComment => {
+                    let (_, close) = syntax.block_comment.unwrap_or(("", ""));
+                    if starts_with_at(&chars, i, close) {
+                        state = ScanState::Code;
+                        i += close.chars().count();
+                        continue;
+                    }
+                }
+                ScanState::Str { delim, escapes, .. } => {
+                    if escapes && c == '\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if c == delim {
+                        state = ScanState::Code;
+                    }
+                }
+                ScanState::RawStr { hashes } => {
+                    if c == '"' && (1..=hashes).all(|k| chars.get(i + k) == Some(&'#')) {
+                        state = ScanState::Code;
+                        i += 1 + hashes;
+                        continue;
+                    }
+                }
+                ScanState::TripleStr(quote) => {
+                    if c == '\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote) && c == quote {
+                        state = ScanState::Code;
+                        i += 3;
+                        continue;
+                    }
+                }
+                ScanState::Code => {
+                    if starts_with_at(&chars, i, syntax.line_comment) {
+                        break;
+                    }
+                    if let Some((open, _)) = syntax.block_comment {
+                        if starts_with_at(&chars, i, open) {
+                            state = ScanState::BlockComment;
+                            i += open.chars().count();
+                            continue;
+                        }
+                    }
+                    if !c.is_whitespace() {
+                        info.last_code_char = Some(c);
+                    }
+                    if syntax.triple_quotes
+                        && (c == '"' || c == '\'')
+                        && chars.get(i + 1) == Some(&c)
+                        && chars.get(i + 2) == Some(&c)
+                    {
+                        state = ScanState::TripleStr(c);
+                        i += 3;
+                        continue;
+                    }
+                    if syntax.rust_literals {
+                        if let Some(next) = skip_rust_literal(&chars, i, &mut state) {
+                            i = next;
+                            continue;
+                        }
+                    }
+                    if syntax.quotes.contains(&c) {
+                        state = ScanState::Str {
+                            delim: c,
+                            escapes: true,
+                            multiline: syntax.multiline_quotes,
+                        };
+                    } else if c == '`' && syntax.backtick_escapes.is_some() {
+                        state = ScanState::Str {
+                            delim: c,
+                            escapes: syntax.backtick_escapes.unwrap_or(true),
+                            multiline: true,
+                        };
+                    } else if matches!(c, '{' | '(' | '[') {
+                        if c == '{' {
+                            info.brace_open_depth = Some(info.brace_open_depth.map_or(depth, |d| d.min(depth)));
+                        }
+                        depth += 1;
+                    } else if matches!(c, '}' | ')' | ']') {
+                        depth = depth.checked_sub(1)?;
+                    }
+                }
+            }
+            i += 1;
+        }
+        if let ScanState::Str { multiline: false, .. } = state {
+            state = ScanState::Code;
+        }
+        info.depth_end = depth;
+        infos.push(info);
+    }
+    (depth == 0 && state == ScanState::Code).then_some(infos)
+}
+
+/// Handles Rust raw strings and char literals (which must not be confused
+/// with lifetimes). Returns the index to resume scanning from, if consumed.
+fn skip_rust_literal(chars: &[char], i: usize, state: &mut ScanState) -> Option<usize> {
+    let c = chars[i];
+    let prev_is_ident = |offset: usize| i >= offset && is_ident_char(chars[i - offset]);
+    if c == 'r' && (!prev_is_ident(1) || (chars[i - 1] == 'b' && !prev_is_ident(2))) {
+        let mut hashes = 0;
+        while chars.get(i + 1 + hashes) == Some(&'#') {
+            hashes += 1;
+        }
+        if chars.get(i + 1 + hashes) == Some(&'"') {
+            *state = ScanState::RawStr { hashes };
+            return Some(i + 2 + hashes);
+        }
+    }
+    if c == '\'' {
+        if chars.get(i + 1) == Some(&'\\') {
+            let close = (i + 3..chars.len()).find(|&j| chars[j] == '\'')?;
+            return Some(close + 1);
+        }
+        if chars.get(i + 2) == Some(&'\'') {
+            return Some(i + 3);
+        }
+        // A lifetime or label.
+        return Some(i + 1);
+    }
+    None
+}
+
+// --- Definition detection ---
+
+fn strip_word<'s>(text: &'s str, word: &str) -> Option<&'s str> {
+    let rest = text.strip_prefix(word)?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(rest.trim_start())
+    } else {
+        None
+    }
+}
+
+fn strip_words<'s>(mut text: &'s str, words: &[&str]) -> &'s str {
+    'outer: loop {
+        for word in words {
+            if let Some(rest) = strip_word(text, word) {
+                text = rest;
+                continue 'outer;
+            }
+        }
+        return text;
+    }
+}
+
+fn leading_ident(text: &str) -> &str {
+    let end = text.find(|c: char| !is_ident_char(c)).unwrap_or(text.len());
+    &text[..end]
+}
+
+/// Skips a balanced `<...>` or `(...)` group at the start of `text`.
+fn skip_group(text: &str, open: char, close: char) -> &str {
+    if !text.starts_with(open) {
+        return text;
+    }
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return text[index + 1..].trim_start();
+            }
+        }
+    }
+    ""
+}
+
+fn detect_definition(language: Language, line: &str, nested: bool) -> Option<Symbol> {
+    match language {
+        Language::Rust => detect_rust(line),
+        Language::Python => detect_python(line),
+        Language::JavaScript | Language::TypeScript => detect_javascript(line, nested),
+        Language::Go => detect_go(line),
+        Language::Java => detect_java(line, nested),
+    }
+}
+
+fn detect_rust(line: &str) -> Option<Symbol> {
+    let mut text = line;
+    if let Some(rest) = strip_word(text, "pub") {
+        text = rest;
+    } else if text.starts_with("pub(") {
+        text = skip_group(&text[3..], '(', ')');
+    }
+    loop {
+        let before = text;
+        text = strip_words(text, &["default", "async", "unsafe"]);
+        if let Some(rest) = strip_word(text, "extern") {
+            text = rest;
+            if text.starts_with('"') {
+                text = text[1..].split_once('"').map_or("", |(_, rest)| rest.trim_start());
+            }
+        }
+        if let Some(rest) = strip_word(text, "const") {
+            let after = strip_words(rest, &["async", "unsafe", "extern"]);
+            if strip_word(after, "fn").is_some() {
+                text = rest;
+            }
+        }
+        if text == before {
+            break;
+        }
+    }
+    if let Some(rest) = text.strip_prefix("macro_rules!") {
+        return Symbol::new("macro", leading_ident(rest.trim_start()));
+    }
+    let generic_impl = text.strip_prefix("impl").filter(|rest| rest.starts_with('<'));
+    if let Some(rest) = strip_word(text, "impl").or(generic_impl) {
+        let rest = skip_group(rest, '<', '>');
+        let header = rest.split(['{', ';']).next().unwrap_or("");
+        let header = header.split(" where").next().unwrap_or("");
+        let name = header.split_whitespace().collect::<Vec<_>>().join(" ");
+        return Symbol::new("impl", &name);
+    }
+    let kinds = [
+        ("fn", "function"),
+        ("struct", "struct"),
+        ("enum", "enum"),
+        ("trait", "trait"),
+        ("union", "union"),
+        ("mod", "module"),
+        ("type", "type"),
+        ("const", "const"),
+        ("static", "static"),
+    ];
+    for (keyword, kind) in kinds {
+        if let Some(rest) = strip_word(text, keyword) {
+            let rest = if keyword == "static" { strip_words(rest, &["mut"]) } else { rest };
+            return Symbol::new(kind, leading_ident(rest));
+        }
+    }
+    None
+}
+
+fn detect_python(line: &str) -> Option<Symbol> {
+    let text = strip_words(line, &["async"]);
+    if let Some(rest) = strip_word(text, "def") {
+        return Symbol::new("function", leading_ident(rest));
+    }
+    if let Some(rest) = strip_word(text, "class") {
+        return Symbol::new("class", leading_ident(rest));
+    }
+    None
+}
+
+fn detect_javascript(line: &str, nested: bool) -> Option<Symbol> {
+    let text = strip_words(line, &["export", "default", "declare", "abstract", "async"]);
+    if let Some(rest) = text.strip_prefix("function") {
+        if rest.starts_with(|c: char| c.is_whitespace() || c == '*' || c == '(') {
+            let name = leading_ident(rest.trim_start_matches('*').trim_start());
+            return Symbol::new("function", if name.is_empty() { "default" } else { name });
+        }
+    }
+    let kinds = [
+        ("class", "class"),
+        ("interface", "interface"),
+        ("enum", "enum"),
+        ("namespace", "namespace"),
+        ("type", "type"),
+    ];
+    for (keyword, kind) in kinds {
+        if let Some(rest) = strip_word(strip_words(text, &["const"]), keyword) {
+            return Symbol::new(kind, leading_ident(rest));
+        }
+    }
+    for keyword in ["const", "let", "var"] {
+        if let Some(rest) = strip_word(text, keyword) {
+            let name = leading_ident(rest);
+            let value = rest.split_once('=').map_or("", |(_, value)| value.trim_start());
+            let is_function = value.starts_with('(')
+                || strip_word(value, "async").is_some()
+                || value.starts_with("function")
+                || strip_word(value, "class").is_some()
+                || value.contains("=>");
+            return if is_function { Symbol::new("function", name) } else { None };
+        }
+    }
+    if nested {
+        let text = strip_words(
+            text,
+            &["public", "private", "protected", "static", "readonly", "override", "get", "set"],
+        );
+        let text = text.trim_start_matches('*').trim_start_matches('#');
+        let name = leading_ident(text);
+        let rest = text[name.len()..].trim_start();
+        let is_keyword = matches!(name, "if" | "for" | "while" | "switch" | "catch" | "return" | "function");
+        if !is_keyword && (rest.starts_with('(') || rest.starts_with('<')) {
+            return Symbol::new("function", name);
+        }
+    }
+    None
+}
+
+fn detect_go(line: &str) -> Option<Symbol> {
+    if let Some(rest) = line.strip_prefix("func") {
+        if let Some(receiver) = rest.trim_start().strip_prefix('(') {
+            let (receiver, after) = receiver.split_once(')')?;
+            let receiver_type = receiver.split_whitespace().last().unwrap_or("").trim_start_matches('*');
+            let receiver_type = receiver_type.split('[').next().unwrap_or("");
+            let name = leading_ident(after.trim_start());
+            if receiver_type.is_empty() || name.is_empty() {
+                return None;
+            }
+            return Symbol::new("method", &format!("{}.{}", receiver_type, name));
+        }
+        if rest.starts_with(char::is_whitespace) {
+            return Symbol::new("function", leading_ident(rest.trim_start()));
+        }
+    }
+    if let Some(rest) = strip_word(line, "type") {
+        let name = leading_ident(rest);
+        let body = rest[name.len()..].trim_start();
+        let body = skip_group(body, '[', ']');
+        let kind = if strip_word(body, "struct").is_some() || body.starts_with("struct{") {
+            "struct"
+        } else if strip_word(body, "interface").is_some() || body.starts_with("interface{") {
+            "interface"
+        } else {
+            "type"
+        };
+        return Symbol::new(kind, name);
+    }
+    None
+}
+
+fn detect_java(line: &str, nested: bool) -> Option<Symbol> {
+    let mut text = line;
+    while text.starts_with('@') && !text.starts_with("@interface") {
+        let name = leading_ident(&text[1..]);
+        text = skip_group(text[1 + name.len()..].trim_start(), '(', ')');
+    }
+    let text = strip_words(
+        text,
+        &[
+            "public", "private", "protected", "abstract", "final", "static", "sealed", "non-sealed",
+            "strictfp", "default", "synchronized", "native",
+        ],
+    );
+    if let Some(rest) = text.strip_prefix("@interface") {
+        return Symbol::new("annotation", leading_ident(rest.trim_start()));
+    }
+    let kinds = [("class", "class"), ("interface", "interface"), ("enum", "enum"), ("record", "record")];
+    for (keyword, kind) in kinds {
+        if let Some(rest) = strip_word(text, keyword) {
+            return Symbol::new(kind, leading_ident(rest));
+        }
+    }
+    if nested {
+        let (signature, _) = text.split_once('(')?;
+        if signature.contains('=') {
+            return None;
+        }
+        let signature = skip_group(signature.trim(), '<', '>');
+        let name = signature.rsplit(|c: char| c.is_whitespace()).next().unwrap_or("");
+        let is_keyword = matches!(
+            signature.split_whitespace().next().unwrap_or(""),
+            "return" | "new" | "if" | "for" | "while" | "switch" | "throw" | "else" | "catch" | "try"
+        );
+        if !is_keyword && !name.is_empty() && name.chars().all(is_ident_char) {
+            return Symbol::new("function", name);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::reassemble;
+
+    fn source_document(name: &str, content: &str) -> Document {
+        Document {
+            content: content.to_string(),
+            id: Some("src".to_string()),
+            name: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn symbols(chunks: &[Document]) -> Vec<Option<&str>> {
+        chunks
+            .iter()
+            .map(|chunk| chunk.meta_data.get("symbol").and_then(|value| value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_language_detection() {
+        assert_eq!(Language::from_path("src/lib.rs"), Some(Language::Rust));
+        assert_eq!(Language::from_path("app/main.PY"), Some(Language::Python));
+        assert_eq!(Language::from_path("web/index.tsx"), Some(Language::TypeScript));
+        assert_eq!(Language::from_path("README.md"), None);
+        assert_eq!(Language::from_name("golang"), Some(Language::Go));
+        assert_eq!(serde_json::to_string(&Language::JavaScript).unwrap(), r#""javascript""#);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(CodeChunking::new(0, 10, 0).is_err());
+        assert!(CodeChunking::new(100, 10, 10).is_err());
+        let strategy = CodeChunking::new(100, 10, 2).unwrap();
+        assert_eq!((strategy.max_chunk_size(), strategy.window_lines(), strategy.overlap_lines()), (100, 10, 2));
+        assert_eq!(strategy.with_language(Language::Rust).language(), Some(Language::Rust));
+    }
+
+    #[test]
+    fn test_rust_top_level_definitions() {
+        let source = r#"use std::fmt;
+
+/// A point.
+#[derive(Debug)]
+pub struct Point {
+    x: i32, // "}" in a comment
+}
+
+impl<T> fmt::Display for Wrapper<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")
+    }
+}
+
+pub(crate) async fn run<'a>(name: &'a str) -> char {
+    let brace = '}';
+    brace
+}
+"#;
+        let chunks = CodeChunking::default().chunk(&source_document("lib.rs", source)).unwrap();
+        assert_eq!(
+            symbols(&chunks),
+            vec![None, Some("Point"), Some("fmt::Display for Wrapper<T>"), Some("run")]
+        );
+        let point = &chunks[1];
+        assert!(point.content.starts_with("/// A point.\n#[derive(Debug)]"));
+        assert_eq!(point.meta_data["symbol_kind"], json!("struct"));
+        assert_eq!(point.meta_data["start_line"], json!(3));
+        assert_eq!(point.meta_data["end_line"], json!(7));
+        assert_eq!(point.meta_data["language"], json!("rust"));
+        assert_eq!(point.meta_data["chunk"], json!(2));
+        assert_eq!(point.id, Some("src_2".to_string()));
+        assert_eq!(chunks[2].meta_data["symbol_kind"], json!("impl"));
+        assert_eq!(chunks[3].meta_data["end_line"], json!(18));
+    }
+
+    #[test]
+    fn test_python_definitions_and_decorators() {
+        let source = r#"import os
+
+
+@decorator(
+    option=True,
+)
+def handler(event):
+    text = """
+def not_a_function():
+"""
+    return text
+
+# Helper comment
+class Service:
+    def start(self):
+        pass
+"#;
+        let chunks = CodeChunking::default().chunk(&source_document("app.py", source)).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("handler"), Some("Service")]);
+        assert!(chunks[1].content.starts_with("@decorator("));
+        assert!(chunks[1].content.ends_with("return text"));
+        assert!(chunks[2].content.starts_with("# Helper comment"));
+        assert_eq!(chunks[2].meta_data["symbol_kind"], json!("class"));
+    }
+
+    #[test]
+    fn test_oversized_container_is_split_by_members() {
+        let mut source = String::from("class Big:\n    \"\"\"Docs.\"\"\"\n");
+        for index in 0..4 {
+            source.push_str(&format!("\n    def method_{}(self):\n        return {}\n", index, "1 + ".repeat(10)));
+        }
+        let strategy = CodeChunking::new(120, 50, 5).unwrap();
+        let chunks = strategy.chunk(&source_document("big.py", &source)).unwrap();
+        let names = symbols(&chunks);
+        assert_eq!(names[0], Some("Big"));
+        assert!(names.contains(&Some("Big.method_0")));
+        assert!(names.contains(&Some("Big.method_3")));
+        let method = chunks.iter().find(|c| c.meta_data["symbol"] == json!("Big.method_1")).unwrap();
+        assert_eq!(method.meta_data["symbol_kind"], json!("method"));
+    }
+
+    #[test]
+    fn test_javascript_go_and_java_definitions() {
+        let js = "import x from 'y';\n\nexport async function load(path) {\n  return `${path}}`;\n}\n\nconst add = (a, b) => {\n  return a + b;\n};\n";
+        let chunks = CodeChunking::default().chunk(&source_document("util.js", js)).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("load"), Some("add")]);
+
+        let go = "package main\n\ntype Server struct {\n\tport int\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n";
+        let chunks = CodeChunking::default().chunk(&source_document("main.go", go)).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("Server"), Some("Server.Start")]);
+        assert_eq!(chunks[1].meta_data["symbol_kind"], json!("struct"));
+
+        let java = "package a;\n\n/** Docs. */\npublic final class App {\n    public static void main(String[] args) {}\n}\n";
+        let chunks = CodeChunking::default().chunk(&source_document("App.java", java)).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("App")]);
+        assert!(chunks[1].content.starts_with("/** Docs. */"));
+    }
+
+    #[test]
+    fn test_fallback_to_line_windows() {
+        let content: Vec<String> = (1..=10).map(|n| format!("line {}", n)).collect();
+        let strategy = CodeChunking::new(5000, 4, 1).unwrap();
+        let chunks = strategy.chunk(&source_document("notes.txt", &content.join("\n"))).unwrap();
+        let ranges: Vec<(u64, u64)> = chunks
+            .iter()
+            .map(|c| (c.meta_data["start_line"].as_u64().unwrap(), c.meta_data["end_line"].as_u64().unwrap()))
+            .collect();
+        assert_eq!(ranges, vec![(1, 4), (4, 7), (7, 10)]);
+        assert!(chunks.iter().all(|c| !c.meta_data.contains_key("language")));
+
+        // Unbalanced source falls back as well, but keeps the language.
+        let chunks = CodeChunking::default().chunk(&source_document("broken.rs", "fn main() {\n")).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].meta_data["language"], json!("rust"));
+        assert!(!chunks[0].meta_data.contains_key("symbol"));
+    }
+
+    #[test]
+    fn test_language_from_meta_data() {
+        let mut document = source_document("snippet", "def f():\n    return 1\n");
+        document.meta_data.insert("language".to_string(), json!("python"));
+        let chunks = CodeChunking::default().chunk(&document).unwrap();
+        assert_eq!(symbols(&chunks), vec![Some("f")]);
+        assert_eq!(chunks[0].meta_data["language"], json!("python"));
+    }
+
+    #[test]
+    fn test_chunks_are_exact_slices_with_provenance() {
+        let source = "use std::io;\r\n\r\nfn main() {\r\n    println!(\"é\");\r\n}\r\n\r\nfn other() {}\r\n";
+        let document = source_document("main.rs", source);
+        let chunks = CodeChunking::default().chunk(&document).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("main"), Some("other")]);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let provenance = chunk.provenance().unwrap();
+            assert_eq!(provenance.parent_id, Some("src".to_string()));
+            assert_eq!((provenance.chunk_index, provenance.total_chunks), (index, 3));
+            assert_eq!(&source[provenance.byte_start..provenance.byte_end], chunk.content);
+        }
+        let main = chunks[1].provenance().unwrap();
+        assert_eq!(main.char_start, "use std::io;\r\n\r\n".len());
+        assert_eq!(main.char_end, main.char_start + chunks[1].content.chars().count());
+        assert_eq!(main.byte_end, main.char_end + 1);
+        assert!(chunks[1].content.ends_with("}"));
+
+        assert_eq!(reassemble(&chunks).unwrap(), source);
+    }
+
+    #[test]
+    fn test_reassemble_restores_trimmed_whitespace() {
+        let source = "\n    \nfn a() {\n    1\n}\n  \n\t\n    \nfn b() {}\n   \n";
+        let chunks = CodeChunking::default().chunk(&source_document("lib.rs", source)).unwrap();
+        assert_eq!(symbols(&chunks), vec![Some("a"), Some("b")]);
+        assert_eq!(chunks[1].content, "fn b() {}");
+        assert_eq!(reassemble(&chunks).unwrap(), source);
+    }
+}
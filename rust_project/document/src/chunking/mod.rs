@@ -0,0 +1,323 @@
+use crate::provenance::ChunkProvenance;
+use crate::Document;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde_json::{json, Value as JsonValue};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+pub mod batch;
+pub mod code;
+
+pub use batch::{chunk_batch, chunk_batch_with_threads, ChunkResult};
+pub use code::{CodeChunking, Language};
+
+// Define a custom error type for chunking operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkingError {
+    InvalidParameters(String),
+    ChunkingFailed(String),
+    ReassemblyFailed(String),
+}
+
+impl fmt::Display for ChunkingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkingError::InvalidParameters(msg) => write!(f, "Invalid parameters: {}", msg),
+            ChunkingError::ChunkingFailed(msg) => write!(f, "Chunking failed: {}", msg),
+            ChunkingError::ReassemblyFailed(msg) => write!(f, "Reassembly failed: {}", msg),
+        }
+    }
+}
+
+impl Error for ChunkingError {}
+
+impl From<ChunkingError> for PyErr {
+    fn from(err: ChunkingError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// A strategy for splitting a `Document` into smaller `Document` chunks.
+///
+/// Mirrors Python's `agno.document.chunking.strategy.ChunkingStrategy`.
+pub trait ChunkingStrategy {
+    /// Splits the document into chunks.
+    fn chunk(&self, document: &Document) -> Result<Vec<Document>, ChunkingError>;
+}
+
+/// Builds the id of the `chunk_number`-th chunk of `document`, following the
+/// Python strategies: `{id}_{n}`, falling back to `{name}_{n}`.
+pub(crate) fn chunk_id(document: &Document, chunk_number: usize) -> Option<String> {
+    document
+        .id
+        .as_ref()
+        .or(document.name.as_ref())
+        .map(|prefix| format!("{}_{}", prefix, chunk_number))
+}
+
+/// Collects the chunks of one parent document.
+///
+/// Every chunk is an exact byte range of the parent's content. `finish`
+/// stamps each chunk with its id, the Python `chunk` and `chunk_size` keys
+/// and its `ChunkProvenance`, so all strategies record the same metadata.
+pub(crate) struct ChunkBuilder<'a> {
+    parent: &'a Document,
+    chunks: Vec<(usize, usize, HashMap<String, JsonValue>)>,
+}
+
+impl<'a> ChunkBuilder<'a> {
+    pub(crate) fn new(parent: &'a Document) -> Self {
+        ChunkBuilder {
+            parent,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Adds the chunk covering `parent.content[byte_start..byte_end]`, with
+    /// strategy-specific keys merged over the parent's `meta_data`.
+    pub(crate) fn push(&mut self, byte_start: usize, byte_end: usize, meta_data: HashMap<String, JsonValue>) {
+        self.chunks.push((byte_start, byte_end, meta_data));
+    }
+
+    pub(crate) fn finish(self) -> Vec<Document> {
+        let content = &self.parent.content;
+        let total_chunks = self.chunks.len();
+
+        // Convert every byte offset to a char offset in a single pass.
+        let mut offsets: Vec<usize> = self
+            .chunks
+            .iter()
+            .flat_map(|(start, end, _)| [*start, *end])
+            .collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        let mut char_offsets = HashMap::with_capacity(offsets.len());
+        let (mut byte_position, mut char_position) = (0, 0);
+        for offset in offsets {
+            char_position += content[byte_position..offset].chars().count();
+            byte_position = offset;
+            char_offsets.insert(offset, char_position);
+        }
+
+        // The parent text each chunk leaves uncovered before it, and after the last one.
+        let mut covered = 0;
+        let mut skipped: Vec<&str> = Vec::with_capacity(total_chunks);
+        for (start, end, _) in &self.chunks {
+            skipped.push(&content[covered.min(*start)..*start]);
+            covered = covered.max(*end);
+        }
+        let skipped_after = &content[covered..];
+
+        self.chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, (byte_start, byte_end, extra))| {
+                let chunk_content = content[byte_start..byte_end].to_string();
+                let mut meta_data = self.parent.meta_data.clone();
+                meta_data.extend(extra);
+                meta_data.insert("chunk".to_string(), json!(chunk_index + 1));
+                meta_data.insert("chunk_size".to_string(), json!(chunk_content.chars().count()));
+                let mut chunk = Document {
+                    content: chunk_content,
+                    id: chunk_id(self.parent, chunk_index + 1),
+                    name: self.parent.name.clone(),
+                    meta_data,
+                    ..Default::default()
+                };
+                chunk.set_provenance(&ChunkProvenance {
+                    parent_id: self.parent.id.clone(),
+                    chunk_index,
+                    total_chunks,
+                    byte_start,
+                    byte_end,
+                    char_start: char_offsets[&byte_start],
+                    char_end: char_offsets[&byte_end],
+                    skipped_before: skipped[chunk_index].to_string(),
+                    skipped_after: if chunk_index + 1 == total_chunks {
+                        skipped_after.to_string()
+                    } else {
+                        String::new()
+                    },
+                });
+                chunk
+            })
+            .collect()
+    }
+}
+
+/// Rebuilds the parent text from its chunks, in any order.
+///
+/// Every chunk of the parent must be present and carry its provenance.
+/// Overlapping ranges are written once, and the text the strategy skipped
+/// between and after the chunks is restored from the provenance, so the
+/// result is byte for byte the parent content.
+pub fn reassemble(chunks: &[Document]) -> Result<String, ChunkingError> {
+    let mut located = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let provenance = chunk.provenance().ok_or_else(|| {
+            ChunkingError::ReassemblyFailed(format!("chunk {:?} has no provenance", chunk.id))
+        })?;
+        if provenance.byte_end.checked_sub(provenance.byte_start) != Some(chunk.content.len()) {
+            return Err(ChunkingError::ReassemblyFailed(format!(
+                "content of chunk {} does not match its recorded offsets",
+                provenance.chunk_index
+            )));
+        }
+        located.push((provenance, &chunk.content));
+    }
+    let Some((first, _)) = located.first() else {
+        return Ok(String::new());
+    };
+    let (parent_id, total_chunks) = (first.parent_id.clone(), first.total_chunks);
+    if located
+        .iter()
+        .any(|(provenance, _)| provenance.parent_id != parent_id || provenance.total_chunks != total_chunks)
+    {
+        return Err(ChunkingError::ReassemblyFailed(
+            "chunks belong to different parent documents".to_string(),
+        ));
+    }
+    located.sort_by_key(|(provenance, _)| provenance.chunk_index);
+    let indices_complete = located.len() == total_chunks
+        && located
+            .iter()
+            .enumerate()
+            .all(|(index, (provenance, _))| provenance.chunk_index == index);
+    if !indices_complete {
+        return Err(ChunkingError::ReassemblyFailed(format!(
+            "expected chunks 0..{} of parent {:?}, got {} chunks",
+            total_chunks,
+            parent_id,
+            located.len()
+        )));
+    }
+
+    let skipped_after = located[located.len() - 1].0.skipped_after.clone();
+    let mut text = String::new();
+    for (provenance, content) in located {
+        if provenance.byte_start > text.len() {
+            if provenance.byte_start - text.len() != provenance.skipped_before.len() {
+                return Err(ChunkingError::ReassemblyFailed(format!(
+                    "the text before chunk {} was not recorded",
+                    provenance.chunk_index
+                )));
+            }
+            text.push_str(&provenance.skipped_before);
+        }
+        let skip = text.len() - provenance.byte_start.min(text.len());
+        if skip < content.len() {
+            let tail = content.get(skip..).ok_or_else(|| {
+                ChunkingError::ReassemblyFailed(format!(
+                    "chunk {} overlaps its predecessor inside a character",
+                    provenance.chunk_index
+                ))
+            })?;
+            text.push_str(tail);
+        }
+    }
+    text.push_str(&skipped_after);
+    Ok(text)
+}
+
+/// Rebuilds the parent text from its chunks.
+///
+/// Args:
+///     chunks (List[Document]): Every chunk produced from one parent document.
+///
+/// Returns:
+///     str: The reassembled parent text.
+///
+/// Raises:
+///     PyValueError: If chunks are missing, lack provenance or mix parents.
+#[pyfunction]
+#[pyo3(name = "reassemble_chunks")]
+pub fn reassemble_chunks_py(chunks: Vec<Document>) -> PyResult<String> {
+    Ok(reassemble(&chunks)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent(content: &str) -> Document {
+        Document {
+            content: content.to_string(),
+            id: Some("parent".to_string()),
+            name: Some("parent.txt".to_string()),
+            meta_data: HashMap::from([("source".to_string(), json!("test"))]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builder_records_provenance_and_char_offsets() {
+        let parent = parent("héllo wörld");
+        let mut builder = ChunkBuilder::new(&parent);
+        builder.push(0, 6, HashMap::new());
+        builder.push(7, parent.content.len(), HashMap::from([("extra".to_string(), json!(1))]));
+        let chunks = builder.finish();
+
+        assert_eq!(chunks[0].content, "héllo");
+        assert_eq!(chunks[1].content, "wörld");
+        assert_eq!(chunks[1].id, Some("parent_2".to_string()));
+        assert_eq!(chunks[1].meta_data["source"], json!("test"));
+        assert_eq!(chunks[1].meta_data["extra"], json!(1));
+        assert_eq!(chunks[1].meta_data["chunk"], json!(2));
+
+        let provenance = chunks[1].provenance().unwrap();
+        assert_eq!(provenance.parent_id, Some("parent".to_string()));
+        assert_eq!((provenance.chunk_index, provenance.total_chunks), (1, 2));
+        assert_eq!((provenance.byte_start, provenance.byte_end), (7, 13));
+        assert_eq!((provenance.char_start, provenance.char_end), (6, 11));
+        let chars: Vec<char> = parent.content.chars().collect();
+        let by_chars: String = chars[provenance.char_start..provenance.char_end].iter().collect();
+        assert_eq!(by_chars, chunks[1].content);
+    }
+
+    #[test]
+    fn test_reassemble_handles_order_overlap_and_gaps() {
+        let parent = parent("  alpha\nbeta\n \t\n  gamma\n\n");
+        let mut builder = ChunkBuilder::new(&parent);
+        builder.push(2, 12, HashMap::new());
+        builder.push(8, 12, HashMap::new());
+        builder.push(18, 23, HashMap::new());
+        let mut chunks = builder.finish();
+        assert_eq!(chunks[0].provenance().unwrap().skipped_before, "  ");
+        assert_eq!(chunks[1].provenance().unwrap().skipped_before, "");
+        assert_eq!(chunks[2].provenance().unwrap().skipped_before, "\n \t\n  ");
+        assert_eq!(chunks[2].provenance().unwrap().skipped_after, "\n\n");
+        chunks.reverse();
+        assert_eq!(reassemble(&chunks).unwrap(), parent.content);
+    }
+
+    #[test]
+    fn test_reassemble_rejects_incomplete_or_mixed_chunks() {
+        let first = parent("one two");
+        let mut builder = ChunkBuilder::new(&first);
+        builder.push(0, 3, HashMap::new());
+        builder.push(4, 7, HashMap::new());
+        let chunks = builder.finish();
+        assert!(matches!(reassemble(&chunks[..1]), Err(ChunkingError::ReassemblyFailed(_))));
+
+        let mut other = parent("three");
+        other.id = Some("other".to_string());
+        let mut builder = ChunkBuilder::new(&other);
+        builder.push(0, 5, HashMap::new());
+        let mixed = vec![chunks[0].clone(), builder.finish().remove(0)];
+        assert!(reassemble(&mixed).is_err());
+
+        let mut edited = chunks.clone();
+        edited[0].content.push('!');
+        assert!(reassemble(&edited).is_err());
+
+        // Chunks stored without the skipped text cannot be rebuilt exactly.
+        let mut unrecorded = chunks.clone();
+        let mut provenance = unrecorded[1].provenance().unwrap();
+        provenance.skipped_before.clear();
+        unrecorded[1].set_provenance(&provenance);
+        assert!(matches!(reassemble(&unrecorded), Err(ChunkingError::ReassemblyFailed(msg)) if msg.contains("chunk 1")));
+        assert_eq!(reassemble(&[]).unwrap(), "");
+    }
+}
@@ -0,0 +1,704 @@
+//! Conversion between documents and Apache Arrow record batches, with Parquet
+//! files on top, so document collections reach pandas, polars and the
+//! analytics stack without a detour through JSON.
+//!
+//! A batch has one row per document and the columns `content`, `id`, `name`,
+//! `meta_data`, `embedding`, `usage`, `reranking_score` and `extra`.
+//! `meta_data` is either JSON text or a struct with one field per key;
+//! `embedding` is a fixed-size list of `float32`; `usage` and `extra` are JSON
+//! text, `extra` including the schema version of documents from a newer
+//! version. Python receives batches through the Arrow C data interface, without
+//! copying.
+
+use crate::Document;
+use arrow::array::{
+    Array, ArrayRef, AsArray, FixedSizeListArray, Float32Array, Float64Array, RecordBatch, RecordBatchIterator,
+    RecordBatchReader, StringArray, StructArray,
+};
+use arrow::buffer::NullBuffer;
+use arrow::compute::cast;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use pyo3::exceptions::{PyFileNotFoundError, PyIOError, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyCapsule, PyTuple};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::error::Error;
+use std::ffi::CString;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+// Define a custom error type for Arrow and Parquet conversion
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnarError {
+    FileNotFound(String),
+    Io(String),
+    Arrow(String),
+    Parquet(String),
+    /// A column is missing or holds values that do not fit a document.
+    InvalidColumn { column: String, message: String },
+}
+
+impl fmt::Display for ColumnarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnarError::FileNotFound(path) => write!(f, "Could not find file: {}", path),
+            ColumnarError::Io(msg) => write!(f, "I/O error: {}", msg),
+            ColumnarError::Arrow(msg) => write!(f, "Arrow error: {}", msg),
+            ColumnarError::Parquet(msg) => write!(f, "Parquet error: {}", msg),
+            ColumnarError::InvalidColumn { column, message } => {
+                write!(f, "Invalid column `{}`: {}", column, message)
+            }
+        }
+    }
+}
+
+impl Error for ColumnarError {}
+
+impl From<io::Error> for ColumnarError {
+    fn from(err: io::Error) -> Self {
+        ColumnarError::Io(err.to_string())
+    }
+}
+
+impl From<ArrowError> for ColumnarError {
+    fn from(err: ArrowError) -> Self {
+        ColumnarError::Arrow(err.to_string())
+    }
+}
+
+impl From<ParquetError> for ColumnarError {
+    fn from(err: ParquetError) -> Self {
+        ColumnarError::Parquet(err.to_string())
+    }
+}
+
+impl From<ColumnarError> for PyErr {
+    fn from(err: ColumnarError) -> PyErr {
+        match err {
+            ColumnarError::FileNotFound(_) => PyFileNotFoundError::new_err(err.to_string()),
+            ColumnarError::Io(_) => PyIOError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+fn invalid(column: &str, message: impl Into<String>) -> ColumnarError {
+    ColumnarError::InvalidColumn { column: column.to_string(), message: message.into() }
+}
+
+/// How the `meta_data` column is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetaDataLayout {
+    /// One JSON object per row, as text. Keeps every value as is.
+    #[default]
+    Json,
+    /// A struct with one field per key, typed from the values, so that
+    /// dataframes get one column per key. Keys missing from a document and
+    /// keys holding null both read back as absent. Values that would read
+    /// back changed, such as an integer in a key that also holds floats, are
+    /// rejected rather than widened.
+    Struct,
+}
+
+impl FromStr for MetaDataLayout {
+    type Err = ColumnarError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(MetaDataLayout::Json),
+            "struct" => Ok(MetaDataLayout::Struct),
+            _ => Err(invalid("meta_data", format!("unknown layout {:?}, expected \"json\" or \"struct\"", s))),
+        }
+    }
+}
+
+/// A column of JSON maps as text, null where a document has no map.
+fn json_column<'a>(
+    column: &str,
+    maps: impl Iterator<Item = Option<&'a HashMap<String, JsonValue>>>,
+) -> Result<ArrayRef, ColumnarError> {
+    let texts = maps
+        .map(|map| map.map(serde_json::to_string).transpose())
+        .collect::<Result<StringArray, _>>()
+        .map_err(|e| invalid(column, e.to_string()))?;
+    Ok(Arc::new(texts))
+}
+
+fn meta_data_struct(documents: &[Document]) -> Result<ArrayRef, ColumnarError> {
+    let rows: Vec<JsonValue> = documents
+        .iter()
+        .map(|document| JsonValue::Object(document.meta_data.clone().into_iter().collect::<Map<_, _>>()))
+        .collect();
+    let schema = arrow_json::reader::infer_json_schema_from_iterator(rows.iter().map(Ok))?;
+    if schema.fields().is_empty() {
+        return Ok(Arc::new(StructArray::new_empty_fields(documents.len(), None)));
+    }
+    let mut decoder = arrow_json::ReaderBuilder::new(Arc::new(schema)).build_decoder()?;
+    decoder.serialize(&rows)?;
+    let batch = decoder
+        .flush()?
+        .ok_or_else(|| invalid("meta_data", "no rows were decoded"))?;
+    let array = StructArray::from(batch);
+    // Inference widens values to a common type instead of failing, so read
+    // the struct back and refuse anything that did not survive unchanged.
+    for (document, stored) in documents.iter().zip(struct_maps("meta_data", &array)?) {
+        let stored = stored.unwrap_or_default();
+        let changed = document.meta_data.iter().find(|(key, value)| !value.is_null() && stored.get(*key) != Some(value));
+        if let Some((key, value)) = changed {
+            return Err(invalid(
+                "meta_data",
+                format!("key {:?} cannot hold {} in the struct layout without changing it; use the json layout", key, value),
+            ));
+        }
+    }
+    Ok(Arc::new(array))
+}
+
+fn embedding_column(documents: &[Document]) -> Result<ArrayRef, ColumnarError> {
+    let mut dimension = None;
+    for embedding in documents.iter().filter_map(|document| document.embedding.as_ref()) {
+        match dimension {
+            None => dimension = Some(embedding.len()),
+            Some(dimension) if dimension != embedding.len() => {
+                return Err(invalid(
+                    "embedding",
+                    format!("embeddings of length {} and {} cannot share a fixed-size list", dimension, embedding.len()),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    let dimension = dimension.unwrap_or(0);
+    let mut values = Vec::with_capacity(dimension * documents.len());
+    for document in documents {
+        match &document.embedding {
+            Some(embedding) => values.extend_from_slice(embedding),
+            None => values.resize(values.len() + dimension, 0.0),
+        }
+    }
+    let nulls = NullBuffer::from(documents.iter().map(|d| d.embedding.is_some()).collect::<Vec<_>>());
+    let size = i32::try_from(dimension).map_err(|_| invalid("embedding", "embeddings are too long"))?;
+    Ok(Arc::new(FixedSizeListArray::try_new(
+        Arc::new(Field::new("item", DataType::Float32, true)),
+        size,
+        Arc::new(Float32Array::from(values)),
+        Some(nulls),
+    )?))
+}
+
+/// Converts documents into one record batch, with `meta_data` in `layout`.
+pub fn to_record_batch(documents: &[Document], layout: MetaDataLayout) -> Result<RecordBatch, ColumnarError> {
+    let content: ArrayRef = Arc::new(documents.iter().map(|d| Some(d.content.as_str())).collect::<StringArray>());
+    let id: ArrayRef = Arc::new(documents.iter().map(|d| d.id.as_deref()).collect::<StringArray>());
+    let name: ArrayRef = Arc::new(documents.iter().map(|d| d.name.as_deref()).collect::<StringArray>());
+    let meta_data = match layout {
+        MetaDataLayout::Json => json_column("meta_data", documents.iter().map(|d| Some(&d.meta_data)))?,
+        MetaDataLayout::Struct => meta_data_struct(documents)?,
+    };
+    let usage = json_column("usage", documents.iter().map(|d| d.usage.as_ref()))?;
+    let reranking_score: ArrayRef =
+        Arc::new(documents.iter().map(|d| d.reranking_score).collect::<Float64Array>());
+    let extras: Vec<_> = documents.iter().map(Document::versioned_extra).collect();
+    let extra = json_column("extra", extras.iter().map(|extra| Some(extra).filter(|extra| !extra.is_empty())))?;
+    let columns = [
+        ("content", content, false),
+        ("id", id, true),
+        ("name", name, true),
+        ("meta_data", meta_data, false),
+        ("embedding", embedding_column(documents)?, true),
+        ("usage", usage, true),
+        ("reranking_score", reranking_score, true),
+        ("extra", extra, true),
+    ];
+    let schema = Schema::new(
+        columns
+            .iter()
+            .map(|(name, array, nullable)| Field::new(*name, array.data_type().clone(), *nullable))
+            .collect::<Vec<_>>(),
+    );
+    let arrays = columns.into_iter().map(|(_, array, _)| array).collect();
+    Ok(RecordBatch::try_new(Arc::new(schema), arrays)?)
+}
+
+/// The column `name` cast to `data_type`, if the batch has it.
+fn column(batch: &RecordBatch, name: &str, data_type: &DataType) -> Result<Option<ArrayRef>, ColumnarError> {
+    batch
+        .column_by_name(name)
+        .map(|array| cast(array, data_type).map_err(|e| invalid(name, e.to_string())))
+        .transpose()
+}
+
+fn strings(batch: &RecordBatch, name: &str) -> Result<Vec<Option<String>>, ColumnarError> {
+    Ok(match column(batch, name, &DataType::Utf8)? {
+        Some(array) => array.as_string::<i32>().iter().map(|s| s.map(str::to_string)).collect(),
+        None => vec![None; batch.num_rows()],
+    })
+}
+
+fn parse_json_map(column: &str, text: &str) -> Result<HashMap<String, JsonValue>, ColumnarError> {
+    serde_json::from_str(text).map_err(|e| invalid(column, e.to_string()))
+}
+
+fn json_maps(batch: &RecordBatch, name: &str) -> Result<Vec<Option<HashMap<String, JsonValue>>>, ColumnarError> {
+    let Some(array) = batch.column_by_name(name) else {
+        return Ok(vec![None; batch.num_rows()]);
+    };
+    if let DataType::Struct(_) = array.data_type() {
+        return struct_maps(name, array.as_struct());
+    }
+    strings(batch, name)?
+        .into_iter()
+        .map(|text| text.map(|text| parse_json_map(name, &text)).transpose())
+        .collect()
+}
+
+fn struct_maps(name: &str, array: &StructArray) -> Result<Vec<Option<HashMap<String, JsonValue>>>, ColumnarError> {
+    if array.num_columns() == 0 {
+        return Ok((0..array.len()).map(|row| array.is_valid(row).then(HashMap::new)).collect());
+    }
+    let mut writer = arrow_json::WriterBuilder::new()
+        .with_explicit_nulls(false)
+        .build::<_, arrow_json::writer::JsonArray>(Vec::new());
+    writer.write(&RecordBatch::from(array.clone()))?;
+    writer.finish()?;
+    let rows: Vec<HashMap<String, JsonValue>> =
+        serde_json::from_slice(&writer.into_inner()).map_err(|e| invalid(name, e.to_string()))?;
+    Ok(rows.into_iter().enumerate().map(|(row, map)| array.is_valid(row).then_some(map)).collect())
+}
+
+fn embeddings(batch: &RecordBatch) -> Result<Vec<Option<Vec<f32>>>, ColumnarError> {
+    let list_type = DataType::List(Arc::new(Field::new("item", DataType::Float32, true)));
+    let Some(array) = column(batch, "embedding", &list_type)? else {
+        return Ok(vec![None; batch.num_rows()]);
+    };
+    array
+        .as_list::<i32>()
+        .iter()
+        .map(|values| {
+            values
+                .map(|values| {
+                    let values = values.as_primitive::<arrow::datatypes::Float32Type>();
+                    if values.null_count() > 0 {
+                        return Err(invalid("embedding", "embeddings cannot contain nulls"));
+                    }
+                    Ok(values.values().to_vec())
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+/// Converts a record batch back into documents.
+///
+/// Only `content` is required. Other columns may be missing, strings may be
+/// any Arrow string type, `embedding` any list of floats, and `meta_data`
+/// JSON text or a struct.
+pub fn from_record_batch(batch: &RecordBatch) -> Result<Vec<Document>, ColumnarError> {
+    let contents = column(batch, "content", &DataType::Utf8)?.ok_or_else(|| invalid("content", "column is missing"))?;
+    let contents = contents.as_string::<i32>();
+    if contents.null_count() > 0 {
+        return Err(invalid("content", "documents need content, found null"));
+    }
+    let ids = strings(batch, "id")?;
+    let names = strings(batch, "name")?;
+    let meta_data = json_maps(batch, "meta_data")?;
+    let embeddings = embeddings(batch)?;
+    let usages = json_maps(batch, "usage")?;
+    let scores = column(batch, "reranking_score", &DataType::Float64)?;
+    let scores = scores.as_ref().map(|scores| scores.as_primitive::<arrow::datatypes::Float64Type>());
+    let extras = json_maps(batch, "extra")?;
+
+    let rows = ids.into_iter().zip(names).zip(meta_data).zip(embeddings).zip(usages).zip(extras);
+    rows.enumerate()
+        .map(|(row, (((((id, name), meta_data), embedding), usage), extra))| {
+            let mut document = Document {
+                content: contents.value(row).to_string(),
+                id,
+                name,
+                meta_data: meta_data.unwrap_or_default(),
+                embedding,
+                usage,
+                reranking_score: scores.filter(|scores| scores.is_valid(row)).map(|scores| scores.value(row)),
+                ..Default::default()
+            };
+            document
+                .set_versioned_extra(extra.unwrap_or_default())
+                .map_err(|e| invalid("extra", e.to_string()))?;
+            Ok(document)
+        })
+        .collect()
+}
+
+/// Writes documents to a Snappy-compressed Parquet file, replacing it.
+pub fn write_parquet(path: &Path, documents: &[Document], layout: MetaDataLayout) -> Result<(), ColumnarError> {
+    let batch = to_record_batch(documents, layout)?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Reads the documents of a Parquet file.
+pub fn read_parquet(path: &Path) -> Result<Vec<Document>, ColumnarError> {
+    let file = File::open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => ColumnarError::FileNotFound(path.display().to_string()),
+        _ => ColumnarError::from(e),
+    })?;
+    let mut documents = Vec::new();
+    for batch in ParquetRecordBatchReaderBuilder::try_new(file)?.build()? {
+        documents.extend(from_record_batch(&batch?)?);
+    }
+    Ok(documents)
+}
+
+fn capsule<'py, T: 'static + Send>(py: Python<'py>, value: T, name: &str) -> PyResult<Bound<'py, PyCapsule>> {
+    // The capsule names are fixed by the Arrow PyCapsule interface.
+    let name = CString::new(name).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    PyCapsule::new_bound(py, value, Some(name))
+}
+
+fn capsule_pointer(capsule: &Bound<'_, PyAny>, expected: &str) -> PyResult<*mut std::ffi::c_void> {
+    let capsule = capsule.downcast::<PyCapsule>()?;
+    let name = capsule.name()?.and_then(|name| name.to_str().ok());
+    if name != Some(expected) {
+        return Err(PyTypeError::new_err(format!("expected a {:?} capsule, found {:?}", expected, name)));
+    }
+    Ok(capsule.pointer())
+}
+
+/// Documents as an Arrow record batch.
+///
+/// Implements the Arrow PyCapsule interface, so `pyarrow.record_batch(batch)`,
+/// `pyarrow.table(batch)` and `polars.DataFrame(batch)` take the columns
+/// without copying them.
+#[pyclass(name = "DocumentBatch")]
+pub struct PyDocumentBatch {
+    batch: RecordBatch,
+}
+
+#[pymethods]
+impl PyDocumentBatch {
+    /// Number of documents in the batch.
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// Names of the columns, in order.
+    #[getter]
+    fn column_names(&self) -> Vec<String> {
+        self.batch.schema().fields().iter().map(|field| field.name().clone()).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.batch.num_rows()
+    }
+
+    /// Converts the batch back into documents.
+    ///
+    /// Returns:
+    ///     List[Document]: One document per row.
+    ///
+    /// Raises:
+    ///     PyValueError: If a column does not fit a document.
+    fn to_documents(&self, py: Python<'_>) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| from_record_batch(&self.batch))?)
+    }
+
+    /// Exports the schema as an `arrow_schema` capsule.
+    fn __arrow_c_schema__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyCapsule>> {
+        let schema = FFI_ArrowSchema::try_from(self.batch.schema().as_ref()).map_err(ColumnarError::from)?;
+        capsule(py, schema, "arrow_schema")
+    }
+
+    /// Exports the batch as `arrow_schema` and `arrow_array` capsules.
+    ///
+    /// Args:
+    ///     requested_schema (Optional[object]): Ignored; the batch is exported as is.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_array__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<Bound<'py, PyTuple>> {
+        let _ = requested_schema;
+        let data = StructArray::from(self.batch.clone()).into_data();
+        let (array, schema) = to_ffi(&data).map_err(ColumnarError::from)?;
+        Ok(PyTuple::new_bound(
+            py,
+            [capsule(py, schema, "arrow_schema")?, capsule(py, array, "arrow_array")?],
+        ))
+    }
+
+    /// Exports the batch as a one-batch `arrow_array_stream` capsule.
+    ///
+    /// Args:
+    ///     requested_schema (Optional[object]): Ignored; the batch is exported as is.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        py: Python<'py>,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<Bound<'py, PyCapsule>> {
+        let _ = requested_schema;
+        let reader = RecordBatchIterator::new([Ok(self.batch.clone())], self.batch.schema());
+        capsule(py, FFI_ArrowArrayStream::new(Box::new(reader)), "arrow_array_stream")
+    }
+}
+
+/// Imports the record batches of any object implementing the Arrow PyCapsule
+/// interface, preferring the stream export.
+fn import_batches(data: &Bound<'_, PyAny>) -> PyResult<(SchemaRef, Vec<RecordBatch>)> {
+    if data.hasattr("__arrow_c_stream__")? {
+        let stream = data.call_method0("__arrow_c_stream__")?;
+        let pointer = capsule_pointer(&stream, "arrow_array_stream")?;
+        // SAFETY: the capsule holds an `ArrowArrayStream`; `from_raw` moves it
+        // out and leaves a released stream behind for the capsule to drop.
+        let reader = unsafe { ArrowArrayStreamReader::from_raw(pointer.cast::<FFI_ArrowArrayStream>()) }
+            .map_err(ColumnarError::from)?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>().map_err(ColumnarError::from)?;
+        return Ok((schema, batches));
+    }
+    if data.hasattr("__arrow_c_array__")? {
+        let capsules = data.call_method0("__arrow_c_array__")?;
+        let (schema, array): (Bound<'_, PyAny>, Bound<'_, PyAny>) = capsules.extract()?;
+        let schema_pointer = capsule_pointer(&schema, "arrow_schema")?;
+        let array_pointer = capsule_pointer(&array, "arrow_array")?;
+        // SAFETY: the capsules hold an `ArrowSchema` and an `ArrowArray`. The
+        // array is moved out, leaving a released one behind; the schema is
+        // only borrowed and released by its capsule.
+        let data = unsafe {
+            let array = std::ptr::replace(array_pointer.cast::<FFI_ArrowArray>(), FFI_ArrowArray::empty());
+            from_ffi(array, &*schema_pointer.cast::<FFI_ArrowSchema>())
+        }
+        .map_err(ColumnarError::from)?;
+        let batch = match data.data_type() {
+            DataType::Struct(_) => RecordBatch::from(StructArray::from(data)),
+            other => {
+                return Err(PyTypeError::new_err(format!("expected a struct array of columns, found {}", other)))
+            }
+        };
+        return Ok((batch.schema(), vec![batch]));
+    }
+    Err(PyTypeError::new_err(
+        "expected an object implementing __arrow_c_stream__ or __arrow_c_array__, such as a pyarrow Table",
+    ))
+}
+
+/// Converts documents into an Arrow record batch, releasing the GIL while working.
+///
+/// Args:
+///     documents (List[Document]): The documents to convert.
+///     meta_data (str): "json" to store `meta_data` as JSON text, or "struct" for one typed
+///         field per key. Defaults to "json".
+///
+/// Returns:
+///     DocumentBatch: The batch, ready for `pyarrow.record_batch` or `polars.DataFrame`.
+///
+/// Raises:
+///     PyValueError: If the layout is unknown, embeddings differ in length, or with
+///         "struct", if a `meta_data` value would not read back unchanged.
+#[pyfunction]
+#[pyo3(name = "documents_to_arrow", signature = (documents, meta_data="json"))]
+pub fn documents_to_arrow_py(py: Python<'_>, documents: Vec<Document>, meta_data: &str) -> PyResult<PyDocumentBatch> {
+    let layout = meta_data.parse()?;
+    let batch = py.allow_threads(|| to_record_batch(&documents, layout))?;
+    Ok(PyDocumentBatch { batch })
+}
+
+/// Converts Arrow data back into documents.
+///
+/// Args:
+///     data (object): Anything implementing the Arrow PyCapsule interface: a `DocumentBatch`,
+///         or a pyarrow `RecordBatch` or `Table`, or a polars `DataFrame`. Only the `content`
+///         column is required.
+///
+/// Returns:
+///     List[Document]: One document per row.
+///
+/// Raises:
+///     TypeError: If `data` does not implement the Arrow PyCapsule interface.
+///     PyValueError: If a column does not fit a document.
+#[pyfunction]
+#[pyo3(name = "documents_from_arrow")]
+pub fn documents_from_arrow_py(py: Python<'_>, data: &Bound<'_, PyAny>) -> PyResult<Vec<Document>> {
+    let (_, batches) = import_batches(data)?;
+    let documents = py.allow_threads(|| {
+        batches.iter().try_fold(Vec::new(), |mut documents, batch| {
+            documents.extend(from_record_batch(batch)?);
+            Ok::<_, ColumnarError>(documents)
+        })
+    })?;
+    Ok(documents)
+}
+
+/// Writes documents to a Snappy-compressed Parquet file, releasing the GIL while working.
+///
+/// Args:
+///     path (Union[str, os.PathLike]): The file to write; it is replaced if it exists.
+///     documents (List[Document]): The documents to write.
+///     meta_data (str): "json" or "struct", as for `documents_to_arrow`. Defaults to "json".
+///
+/// Raises:
+///     PyIOError: If the file cannot be written.
+///     PyValueError: If the documents cannot be converted or encoded.
+#[pyfunction]
+#[pyo3(name = "write_parquet", signature = (path, documents, meta_data="json"))]
+pub fn write_parquet_py(py: Python<'_>, path: PathBuf, documents: Vec<Document>, meta_data: &str) -> PyResult<()> {
+    let layout = meta_data.parse()?;
+    Ok(py.allow_threads(|| write_parquet(&path, &documents, layout))?)
+}
+
+/// Reads the documents of a Parquet file, releasing the GIL while working.
+///
+/// Args:
+///     path (Union[str, os.PathLike]): The file to read. Only the `content` column is required.
+///
+/// Returns:
+///     List[Document]: One document per row.
+///
+/// Raises:
+///     PyFileNotFoundError: If the file does not exist.
+///     PyValueError: If the file is not valid Parquet or a column does not fit a document.
+#[pyfunction]
+#[pyo3(name = "read_parquet")]
+pub fn read_parquet_py(py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+    Ok(py.allow_threads(|| read_parquet(&path))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{LargeStringArray, ListArray};
+    use arrow::datatypes::Float64Type;
+
+    fn doc(id: &str, page: i64, embedding: Option<Vec<f32>>) -> Document {
+        let mut meta_data = HashMap::new();
+        meta_data.insert("page".to_string(), serde_json::json!(page));
+        meta_data.insert("source".to_string(), serde_json::json!(format!("{}.pdf", id)));
+        Document {
+            content: format!("content of {}", id),
+            id: Some(id.to_string()),
+            meta_data,
+            embedding,
+            reranking_score: Some(0.25),
+            ..Default::default()
+        }
+    }
+
+    fn documents() -> Vec<Document> {
+        let mut usage = HashMap::new();
+        usage.insert("tokens".to_string(), serde_json::json!(7));
+        let mut first = doc("a", 1, Some(vec![0.5, 1.5, 2.5]));
+        first.usage = Some(usage);
+        // Fields written by a newer build survive the trip.
+        let mut third = Document { schema_version: Some(9), ..doc("c", 3, Some(vec![-1.0, 0.0, 1.0])) };
+        third.extra.insert("created_at".to_string(), serde_json::json!(1700000000));
+        vec![first, doc("b", 2, None), third]
+    }
+
+    #[test]
+    fn test_record_batch_round_trip() {
+        let documents = documents();
+        for layout in [MetaDataLayout::Json, MetaDataLayout::Struct] {
+            let batch = to_record_batch(&documents, layout).unwrap();
+            assert_eq!(batch.num_rows(), 3);
+            assert_eq!(
+                batch.schema().field_with_name("embedding").unwrap().data_type(),
+                &DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 3)
+            );
+            assert_eq!(from_record_batch(&batch).unwrap(), documents);
+        }
+
+        let batch = to_record_batch(&documents, MetaDataLayout::Struct).unwrap();
+        let meta_data = batch.column_by_name("meta_data").unwrap().as_struct();
+        assert_eq!(meta_data.column_names(), vec!["page", "source"]);
+        let extra = batch.column_by_name("extra").unwrap();
+        assert_eq!(extra.null_count(), 2);
+
+        let empty = to_record_batch(&[], MetaDataLayout::Struct).unwrap();
+        assert_eq!(from_record_batch(&empty).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_lossy_struct_meta_data_is_rejected() {
+        // An integer next to a float would read back as a float.
+        let mut documents = vec![doc("a", 1, None), doc("b", 2, None)];
+        documents[1].meta_data.insert("page".to_string(), serde_json::json!(2.5));
+        let err = to_record_batch(&documents, MetaDataLayout::Struct).unwrap_err();
+        assert!(matches!(err, ColumnarError::InvalidColumn { ref column, .. } if column == "meta_data"), "{}", err);
+        assert!(err.to_string().contains("\"page\""), "{}", err);
+
+        // The JSON layout keeps both as they are.
+        let batch = to_record_batch(&documents, MetaDataLayout::Json).unwrap();
+        assert_eq!(from_record_batch(&batch).unwrap(), documents);
+
+        // Floats alone, and nulls, are fine.
+        documents[0].meta_data.insert("page".to_string(), serde_json::json!(1.5));
+        documents[0].meta_data.insert("draft".to_string(), JsonValue::Null);
+        let batch = to_record_batch(&documents, MetaDataLayout::Struct).unwrap();
+        documents[0].meta_data.remove("draft");
+        assert_eq!(from_record_batch(&batch).unwrap(), documents);
+    }
+
+    #[test]
+    fn test_mismatched_embeddings_are_rejected() {
+        let documents = vec![doc("a", 1, Some(vec![1.0])), doc("b", 2, Some(vec![1.0, 2.0]))];
+        let err = to_record_batch(&documents, MetaDataLayout::Json).unwrap_err();
+        assert!(matches!(err, ColumnarError::InvalidColumn { ref column, .. } if column == "embedding"), "{}", err);
+    }
+
+    #[test]
+    fn test_foreign_batches_are_read() {
+        // Only `content` is required; strings and lists may use other Arrow types.
+        let content: ArrayRef = Arc::new(LargeStringArray::from(vec!["x", "y"]));
+        let embedding: ArrayRef = Arc::new(ListArray::from_iter_primitive::<Float64Type, _, _>(vec![
+            Some(vec![Some(1.0), Some(2.0)]),
+            None,
+        ]));
+        let batch = RecordBatch::try_from_iter([("content", content), ("embedding", embedding)]).unwrap();
+        let documents = from_record_batch(&batch).unwrap();
+        assert_eq!(documents[0].content, "x");
+        assert_eq!(documents[0].embedding, Some(vec![1.0, 2.0]));
+        assert_eq!(documents[1].embedding, None);
+        assert!(documents[1].meta_data.is_empty());
+
+        let batch = RecordBatch::try_from_iter([("id", Arc::new(StringArray::from(vec!["a"])) as ArrayRef)]).unwrap();
+        assert!(matches!(from_record_batch(&batch), Err(ColumnarError::InvalidColumn { .. })));
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let dir = std::env::temp_dir().join(format!("document_parquet_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let documents = documents();
+        for (layout, file) in [(MetaDataLayout::Json, "json.parquet"), (MetaDataLayout::Struct, "struct.parquet")] {
+            let path = dir.join(file);
+            write_parquet(&path, &documents, layout).unwrap();
+            assert_eq!(read_parquet(&path).unwrap(), documents);
+        }
+
+        let no_embeddings: Vec<Document> = documents.into_iter().map(|d| Document { embedding: None, ..d }).collect();
+        let path = dir.join("no_embeddings.parquet");
+        write_parquet(&path, &no_embeddings, MetaDataLayout::Json).unwrap();
+        assert_eq!(read_parquet(&path).unwrap(), no_embeddings);
+
+        assert!(matches!(read_parquet(&dir.join("missing.parquet")), Err(ColumnarError::FileNotFound(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
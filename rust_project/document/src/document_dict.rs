@@ -0,0 +1,184 @@
+//! The `dict` Python sees as `Document.meta_data` and `Document.usage`.
+
+use crate::py_json::{json_to_py, py_dict_to_json, py_to_json};
+use crate::Document;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyString, PyTuple};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// The `Document` field a [`DocumentDict`] writes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DictField {
+    MetaData,
+    Usage,
+}
+
+/// A `dict` that writes every change through to a field of its `Document`,
+/// so that `doc.meta_data["key"] = value` works as it does on the Python
+/// dataclass.
+///
+/// Only changes to the dict itself are written through, one key at a time,
+/// so two views of the same field do not undo each other's writes. Nested
+/// lists and dicts are converted values, so assign them back after changing
+/// them.
+/// Copies (`copy()`, `copy.copy`, pickling) are plain dicts.
+///
+/// This struct is exposed to Python as the `DocumentDict` class.
+#[pyclass(extends = PyDict, name = "DocumentDict")]
+pub struct DocumentDict {
+    document: Py<Document>,
+    field: DictField,
+}
+
+impl DocumentDict {
+    /// A dict holding `map`, writing to `field` of `document`.
+    pub(crate) fn new_bound<'py>(
+        document: &Bound<'py, Document>,
+        field: DictField,
+        map: &HashMap<String, JsonValue>,
+    ) -> PyResult<Bound<'py, DocumentDict>> {
+        let py = document.py();
+        let view = Bound::new(py, DocumentDict { document: document.clone().unbind(), field })?;
+        let dict = view.downcast::<PyDict>()?;
+        for (key, value) in map {
+            dict.set_item(key, json_to_py(py, value))?;
+        }
+        Ok(view)
+    }
+
+    /// Stores `value` under `key` in the document, or removes `key` when
+    /// `value` is None.
+    fn write_item(slf: &Bound<'_, Self>, key: &Bound<'_, PyAny>, value: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+        let key: String = key.extract()?;
+        let value = value.map(py_to_json).transpose()?;
+        let this = slf.borrow();
+        let mut document = this.document.bind(slf.py()).borrow_mut();
+        let map = match this.field {
+            DictField::MetaData => &mut document.meta_data,
+            DictField::Usage if value.is_none() => match document.usage.as_mut() {
+                Some(usage) => usage,
+                None => return Ok(()),
+            },
+            DictField::Usage => document.usage.get_or_insert_with(HashMap::new),
+        };
+        match value {
+            Some(value) => map.insert(key, value),
+            None => map.remove(&key),
+        };
+        Ok(())
+    }
+
+    /// Calls the `dict` method `name` on the dict.
+    fn call_dict<'py>(slf: &Bound<'py, Self>, name: &str, args: &Bound<'py, PyTuple>) -> PyResult<Bound<'py, PyAny>> {
+        let py = slf.py();
+        let method = py.get_type_bound::<PyDict>().getattr(name)?;
+        let args: Vec<Bound<'py, PyAny>> = std::iter::once(slf.as_any().clone()).chain(args.iter()).collect();
+        method.call1(PyTuple::new_bound(py, args))
+    }
+}
+
+/// Checks that `key` and `value` can be stored before the dict changes.
+fn check_item(key: &Bound<'_, PyAny>, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    if !key.is_instance_of::<PyString>() {
+        return Err(PyTypeError::new_err(format!("dict key {} is not a str", key)));
+    }
+    py_to_json(value).map(|_| ())
+}
+
+#[pymethods]
+impl DocumentDict {
+    fn __setitem__(slf: &Bound<'_, Self>, key: &Bound<'_, PyAny>, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        check_item(key, value)?;
+        slf.downcast::<PyDict>()?.set_item(key, value)?;
+        DocumentDict::write_item(slf, key, Some(value))
+    }
+
+    fn __delitem__(slf: &Bound<'_, Self>, key: &Bound<'_, PyAny>) -> PyResult<()> {
+        slf.downcast::<PyDict>()?.del_item(key)?;
+        DocumentDict::write_item(slf, key, None)
+    }
+
+    #[pyo3(signature = (*args, **kwargs))]
+    fn update(slf: &Bound<'_, Self>, args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        // Collect the new items first, so that invalid ones change nothing.
+        let items = PyDict::new_bound(slf.py());
+        items.call_method("update", args, kwargs)?;
+        py_dict_to_json(&items)?;
+        slf.downcast::<PyDict>()?.update(items.as_mapping())?;
+        for (key, value) in items.iter() {
+            DocumentDict::write_item(slf, &key, Some(&value))?;
+        }
+        Ok(())
+    }
+
+    fn __ior__(slf: &Bound<'_, Self>, other: &Bound<'_, PyAny>) -> PyResult<()> {
+        DocumentDict::update(slf, &PyTuple::new_bound(slf.py(), [other]), None)
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn setdefault<'py>(
+        slf: &Bound<'py, Self>,
+        key: &Bound<'py, PyAny>,
+        default: Option<&Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let py = slf.py();
+        let default = default.cloned().unwrap_or_else(|| py.None().into_bound(py));
+        check_item(key, &default)?;
+        if let Some(value) = slf.downcast::<PyDict>()?.get_item(key)? {
+            return Ok(value);
+        }
+        DocumentDict::__setitem__(slf, key, &default)?;
+        Ok(default)
+    }
+
+    #[pyo3(signature = (*args))]
+    fn pop<'py>(slf: &Bound<'py, Self>, args: &Bound<'py, PyTuple>) -> PyResult<Bound<'py, PyAny>> {
+        let key = args.get_item(0).ok();
+        let found = match &key {
+            Some(key) => slf.downcast::<PyDict>()?.contains(key)?,
+            None => false,
+        };
+        let value = DocumentDict::call_dict(slf, "pop", args)?;
+        if let (Some(key), true) = (key, found) {
+            DocumentDict::write_item(slf, &key, None)?;
+        }
+        Ok(value)
+    }
+
+    fn popitem<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        let item = DocumentDict::call_dict(slf, "popitem", &PyTuple::empty_bound(slf.py()))?;
+        DocumentDict::write_item(slf, &item.get_item(0)?, None)?;
+        Ok(item)
+    }
+
+    fn clear(slf: &Bound<'_, Self>) -> PyResult<()> {
+        let dict = slf.downcast::<PyDict>()?;
+        let keys = dict.keys();
+        dict.clear();
+        for key in keys.iter() {
+            DocumentDict::write_item(slf, &key, None)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a plain `dict` with the same items.
+    fn copy<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyDict>> {
+        slf.downcast::<PyDict>()?.copy()
+    }
+
+    fn __copy__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyDict>> {
+        DocumentDict::copy(slf)
+    }
+
+    fn __deepcopy__<'py>(slf: &Bound<'py, Self>, memo: &Bound<'py, PyDict>) -> PyResult<Bound<'py, PyAny>> {
+        let copy = slf.py().import_bound("copy")?;
+        copy.call_method1("deepcopy", (DocumentDict::copy(slf)?, memo))
+    }
+
+    /// Pickles as a plain `dict`.
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyDict>,))> {
+        Ok((slf.py().get_type_bound::<PyDict>().into_any(), (DocumentDict::copy(slf)?,)))
+    }
+}
@@ -1,14 +1,38 @@
 use pyo3::prelude::*;
-use serde::{Serialize, Deserialize};
+use pyo3::pyclass::CompareOp;
+use pyo3::types::{PyBytes, PyDict};
+use chrono::{DateTime, FixedOffset};
+use document_dict::DictField;
+use py_json::{json_map_to_py, json_to_py, py_dict_to_json, py_to_json};
 use std::collections::HashMap;
-use serde_json;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+pub mod binary;
+pub mod bulk;
+pub mod chunking;
+pub mod columnar;
+pub mod document_dict;
+pub mod meta_data;
+pub mod provenance;
+pub mod py_json;
+pub mod reader;
+pub mod versioning;
+#[cfg(test)]
+mod testing;
+
+pub use binary::BinaryFormat;
+pub use document_dict::DocumentDict;
+pub use meta_data::{MetaDataSchema, MetaDataType};
+pub use provenance::ChunkProvenance;
+pub use versioning::DOCUMENT_SCHEMA_VERSION;
 
 /// Represents a document with content and associated metadata.
 ///
 /// This struct is exposed to Python as the `Document` class.
-/// It can be serialized to and deserialized from JSON.
+/// It can be serialized to and deserialized from JSON; see [`versioning`]
+/// for the schema version written alongside the fields.
 #[pyclass(name = "Document")]
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Document {
     /// The main textual content of the document.
     #[pyo3(get, set)]
@@ -16,29 +40,45 @@ pub struct Document {
 
     /// An optional unique identifier for the document.
     #[pyo3(get, set)]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
 
     /// An optional name or title for the document.
     #[pyo3(get, set)]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
 
     /// Optional metadata associated with the document, stored as a map
     /// where keys are strings and values are flexible JSON types.
     /// Defaults to an empty map.
-    #[serde(default)]
     pub meta_data: HashMap<String, serde_json::Value>,
 
+    /// An optional embedding vector of the content.
+    #[pyo3(get, set)]
+    pub embedding: Option<Vec<f32>>,
+
     /// Optional usage information, typically related to processing or embedding.
     /// For example, token counts from an API.
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<HashMap<String, serde_json::Value>>,
 
     /// An optional score, often used for search result ranking.
     #[pyo3(get, set)]
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub reranking_score: Option<f64>,
+
+    /// Serialized fields this version does not know, e.g. from a newer
+    /// version. They are written back unchanged.
+    pub extra: HashMap<String, serde_json::Value>,
+
+    /// The schema version of a document read from a newer version, which it
+    /// is written back with. None for documents of this version or older.
+    pub schema_version: Option<u32>,
+}
+
+impl Document {
+    /// Creates a document with `content` and every other field empty; set
+    /// the rest with struct update syntax, e.g.
+    /// `Document { id: Some(id), ..Document::new(content) }`.
+    pub fn new(content: impl Into<String>) -> Self {
+        Document { content: content.into(), ..Default::default() }
+    }
 }
 
 #[pymethods]
@@ -50,26 +90,70 @@ impl Document {
     ///     id (Optional[str]): An optional unique identifier. Defaults to None.
     ///     name (Optional[str]): An optional name or title. Defaults to None.
     ///     reranking_score (Optional[float]): An optional score. Defaults to None.
+    ///     meta_data (Optional[Dict[str, Any]]): Metadata of the document. Defaults to an empty dict.
+    ///     usage (Optional[Dict[str, Any]]): Usage information, e.g. token counts. Defaults to None.
+    ///     embedding (Optional[List[float]]): The embedding of the content. Defaults to None.
     ///
-    /// Note:
-    ///     `meta_data` and `usage` fields are initialized to their defaults (empty map and None, respectively)
-    ///     and can be modified via methods like `set_meta_data_from_json`.
+    /// Raises:
+    ///     TypeError: If `meta_data` or `usage` holds a value with no JSON equivalent.
     #[new]
-    #[pyo3(signature = (content, id=None, name=None, reranking_score=None))]
+    #[pyo3(signature = (content, id=None, name=None, reranking_score=None, meta_data=None, usage=None, embedding=None))]
     fn py_new(
         content: String,
         id: Option<String>,
         name: Option<String>,
-        reranking_score: Option<f64>
-    ) -> Self {
-        Document {
+        reranking_score: Option<f64>,
+        meta_data: Option<&Bound<'_, PyDict>>,
+        usage: Option<&Bound<'_, PyDict>>,
+        embedding: Option<Vec<f32>>,
+    ) -> PyResult<Self> {
+        Ok(Document {
             content,
             id,
             name,
-            meta_data: HashMap::new(),
-            usage: None,
+            meta_data: meta_data.map(py_dict_to_json).transpose()?.unwrap_or_default(),
+            embedding,
+            usage: usage.map(py_dict_to_json).transpose()?,
             reranking_score,
-        }
+            ..Default::default()
+        })
+    }
+
+    /// The metadata of the document as a dict.
+    ///
+    /// Changes to the dict, e.g. `doc.meta_data["key"] = value`, are written to
+    /// the document. Nested values are copies: assign them back after changing them.
+    ///
+    /// Raises:
+    ///     TypeError: If a value set or assigned has no JSON equivalent.
+    #[getter]
+    fn meta_data<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, DocumentDict>> {
+        let meta_data = slf.borrow().meta_data.clone();
+        DocumentDict::new_bound(slf, DictField::MetaData, &meta_data)
+    }
+
+    #[setter]
+    fn set_meta_data(&mut self, meta_data: &Bound<'_, PyDict>) -> PyResult<()> {
+        self.meta_data = py_dict_to_json(meta_data)?;
+        Ok(())
+    }
+
+    /// Usage information of the document as a dict, or None.
+    ///
+    /// Like `meta_data`, changes to the dict are written to the document.
+    ///
+    /// Raises:
+    ///     TypeError: If a value set or assigned has no JSON equivalent.
+    #[getter]
+    fn usage<'py>(slf: &Bound<'py, Self>) -> PyResult<Option<Bound<'py, DocumentDict>>> {
+        let usage = slf.borrow().usage.clone();
+        usage.map(|usage| DocumentDict::new_bound(slf, DictField::Usage, &usage)).transpose()
+    }
+
+    #[setter]
+    fn set_usage(&mut self, usage: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        self.usage = usage.map(py_dict_to_json).transpose()?;
+        Ok(())
     }
 
     /// Serializes the `Document` instance to a JSON string.
@@ -100,6 +184,331 @@ impl Document {
         serde_json::from_str(json_str).map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))
     }
 
+    /// Returns the fields of the document like the Python dataclass repr.
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        let optional = |value: Option<PyObject>| -> PyResult<String> {
+            match value {
+                Some(value) => Ok(value.bind(py).repr()?.to_string()),
+                None => Ok("None".to_string()),
+            }
+        };
+        Ok(format!(
+            "Document(content={}, id={}, name={}, meta_data={}, embedding={}, usage={}, reranking_score={})",
+            self.content.to_object(py).bind(py).repr()?,
+            optional(self.id.as_ref().map(|id| id.to_object(py)))?,
+            optional(self.name.as_ref().map(|name| name.to_object(py)))?,
+            json_map_to_py(py, &self.meta_data).bind(py).repr()?,
+            optional(self.embedding.as_ref().map(|embedding| embedding.to_object(py)))?,
+            optional(self.usage.as_ref().map(|usage| json_map_to_py(py, usage)))?,
+            optional(self.reranking_score.map(|score| score.to_object(py)))?,
+        ))
+    }
+
+    /// Compares every field, like the Rust `PartialEq`. Only `==` and `!=`
+    /// are supported, and documents never equal objects of other types.
+    fn __richcmp__(&self, other: &Bound<'_, PyAny>, op: CompareOp, py: Python<'_>) -> PyObject {
+        let Ok(other) = other.downcast::<Document>() else {
+            return py.NotImplemented();
+        };
+        match op {
+            CompareOp::Eq => (*self == *other.borrow()).into_py(py),
+            CompareOp::Ne => (*self != *other.borrow()).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    /// Hashes `content`, `id` and `name`, so equal documents hash equally.
+    ///
+    /// Note:
+    ///     Documents are mutable: changing one of these fields while the document
+    ///     is a dict key or in a set changes its hash.
+    fn __hash__(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.content, &self.id, &self.name).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    /// Documents own all their fields, so a deep copy is a plain clone.
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyDict>) -> Self {
+        self.clone()
+    }
+
+    /// Pickle support: the document is rebuilt from its content, then its
+    /// bincode-encoded state is restored.
+    fn __getnewargs__(&self) -> (String,) {
+        (self.content.clone(),)
+    }
+
+    fn __getstate__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.to_bytes_py(py, "bincode")
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        *self = Document::from_bytes(state)?;
+        Ok(())
+    }
+
+    /// Encodes the document in a compact binary format.
+    ///
+    /// The bytes start with a version byte and a format byte, so `from_bytes`
+    /// needs no format and keeps reading documents written by older versions.
+    ///
+    /// Args:
+    ///     format (str): "msgpack" (self-describing MessagePack) or "bincode" (smallest and
+    ///         fastest). Defaults to "msgpack".
+    ///
+    /// Returns:
+    ///     bytes: The encoded document.
+    ///
+    /// Raises:
+    ///     PyValueError: If the format is unknown or encoding fails.
+    #[pyo3(name = "to_bytes", signature = (format="msgpack"))]
+    fn to_bytes_py<'py>(&self, py: Python<'py>, format: &str) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = self.to_bytes(format.parse()?)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Decodes a document encoded by `to_bytes`, in any format.
+    ///
+    /// Args:
+    ///     data (bytes): The encoded document.
+    ///
+    /// Returns:
+    ///     Document: A new instance of the Document.
+    ///
+    /// Raises:
+    ///     PyValueError: If the data is truncated, malformed or from a newer version.
+    #[staticmethod]
+    #[pyo3(name = "from_bytes")]
+    fn from_bytes_py(data: &[u8]) -> PyResult<Self> {
+        Ok(Document::from_bytes(data)?)
+    }
+
+    /// Returns a dict of `content`, `name` and `meta_data`, like Python's
+    /// `Document.to_dict`. `name` is left out when it is None.
+    ///
+    /// Returns:
+    ///     Dict[str, Any]: The dictionary representation of the document.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("content", &self.content)?;
+        if let Some(name) = &self.name {
+            dict.set_item("name", name)?;
+        }
+        dict.set_item("meta_data", json_map_to_py(py, &self.meta_data))?;
+        Ok(dict.into_py(py))
+    }
+
+    /// Creates a `Document` from a dict of constructor arguments, like
+    /// Python's `Document.from_dict`.
+    ///
+    /// The dict is migrated first, like `from_json`, so that dicts parsed from
+    /// `to_json` output, which carry `schema_version`, and older dicts work too.
+    ///
+    /// Args:
+    ///     document (Dict[str, Any]): The constructor arguments, e.g. from `to_dict`.
+    ///
+    /// Returns:
+    ///     Document: A new instance of the Document.
+    ///
+    /// Raises:
+    ///     TypeError: If a key is not a constructor argument, or a value has the wrong type.
+    ///     PyValueError: If `schema_version` is invalid.
+    #[staticmethod]
+    fn from_dict(document: &Bound<'_, PyDict>) -> PyResult<Self> {
+        let py = document.py();
+        let fields = serde_json::Value::Object(py_dict_to_json(document)?.into_iter().collect());
+        let fields = versioning::migrate_document(fields)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(e.to_string()))?;
+        let kwargs = json_to_py(py, &fields);
+        py.get_type_bound::<Document>()
+            .call((), Some(kwargs.bind(py).downcast::<PyDict>()?))?
+            .extract()
+    }
+
+    /// The chunk provenance recorded in `meta_data` by the Rust chunking strategies.
+    ///
+    /// Returns:
+    ///     Optional[ChunkProvenance]: Parent id, chunk index, total chunks and offsets, or None.
+    #[getter(provenance)]
+    fn provenance_py(&self) -> Option<ChunkProvenance> {
+        self.provenance()
+    }
+
+    /// Reads the `meta_data` value at a dotted path, e.g. "source.page" or "authors.0".
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///     default (Any): Returned when the path is missing. Defaults to None.
+    ///
+    /// Returns:
+    ///     Any: The value as a native Python object, or `default`.
+    ///
+    /// Raises:
+    ///     PyValueError: If the path is malformed.
+    #[pyo3(name = "get_path", signature = (path, default=None))]
+    fn get_path_py(&self, py: Python<'_>, path: &str, default: Option<PyObject>) -> PyResult<PyObject> {
+        Ok(match self.get_path(path)? {
+            Some(value) => json_to_py(py, value),
+            None => default.unwrap_or_else(|| py.None()),
+        })
+    }
+
+    /// Reads the string at a dotted `meta_data` path.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Optional[str]: The string, or None if the path is missing or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value is not a string.
+    #[pyo3(name = "get_str")]
+    fn get_str_py(&self, path: &str) -> PyResult<Option<String>> {
+        Ok(self.get_str(path)?.map(str::to_string))
+    }
+
+    /// Reads the integer at a dotted `meta_data` path.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Optional[int]: The integer, or None if the path is missing or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value is not an integer that fits in 64 bits.
+    #[pyo3(name = "get_i64")]
+    fn get_i64_py(&self, path: &str) -> PyResult<Option<i64>> {
+        Ok(self.get_i64(path)?)
+    }
+
+    /// Reads the number at a dotted `meta_data` path.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Optional[float]: The number, or None if the path is missing or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value is not a number.
+    #[pyo3(name = "get_f64")]
+    fn get_f64_py(&self, path: &str) -> PyResult<Option<f64>> {
+        Ok(self.get_f64(path)?)
+    }
+
+    /// Reads the bool at a dotted `meta_data` path.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Optional[bool]: The bool, or None if the path is missing or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value is not a bool.
+    #[pyo3(name = "get_bool")]
+    fn get_bool_py(&self, path: &str) -> PyResult<Option<bool>> {
+        Ok(self.get_bool(path)?)
+    }
+
+    /// Reads the datetime at a dotted `meta_data` path, stored as an RFC 3339
+    /// string or as Unix seconds.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Optional[datetime.datetime]: A timezone-aware datetime, or None if the path is missing or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value is neither a string nor an integer.
+    ///     PyValueError: If the string is not an RFC 3339 datetime.
+    #[pyo3(name = "get_datetime")]
+    fn get_datetime_py(&self, path: &str) -> PyResult<Option<DateTime<FixedOffset>>> {
+        Ok(self.get_datetime(path)?)
+    }
+
+    /// Sets the `meta_data` value at a dotted path, creating missing dicts along it.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into existing lists.
+    ///     value (Any): A JSON-compatible value.
+    ///
+    /// Returns:
+    ///     Any: The value it replaced, or None.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the value has no JSON equivalent, or the path runs through a scalar.
+    ///     PyValueError: If the path is malformed or indexes past the end of a list.
+    #[pyo3(name = "set_path")]
+    fn set_path_py(&mut self, py: Python<'_>, path: &str, value: &Bound<'_, PyAny>) -> PyResult<PyObject> {
+        let replaced = self.set_path(path, py_to_json(value)?)?;
+        Ok(replaced.map_or_else(|| py.None(), |value| json_to_py(py, &value)))
+    }
+
+    /// Stores a datetime at a dotted `meta_data` path as an RFC 3339 string.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into existing lists.
+    ///     value (datetime.datetime): A timezone-aware datetime.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the datetime is naive, or the path runs through a scalar.
+    ///     PyValueError: If the path is malformed.
+    #[pyo3(name = "set_datetime")]
+    fn set_datetime_py(&mut self, path: &str, value: DateTime<FixedOffset>) -> PyResult<()> {
+        self.set_datetime(path, &value)?;
+        Ok(())
+    }
+
+    /// Removes the `meta_data` value at a dotted path.
+    ///
+    /// Args:
+    ///     path (str): Keys separated by dots; digits index into lists.
+    ///
+    /// Returns:
+    ///     Any: The removed value, or None if the path was missing.
+    ///
+    /// Raises:
+    ///     PyValueError: If the path is malformed.
+    #[pyo3(name = "remove_path")]
+    fn remove_path_py(&mut self, py: Python<'_>, path: &str) -> PyResult<PyObject> {
+        Ok(self.remove_path(path)?.map_or_else(|| py.None(), |value| json_to_py(py, &value)))
+    }
+
+    /// Applies a JSON Merge Patch (RFC 7386) to `meta_data`: dicts merge key
+    /// by key, None removes a key, and anything else replaces the value.
+    ///
+    /// Args:
+    ///     patch (dict): The patch, with string keys and JSON-compatible values.
+    ///
+    /// Raises:
+    ///     PyTypeError: If the patch has no JSON equivalent.
+    #[pyo3(name = "merge_meta_data")]
+    fn merge_meta_data_py(&mut self, patch: &Bound<'_, PyDict>) -> PyResult<()> {
+        self.merge_meta_data(&py_dict_to_json(patch)?.into_iter().collect());
+        Ok(())
+    }
+
+    /// Checks `meta_data` against a schema.
+    ///
+    /// Args:
+    ///     schema (MetaDataSchema): The fields the application expects.
+    ///
+    /// Raises:
+    ///     PyValueError: If `meta_data` breaks the schema; the message lists every violation.
+    #[pyo3(name = "validate_meta_data")]
+    fn validate_meta_data_py(&self, schema: PyRef<'_, MetaDataSchema>) -> PyResult<()> {
+        Ok(self.validate_meta_data(&schema)?)
+    }
+
     /// Retrieves the `meta_data` field as a JSON string.
     ///
     /// Returns:
@@ -130,10 +539,43 @@ impl Document {
 ///
 /// This module exposes the `Document` class, allowing creation and manipulation
 /// of document objects from Python, with the underlying implementation in Rust
-/// for performance benefits.
+/// for performance benefits, along with the native chunking strategies and readers.
 #[pymodule]
-fn document(_py: Python, m: &PyModule) -> PyResult<()> {
+fn document(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    register(m)
+}
+
+/// Adds the classes and functions of the `document` module to `m`, so that
+/// other extension modules can embed them.
+///
+/// `Document.__module__` is set to the name of `m`, so that pickle can find the
+/// class again; `m` must therefore be importable under its name.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<Document>()?;
+    m.getattr("Document")?.setattr("__module__", m.name()?)?;
+    m.add_class::<DocumentDict>()?;
+    m.add_class::<ChunkProvenance>()?;
+    m.add_class::<MetaDataSchema>()?;
+    m.add_class::<chunking::CodeChunking>()?;
+    m.add_function(wrap_pyfunction!(chunking::reassemble_chunks_py, m)?)?;
+    m.add_function(wrap_pyfunction!(chunking::batch::chunk_documents_py, m)?)?;
+    m.add_function(wrap_pyfunction!(bulk::documents_to_json_py, m)?)?;
+    m.add_function(wrap_pyfunction!(bulk::documents_from_json_py, m)?)?;
+    m.add_function(wrap_pyfunction!(bulk::write_documents_py, m)?)?;
+    m.add_function(wrap_pyfunction!(bulk::read_documents_py, m)?)?;
+    m.add_class::<columnar::PyDocumentBatch>()?;
+    m.add_function(wrap_pyfunction!(columnar::documents_to_arrow_py, m)?)?;
+    m.add_function(wrap_pyfunction!(columnar::documents_from_arrow_py, m)?)?;
+    m.add_function(wrap_pyfunction!(columnar::write_parquet_py, m)?)?;
+    m.add_function(wrap_pyfunction!(columnar::read_parquet_py, m)?)?;
+    m.add("DOCUMENT_SCHEMA_VERSION", DOCUMENT_SCHEMA_VERSION)?;
+    m.add_class::<reader::TextReader>()?;
+    m.add_class::<reader::MarkdownReader>()?;
+    m.add_class::<reader::JsonReader>()?;
+    m.add_class::<reader::CsvReader>()?;
+    m.add_class::<reader::PdfReader>()?;
+    m.add_class::<reader::DocxReader>()?;
+    m.add_class::<reader::HtmlReader>()?;
     Ok(())
 }
 
@@ -152,8 +594,11 @@ mod tests {
             id: Some("123".to_string()),
             name: Some("Test Document".to_string()),
             meta_data: meta.clone(),
+            embedding: None,
             usage: None,
             reranking_score: Some(0.95),
+            extra: HashMap::new(),
+            schema_version: None,
         };
 
         let serialized_json = serde_json::to_string(&doc).unwrap();
@@ -176,11 +621,14 @@ mod tests {
             id: None,
             name: None,
             meta_data: HashMap::new(),
+            embedding: None,
             usage: None,
             reranking_score: None,
+            extra: HashMap::new(),
+            schema_version: None,
         };
         let serialized_json = serde_json::to_string(&doc).unwrap();
-        assert_eq!(serialized_json, r#"{"content":"Minimal content","meta_data":{}}"#);
+        assert_eq!(serialized_json, r#"{"schema_version":2,"content":"Minimal content","meta_data":{}}"#);
 
         let deserialized_doc: Document = serde_json::from_str(&serialized_json).unwrap();
         assert_eq!(doc, deserialized_doc);
@@ -199,8 +647,11 @@ mod tests {
             id: None,
             name: None,
             meta_data: HashMap::new(),
+            embedding: None,
             usage: None,
             reranking_score: None,
+            extra: HashMap::new(),
+            schema_version: None,
         };
         let serialized_json = serde_json::to_string(&doc).unwrap();
         assert!(serialized_json.contains("\"meta_data\":{}"));
@@ -219,8 +670,11 @@ mod tests {
             id: None,
             name: None,
             meta_data: HashMap::new(),
+            embedding: None,
             usage: None,
             reranking_score: None,
+            extra: HashMap::new(),
+            schema_version: None,
         };
         assert_eq!(deserialized_doc, expected_doc);
     }
@@ -246,9 +700,32 @@ mod tests {
             id: Some("doc_id_001".to_string()),
             name: Some("Full Doc".to_string()),
             meta_data: meta,
+            embedding: None,
             usage: Some(usage_map),
             reranking_score: Some(0.88),
+            extra: HashMap::new(),
+            schema_version: None,
         };
         assert_eq!(deserialized_doc, expected_doc);
     }
+
+    #[test]
+    fn test_document_embedding_serialization() {
+        let doc = Document {
+            content: "Embedded".to_string(),
+            id: None,
+            name: None,
+            meta_data: HashMap::new(),
+            embedding: Some(vec![0.5, -1.0]),
+            usage: None,
+            reranking_score: None,
+            extra: HashMap::new(),
+            schema_version: None,
+        };
+        let serialized_json = serde_json::to_string(&doc).unwrap();
+        assert_eq!(serialized_json, r#"{"schema_version":2,"content":"Embedded","meta_data":{},"embedding":[0.5,-1.0]}"#);
+
+        let deserialized_doc: Document = serde_json::from_str(&serialized_json).unwrap();
+        assert_eq!(doc, deserialized_doc);
+    }
 }
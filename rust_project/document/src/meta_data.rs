@@ -0,0 +1,679 @@
+//! Typed access to `Document.meta_data`, merge patches and schema checks.
+//!
+//! Paths are dotted keys: `"source.page"` reads `meta_data["source"]["page"]`,
+//! and a segment made of digits indexes into an array (`"authors.0"`). Keys
+//! that contain a dot are only reachable through `meta_data` itself.
+
+use crate::Document;
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Define a custom error type for meta_data access
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaDataError {
+    InvalidPath(String),
+    /// The value at `path` is not of the `expected` type.
+    TypeMismatch { path: String, expected: MetaDataType, found: String },
+    /// A segment of `path` runs through something that is not an object or array.
+    NotAContainer(String),
+    InvalidDateTime { path: String, message: String },
+    UnknownType(String),
+    /// Every violation found by a schema check.
+    Schema(Vec<String>),
+}
+
+impl fmt::Display for MetaDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MetaDataError::InvalidPath(path) => write!(f, "Invalid meta_data path: {:?}", path),
+            MetaDataError::TypeMismatch { path, expected, found } => {
+                write!(f, "meta_data[{:?}] is {}, expected {}", path, found, expected)
+            }
+            MetaDataError::NotAContainer(path) => {
+                write!(f, "meta_data[{:?}] is neither an object nor an array", path)
+            }
+            MetaDataError::InvalidDateTime { path, message } => {
+                write!(f, "meta_data[{:?}] is not an RFC 3339 datetime: {}", path, message)
+            }
+            MetaDataError::UnknownType(name) => write!(f, "Unknown meta_data type: {}", name),
+            MetaDataError::Schema(violations) => {
+                write!(f, "meta_data does not match the schema: {}", violations.join("; "))
+            }
+        }
+    }
+}
+
+impl Error for MetaDataError {}
+
+impl From<MetaDataError> for PyErr {
+    fn from(err: MetaDataError) -> PyErr {
+        match err {
+            MetaDataError::TypeMismatch { .. } | MetaDataError::NotAContainer(_) => PyTypeError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// The types a schema can require of a `meta_data` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetaDataType {
+    String,
+    /// A number that fits in an `i64`.
+    Integer,
+    /// Any number.
+    Float,
+    Boolean,
+    /// An RFC 3339 string, or an integer of Unix seconds.
+    DateTime,
+    Array,
+    Object,
+    /// Anything but null.
+    Any,
+}
+
+impl MetaDataType {
+    /// The narrowest type of `value`, or `None` for null. Strings are
+    /// [`MetaDataType::String`] even when they hold a datetime.
+    pub fn of(value: &JsonValue) -> Option<Self> {
+        match value {
+            JsonValue::Null => None,
+            JsonValue::Bool(_) => Some(MetaDataType::Boolean),
+            JsonValue::Number(number) if number.is_i64() => Some(MetaDataType::Integer),
+            JsonValue::Number(_) => Some(MetaDataType::Float),
+            JsonValue::String(_) => Some(MetaDataType::String),
+            JsonValue::Array(_) => Some(MetaDataType::Array),
+            JsonValue::Object(_) => Some(MetaDataType::Object),
+        }
+    }
+
+    /// The name used in Python schemas and error messages.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetaDataType::String => "str",
+            MetaDataType::Integer => "int",
+            MetaDataType::Float => "float",
+            MetaDataType::Boolean => "bool",
+            MetaDataType::DateTime => "datetime",
+            MetaDataType::Array => "list",
+            MetaDataType::Object => "dict",
+            MetaDataType::Any => "any",
+        }
+    }
+
+    /// Whether `value` is of this type.
+    pub fn matches(self, value: &JsonValue) -> bool {
+        match self {
+            MetaDataType::String => value.is_string(),
+            MetaDataType::Integer => value.is_i64(),
+            MetaDataType::Float => value.is_number(),
+            MetaDataType::Boolean => value.is_boolean(),
+            MetaDataType::DateTime => parse_datetime("", value).is_ok(),
+            MetaDataType::Array => value.is_array(),
+            MetaDataType::Object => value.is_object(),
+            MetaDataType::Any => !value.is_null(),
+        }
+    }
+}
+
+impl fmt::Display for MetaDataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MetaDataType {
+    type Err = MetaDataError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "str" => Ok(MetaDataType::String),
+            "int" => Ok(MetaDataType::Integer),
+            "float" => Ok(MetaDataType::Float),
+            "bool" => Ok(MetaDataType::Boolean),
+            "datetime" => Ok(MetaDataType::DateTime),
+            "list" => Ok(MetaDataType::Array),
+            "dict" => Ok(MetaDataType::Object),
+            "any" => Ok(MetaDataType::Any),
+            _ => Err(MetaDataError::UnknownType(s.to_string())),
+        }
+    }
+}
+
+/// Describes a JSON value for error messages, e.g. `a string`.
+fn describe(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Null => "null".to_string(),
+        JsonValue::Bool(_) => "a bool".to_string(),
+        JsonValue::Number(n) if n.is_i64() || n.is_u64() => "an int".to_string(),
+        JsonValue::Number(_) => "a float".to_string(),
+        JsonValue::String(_) => "a str".to_string(),
+        JsonValue::Array(_) => "a list".to_string(),
+        JsonValue::Object(_) => "a dict".to_string(),
+    }
+}
+
+fn segments(path: &str) -> Result<Vec<&str>, MetaDataError> {
+    let segments: Vec<&str> = path.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(MetaDataError::InvalidPath(path.to_string()));
+    }
+    Ok(segments)
+}
+
+fn child<'a>(value: &'a JsonValue, segment: &str) -> Option<&'a JsonValue> {
+    match value {
+        JsonValue::Object(map) => map.get(segment),
+        JsonValue::Array(values) => segment.parse::<usize>().ok().and_then(|index| values.get(index)),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(value: &'a mut JsonValue, segment: &str) -> Option<&'a mut JsonValue> {
+    match value {
+        JsonValue::Object(map) => map.get_mut(segment),
+        JsonValue::Array(values) => segment.parse::<usize>().ok().and_then(|index| values.get_mut(index)),
+        _ => None,
+    }
+}
+
+/// Reads a datetime: an RFC 3339 string, or an integer of Unix seconds (UTC).
+fn parse_datetime(path: &str, value: &JsonValue) -> Result<DateTime<FixedOffset>, MetaDataError> {
+    let invalid = |message: String| MetaDataError::InvalidDateTime { path: path.to_string(), message };
+    match value {
+        JsonValue::String(text) => DateTime::parse_from_rfc3339(text).map_err(|e| invalid(e.to_string())),
+        JsonValue::Number(number) => number
+            .as_i64()
+            .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+            .map(|datetime| datetime.fixed_offset())
+            .ok_or_else(|| invalid(format!("{} is not a valid Unix timestamp", number))),
+        _ => Err(MetaDataError::TypeMismatch {
+            path: path.to_string(),
+            expected: MetaDataType::DateTime,
+            found: describe(value),
+        }),
+    }
+}
+
+/// Applies a JSON Merge Patch (RFC 7386) to `target`: objects merge key by
+/// key, null removes a key, and anything else replaces the target.
+pub fn merge_patch(target: &mut JsonValue, patch: &JsonValue) {
+    let JsonValue::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = JsonValue::Object(Map::new());
+    }
+    if let JsonValue::Object(target) = target {
+        merge_patch_map(target, patch);
+    }
+}
+
+fn merge_patch_map(target: &mut Map<String, JsonValue>, patch: &Map<String, JsonValue>) {
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(JsonValue::Null), value);
+        }
+    }
+}
+
+/// The value at a dotted `path` of `meta_data`, if present.
+fn lookup<'a>(meta_data: &'a HashMap<String, JsonValue>, path: &str) -> Result<Option<&'a JsonValue>, MetaDataError> {
+    let segments = segments(path)?;
+    let mut value = match meta_data.get(segments[0]) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    for segment in &segments[1..] {
+        match child(value, segment) {
+            Some(next) => value = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+impl Document {
+    /// The `meta_data` value at a dotted `path`, if present.
+    pub fn get_path(&self, path: &str) -> Result<Option<&JsonValue>, MetaDataError> {
+        lookup(&self.meta_data, path)
+    }
+
+    /// Reads the value at `path` with `read`, treating null as missing.
+    fn get_typed<'a, T>(
+        &'a self,
+        path: &str,
+        expected: MetaDataType,
+        read: impl FnOnce(&'a JsonValue) -> Option<T>,
+    ) -> Result<Option<T>, MetaDataError> {
+        match self.get_path(path)? {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(value) => read(value).map(Some).ok_or_else(|| MetaDataError::TypeMismatch {
+                path: path.to_string(),
+                expected,
+                found: describe(value),
+            }),
+        }
+    }
+
+    /// The string at `path`; `None` if missing or null, an error if not a string.
+    pub fn get_str(&self, path: &str) -> Result<Option<&str>, MetaDataError> {
+        self.get_typed(path, MetaDataType::String, JsonValue::as_str)
+    }
+
+    /// The integer at `path`; `None` if missing or null, an error if not an `i64`.
+    pub fn get_i64(&self, path: &str) -> Result<Option<i64>, MetaDataError> {
+        self.get_typed(path, MetaDataType::Integer, JsonValue::as_i64)
+    }
+
+    /// The number at `path`; `None` if missing or null, an error if not a number.
+    pub fn get_f64(&self, path: &str) -> Result<Option<f64>, MetaDataError> {
+        self.get_typed(path, MetaDataType::Float, JsonValue::as_f64)
+    }
+
+    /// The bool at `path`; `None` if missing or null, an error if not a bool.
+    pub fn get_bool(&self, path: &str) -> Result<Option<bool>, MetaDataError> {
+        self.get_typed(path, MetaDataType::Boolean, JsonValue::as_bool)
+    }
+
+    /// The datetime at `path`, stored as an RFC 3339 string or as Unix
+    /// seconds; `None` if missing or null.
+    pub fn get_datetime(&self, path: &str) -> Result<Option<DateTime<FixedOffset>>, MetaDataError> {
+        match self.get_path(path)? {
+            None | Some(JsonValue::Null) => Ok(None),
+            Some(value) => parse_datetime(path, value).map(Some),
+        }
+    }
+
+    /// Sets the value at a dotted `path`, creating the missing objects along
+    /// it. Returns the value it replaced.
+    pub fn set_path(&mut self, path: &str, value: impl Into<JsonValue>) -> Result<Option<JsonValue>, MetaDataError> {
+        let segments = segments(path)?;
+        let (last, parents) = segments.split_last().expect("split yields at least one segment");
+        if parents.is_empty() {
+            return Ok(self.meta_data.insert(last.to_string(), value.into()));
+        }
+        let mut current = self
+            .meta_data
+            .entry(parents[0].to_string())
+            .or_insert_with(|| JsonValue::Object(Map::new()));
+        for (depth, segment) in parents.iter().enumerate().skip(1) {
+            if current.is_null() {
+                *current = JsonValue::Object(Map::new());
+            }
+            current = match current {
+                JsonValue::Object(map) => map.entry(segment.to_string()).or_insert_with(|| JsonValue::Object(Map::new())),
+                JsonValue::Array(_) => child_mut(current, segment)
+                    .ok_or_else(|| MetaDataError::InvalidPath(segments[..=depth].join(".")))?,
+                _ => return Err(MetaDataError::NotAContainer(segments[..depth].join("."))),
+            };
+        }
+        if current.is_null() {
+            *current = JsonValue::Object(Map::new());
+        }
+        match current {
+            JsonValue::Object(map) => Ok(map.insert(last.to_string(), value.into())),
+            JsonValue::Array(values) => {
+                let slot = last
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| values.get_mut(index))
+                    .ok_or_else(|| MetaDataError::InvalidPath(path.to_string()))?;
+                Ok(Some(std::mem::replace(slot, value.into())))
+            }
+            _ => Err(MetaDataError::NotAContainer(parents.join("."))),
+        }
+    }
+
+    /// Stores a datetime at `path` as an RFC 3339 string.
+    pub fn set_datetime(
+        &mut self,
+        path: &str,
+        datetime: &DateTime<FixedOffset>,
+    ) -> Result<Option<JsonValue>, MetaDataError> {
+        self.set_path(path, datetime.to_rfc3339_opts(SecondsFormat::AutoSi, false))
+    }
+
+    /// Removes the value at a dotted `path` from its object, returning it.
+    pub fn remove_path(&mut self, path: &str) -> Result<Option<JsonValue>, MetaDataError> {
+        let segments = segments(path)?;
+        let (last, parents) = segments.split_last().expect("split yields at least one segment");
+        if parents.is_empty() {
+            return Ok(self.meta_data.remove(*last));
+        }
+        let mut current = match self.meta_data.get_mut(parents[0]) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        for segment in &parents[1..] {
+            match child_mut(current, segment) {
+                Some(next) => current = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(current.as_object_mut().and_then(|map| map.remove(*last)))
+    }
+
+    /// Applies a JSON Merge Patch (RFC 7386) to `meta_data`.
+    pub fn merge_meta_data(&mut self, patch: &Map<String, JsonValue>) {
+        let mut target: Map<String, JsonValue> = std::mem::take(&mut self.meta_data).into_iter().collect();
+        merge_patch_map(&mut target, patch);
+        self.meta_data = target.into_iter().collect();
+    }
+
+    /// Checks `meta_data` against `schema`.
+    pub fn validate_meta_data(&self, schema: &MetaDataSchema) -> Result<(), MetaDataError> {
+        schema.validate(self)
+    }
+}
+
+/// The fields an application expects in `meta_data`, by dotted path.
+///
+/// This struct is exposed to Python as the `MetaDataSchema` class.
+#[pyclass(name = "MetaDataSchema")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MetaDataSchema {
+    /// `(path, type, required)` for each field, in the order they were added.
+    fields: Vec<(String, MetaDataType, bool)>,
+    /// Whether top-level keys outside the schema are accepted.
+    allow_unknown: bool,
+}
+
+impl MetaDataSchema {
+    /// A schema without fields that accepts unknown keys.
+    pub fn new() -> Self {
+        MetaDataSchema { fields: Vec::new(), allow_unknown: true }
+    }
+
+    /// Adds a field that must be present and not null, replacing an
+    /// earlier field with the same path.
+    pub fn with_required(self, path: &str, kind: MetaDataType) -> Self {
+        self.with_field(path, kind, true)
+    }
+
+    /// Adds a field that may be missing or null, replacing an earlier field
+    /// with the same path.
+    pub fn with_optional(self, path: &str, kind: MetaDataType) -> Self {
+        self.with_field(path, kind, false)
+    }
+
+    fn with_field(mut self, path: &str, kind: MetaDataType, required: bool) -> Self {
+        match self.fields.iter_mut().find(|(known, _, _)| known == path) {
+            Some(field) => *field = (path.to_string(), kind, required),
+            None => self.fields.push((path.to_string(), kind, required)),
+        }
+        self
+    }
+
+    /// Sets whether top-level keys outside the schema are accepted.
+    pub fn with_allow_unknown(mut self, allow_unknown: bool) -> Self {
+        self.allow_unknown = allow_unknown;
+        self
+    }
+
+    /// The type of the field at `path`, if it is in the schema.
+    pub fn field(&self, path: &str) -> Option<MetaDataType> {
+        self.fields.iter().find(|(known, _, _)| known == path).map(|(_, kind, _)| *kind)
+    }
+
+    /// The path and type of each field, in the order they were added.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, MetaDataType)> {
+        self.fields.iter().map(|(path, kind, _)| (path.as_str(), *kind))
+    }
+
+    /// Whether top-level keys outside the schema are accepted.
+    pub fn allows_unknown(&self) -> bool {
+        self.allow_unknown
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Every way `document.meta_data` breaks the schema, in field order.
+    pub fn violations(&self, document: &Document) -> Vec<String> {
+        self.meta_data_violations(&document.meta_data)
+    }
+
+    /// Every way `meta_data` breaks the schema, in field order.
+    pub fn meta_data_violations(&self, meta_data: &HashMap<String, JsonValue>) -> Vec<String> {
+        let mut violations = Vec::new();
+        for (path, kind, required) in &self.fields {
+            match lookup(meta_data, path) {
+                Err(err) => violations.push(err.to_string()),
+                Ok(None) | Ok(Some(JsonValue::Null)) => {
+                    if *required {
+                        violations.push(format!("{} is required", path));
+                    }
+                }
+                Ok(Some(value)) if !kind.matches(value) => {
+                    violations.push(format!("{} is {}, expected {}", path, describe(value), kind));
+                }
+                Ok(Some(_)) => {}
+            }
+        }
+        if !self.allow_unknown {
+            let mut unknown: Vec<&String> = meta_data
+                .keys()
+                .filter(|key| !self.fields.iter().any(|(path, _, _)| path.split('.').next() == Some(key.as_str())))
+                .collect();
+            unknown.sort();
+            violations.extend(unknown.into_iter().map(|key| format!("{} is not in the schema", key)));
+        }
+        violations
+    }
+
+    /// Checks `document.meta_data`, reporting every violation at once.
+    pub fn validate(&self, document: &Document) -> Result<(), MetaDataError> {
+        self.validate_meta_data(&document.meta_data)
+    }
+
+    /// Checks `meta_data`, reporting every violation at once.
+    pub fn validate_meta_data(&self, meta_data: &HashMap<String, JsonValue>) -> Result<(), MetaDataError> {
+        let violations = self.meta_data_violations(meta_data);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(MetaDataError::Schema(violations))
+        }
+    }
+}
+
+#[pymethods]
+impl MetaDataSchema {
+    /// Creates a schema for `meta_data`.
+    ///
+    /// Args:
+    ///     fields (Dict[str, str]): The type of each dotted path: "str", "int", "float",
+    ///         "bool", "datetime", "list", "dict" or "any".
+    ///     required (Optional[List[str]]): The paths that must be present and not None.
+    ///         Defaults to None.
+    ///     allow_unknown (bool): Whether top-level keys outside `fields` are accepted.
+    ///         Defaults to True.
+    ///
+    /// Raises:
+    ///     PyValueError: If a type name is unknown, or a required path is not in `fields`.
+    #[new]
+    #[pyo3(signature = (fields, required=None, allow_unknown=true))]
+    fn py_new(fields: HashMap<String, String>, required: Option<Vec<String>>, allow_unknown: bool) -> PyResult<Self> {
+        let required = required.unwrap_or_default();
+        if let Some(path) = required.iter().find(|path| !fields.contains_key(*path)) {
+            return Err(PyValueError::new_err(format!("Required path {:?} is not in fields", path)));
+        }
+        let mut paths: Vec<(String, String)> = fields.into_iter().collect();
+        paths.sort();
+        let mut schema = MetaDataSchema::new().with_allow_unknown(allow_unknown);
+        for (path, kind) in paths {
+            let kind = kind.parse::<MetaDataType>()?;
+            schema = if required.contains(&path) {
+                schema.with_required(&path, kind)
+            } else {
+                schema.with_optional(&path, kind)
+            };
+        }
+        Ok(schema)
+    }
+
+    /// Lists the ways a document's `meta_data` breaks the schema.
+    ///
+    /// Args:
+    ///     document (Document): The document to check.
+    ///
+    /// Returns:
+    ///     List[str]: One message per violation; empty if the document matches.
+    #[pyo3(name = "violations")]
+    fn violations_py(&self, document: PyRef<'_, Document>) -> Vec<String> {
+        self.violations(&document)
+    }
+
+    /// Checks a document's `meta_data`.
+    ///
+    /// Args:
+    ///     document (Document): The document to check.
+    ///
+    /// Raises:
+    ///     PyValueError: If the document breaks the schema; the message lists every violation.
+    #[pyo3(name = "validate")]
+    fn validate_py(&self, document: PyRef<'_, Document>) -> PyResult<()> {
+        Ok(self.validate(&document)?)
+    }
+
+    fn __repr__(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(path, kind, required)| format!("{}: {}{}", path, kind, if *required { "" } else { "?" }))
+            .collect();
+        format!("MetaDataSchema({{{}}}, allow_unknown={})", fields.join(", "), if self.allow_unknown { "True" } else { "False" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(meta_data: JsonValue) -> Document {
+        Document {
+            content: "meta".to_string(),
+            meta_data: serde_json::from_value(meta_data).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_typed_getters_and_paths() {
+        let document = document(json!({
+            "source": {"url": "https://example.com", "page": 3, "score": 0.5},
+            "authors": [{"name": "Ada"}],
+            "draft": false,
+            "missing": null,
+            "created": "2024-05-01T12:30:00+02:00",
+            "updated": 1700000000
+        }));
+        assert_eq!(document.get_str("source.url").unwrap(), Some("https://example.com"));
+        assert_eq!(document.get_i64("source.page").unwrap(), Some(3));
+        assert_eq!(document.get_f64("source.page").unwrap(), Some(3.0));
+        assert_eq!(document.get_f64("source.score").unwrap(), Some(0.5));
+        assert_eq!(document.get_bool("draft").unwrap(), Some(false));
+        assert_eq!(document.get_str("authors.0.name").unwrap(), Some("Ada"));
+        assert_eq!(document.get_str("authors.1.name").unwrap(), None);
+        assert_eq!(document.get_str("missing").unwrap(), None);
+        assert_eq!(document.get_str("source.url.host").unwrap(), None);
+
+        assert_eq!(document.get_datetime("created").unwrap().unwrap().to_rfc3339(), "2024-05-01T12:30:00+02:00");
+        assert_eq!(document.get_datetime("updated").unwrap().unwrap().timestamp(), 1700000000);
+
+        assert_eq!(
+            document.get_i64("source.score"),
+            Err(MetaDataError::TypeMismatch {
+                path: "source.score".to_string(),
+                expected: MetaDataType::Integer,
+                found: "a float".to_string()
+            })
+        );
+        assert!(matches!(document.get_datetime("source.url"), Err(MetaDataError::InvalidDateTime { .. })));
+        assert_eq!(document.get_str("source..url"), Err(MetaDataError::InvalidPath("source..url".to_string())));
+    }
+
+    #[test]
+    fn test_set_and_remove_paths() {
+        let mut document = document(json!({"tags": ["a", "b"], "title": "t"}));
+        assert_eq!(document.set_path("source.page", 4).unwrap(), None);
+        assert_eq!(document.set_path("source.page", 5).unwrap(), Some(json!(4)));
+        assert_eq!(document.set_path("tags.1", "c").unwrap(), Some(json!("b")));
+        assert_eq!(document.set_path("tags.5", "x"), Err(MetaDataError::InvalidPath("tags.5".to_string())));
+        assert_eq!(document.set_path("title.text", "x"), Err(MetaDataError::NotAContainer("title".to_string())));
+
+        let datetime = DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap();
+        document.set_datetime("source.fetched", &datetime).unwrap();
+        assert_eq!(document.get_str("source.fetched").unwrap(), Some("2024-01-02T03:04:05+00:00"));
+        assert_eq!(document.get_datetime("source.fetched").unwrap(), Some(datetime));
+
+        assert_eq!(document.remove_path("source.page").unwrap(), Some(json!(5)));
+        assert_eq!(document.remove_path("source.page").unwrap(), None);
+        assert_eq!(document.remove_path("nothing.here").unwrap(), None);
+        assert_eq!(
+            serde_json::to_value(&document.meta_data).unwrap(),
+            json!({"tags": ["a", "c"], "title": "t", "source": {"fetched": "2024-01-02T03:04:05+00:00"}})
+        );
+    }
+
+    #[test]
+    fn test_merge_patch_follows_rfc_7386() {
+        // Examples from appendix A of RFC 7386.
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "b"}), json!({"b": "c"}), json!({"a": "b", "b": "c"})),
+            (json!({"a": "b", "b": "c"}), json!({"a": null}), json!({"b": "c"})),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": {"b": "c"}}), json!({"a": {"b": "d", "c": null}}), json!({"a": {"b": "d"}})),
+            (json!({"a": "foo"}), json!({"a": {"bb": {"ccc": null}}}), json!({"a": {"bb": {}}})),
+            (json!({"e": null}), json!({"a": 1}), json!({"e": null, "a": 1})),
+        ];
+        for (target, patch, expected) in cases {
+            let mut document = document(target);
+            document.merge_meta_data(patch.as_object().unwrap());
+            assert_eq!(serde_json::to_value(&document.meta_data).unwrap(), expected);
+        }
+
+        let mut value = json!({"a": 1});
+        merge_patch(&mut value, &json!(["c"]));
+        assert_eq!(value, json!(["c"]));
+    }
+
+    #[test]
+    fn test_schema_reports_every_violation() {
+        let schema = MetaDataSchema::new()
+            .with_required("source.url", MetaDataType::String)
+            .with_required("page", MetaDataType::Integer)
+            .with_optional("created", MetaDataType::DateTime)
+            .with_optional("tags", MetaDataType::Array);
+        let valid = document(json!({"source": {"url": "u"}, "page": 1, "created": "2024-05-01T00:00:00Z", "x": 1}));
+        assert_eq!(valid.validate_meta_data(&schema), Ok(()));
+
+        let invalid = document(json!({"page": "one", "created": "yesterday", "tags": null, "x": 1}));
+        assert_eq!(
+            schema.violations(&invalid),
+            vec!["source.url is required", "page is a str, expected int", "created is a str, expected datetime"]
+        );
+        let strict = schema.with_allow_unknown(false);
+        assert_eq!(strict.violations(&valid), vec!["x is not in the schema"]);
+        assert!(matches!(strict.validate(&invalid), Err(MetaDataError::Schema(v)) if v.len() == 4));
+        assert_eq!("blob".parse::<MetaDataType>(), Err(MetaDataError::UnknownType("blob".to_string())));
+
+        let replaced = strict.with_optional("page", MetaDataType::Float);
+        assert_eq!(replaced.field("page"), Some(MetaDataType::Float));
+        assert_eq!(replaced.fields().count(), 4);
+        assert_eq!(MetaDataType::of(&json!(1.5)), Some(MetaDataType::Float));
+        assert_eq!(MetaDataType::of(&json!(null)), None);
+    }
+}
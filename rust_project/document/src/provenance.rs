@@ -0,0 +1,104 @@
+use crate::Document;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Key under which `ChunkProvenance` is stored in `Document.meta_data`.
+pub const PROVENANCE_KEY: &str = "provenance";
+
+/// Where a chunk came from within its parent document.
+///
+/// Offsets are half-open ranges (`start..end`) into the parent's `content`,
+/// both in bytes (for Rust slicing) and in characters (for Python indexing).
+/// Stored in `meta_data["provenance"]` so it survives a round-trip through
+/// the Python `Document` dataclass.
+///
+/// Parent text that no chunk covers, such as blank lines or indentation a
+/// strategy trimmed, is kept verbatim so the parent can be rebuilt exactly.
+#[pyclass(name = "ChunkProvenance", get_all)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChunkProvenance {
+    /// The id of the parent document, if it had one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Zero-based position of the chunk among its siblings.
+    pub chunk_index: usize,
+    /// Number of chunks the parent document was split into.
+    pub total_chunks: usize,
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    /// Parent text between the chunks before this one and this chunk.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub skipped_before: String,
+    /// Parent text after the last chunk; empty on every other chunk.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub skipped_after: String,
+}
+
+impl Document {
+    /// Reads the chunk provenance from `meta_data`, if present and well-formed.
+    pub fn provenance(&self) -> Option<ChunkProvenance> {
+        self.meta_data
+            .get(PROVENANCE_KEY)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// Stores the chunk provenance in `meta_data`.
+    pub fn set_provenance(&mut self, provenance: &ChunkProvenance) {
+        // Serializing a struct of strings and integers cannot fail.
+        if let Ok(value) = serde_json::to_value(provenance) {
+            self.meta_data.insert(PROVENANCE_KEY.to_string(), value);
+        }
+    }
+}
+
+#[pymethods]
+impl ChunkProvenance {
+    fn __repr__(&self) -> String {
+        let parent_id = match &self.parent_id {
+            Some(id) => format!("{:?}", id),
+            None => "None".to_string(),
+        };
+        format!(
+            "ChunkProvenance(parent_id={}, chunk_index={}, total_chunks={}, char_start={}, char_end={})",
+            parent_id, self.chunk_index, self.total_chunks, self.char_start, self.char_end
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provenance_round_trip_through_meta_data() {
+        let mut document = Document {
+            content: "chunk".to_string(),
+            id: Some("parent_1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(document.provenance(), None);
+
+        let provenance = ChunkProvenance {
+            parent_id: Some("parent".to_string()),
+            chunk_index: 0,
+            total_chunks: 3,
+            byte_start: 0,
+            byte_end: 5,
+            char_start: 0,
+            char_end: 5,
+            skipped_before: String::new(),
+            skipped_after: "\n".to_string(),
+        };
+        document.set_provenance(&provenance);
+        assert_eq!(document.provenance(), Some(provenance));
+
+        let json = serde_json::to_string(&document).unwrap();
+        assert!(json.contains(r#""provenance":{"#));
+        assert!(json.contains(r#""total_chunks":3"#));
+        assert!(!json.contains("skipped_before"));
+        let restored: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.provenance(), document.provenance());
+    }
+}
@@ -0,0 +1,101 @@
+//! Conversions between `serde_json::Value` and native Python objects.
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use serde_json::{Map, Number, Value as JsonValue};
+use std::collections::HashMap;
+
+/// Converts a JSON value into the matching Python object: `dict`, `list`,
+/// `str`, `int`, `float`, `bool` or `None`.
+pub fn json_to_py(py: Python<'_>, value: &JsonValue) -> PyObject {
+    match value {
+        JsonValue::Null => py.None(),
+        JsonValue::Bool(b) => b.into_py(py),
+        JsonValue::Number(number) => {
+            if let Some(i) = number.as_i64() {
+                i.into_py(py)
+            } else if let Some(u) = number.as_u64() {
+                u.into_py(py)
+            } else {
+                number.as_f64().unwrap_or(f64::NAN).into_py(py)
+            }
+        }
+        JsonValue::String(s) => s.into_py(py),
+        JsonValue::Array(values) => PyList::new_bound(py, values.iter().map(|v| json_to_py(py, v))).into_py(py),
+        JsonValue::Object(map) => {
+            let dict = PyDict::new_bound(py);
+            for (key, value) in map {
+                // Setting a str key on a fresh dict cannot fail.
+                let _ = dict.set_item(key, json_to_py(py, value));
+            }
+            dict.into_py(py)
+        }
+    }
+}
+
+/// Converts a Python object into a JSON value. Dicts need string keys,
+/// tuples become lists, and floats must be finite.
+///
+/// Raises:
+///     TypeError: If the object, or anything inside it, has no JSON equivalent.
+pub fn py_to_json(object: &Bound<'_, PyAny>) -> PyResult<JsonValue> {
+    if object.is_none() {
+        return Ok(JsonValue::Null);
+    }
+    // `bool` is a subclass of `int`, so it is checked first.
+    if let Ok(b) = object.downcast::<PyBool>() {
+        return Ok(JsonValue::Bool(b.is_true()));
+    }
+    if object.is_instance_of::<PyLong>() {
+        if let Ok(i) = object.extract::<i64>() {
+            return Ok(JsonValue::from(i));
+        }
+        if let Ok(u) = object.extract::<u64>() {
+            return Ok(JsonValue::from(u));
+        }
+        return Err(PyTypeError::new_err(format!("integer {} does not fit in 64 bits", object)));
+    }
+    if let Ok(f) = object.downcast::<PyFloat>() {
+        return Number::from_f64(f.value())
+            .map(JsonValue::Number)
+            .ok_or_else(|| PyTypeError::new_err(format!("float {} has no JSON equivalent", f.value())));
+    }
+    if let Ok(s) = object.downcast::<PyString>() {
+        return Ok(JsonValue::String(s.to_str()?.to_string()));
+    }
+    if let Ok(list) = object.downcast::<PyList>() {
+        return list.iter().map(|item| py_to_json(&item)).collect::<PyResult<_>>().map(JsonValue::Array);
+    }
+    if let Ok(tuple) = object.downcast::<PyTuple>() {
+        return tuple.iter().map(|item| py_to_json(&item)).collect::<PyResult<_>>().map(JsonValue::Array);
+    }
+    if let Ok(dict) = object.downcast::<PyDict>() {
+        return py_dict_to_json(dict).map(|map| JsonValue::Object(map.into_iter().collect::<Map<_, _>>()));
+    }
+    Err(PyTypeError::new_err(format!(
+        "object of type {} has no JSON equivalent",
+        object.get_type().name()?
+    )))
+}
+
+/// Converts a Python `dict` with string keys into a map of JSON values.
+pub fn py_dict_to_json(dict: &Bound<'_, PyDict>) -> PyResult<HashMap<String, JsonValue>> {
+    let mut map = HashMap::with_capacity(dict.len());
+    for (key, value) in dict.iter() {
+        let key: String = key
+            .extract()
+            .map_err(|_| PyTypeError::new_err(format!("dict key {} is not a str", key)))?;
+        map.insert(key, py_to_json(&value)?);
+    }
+    Ok(map)
+}
+
+/// Converts a map of JSON values into a Python `dict`.
+pub fn json_map_to_py(py: Python<'_>, map: &HashMap<String, JsonValue>) -> PyObject {
+    let dict = PyDict::new_bound(py);
+    for (key, value) in map {
+        let _ = dict.set_item(key, json_to_py(py, value));
+    }
+    dict.into_py(py)
+}
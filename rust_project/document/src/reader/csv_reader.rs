@@ -0,0 +1,212 @@
+use super::{new_document, Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pyo3::prelude::*;
+use serde_json::json;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for CSV files.
+///
+/// As in the Python `CSVReader`, each row becomes a line of its fields
+/// joined by `", "`. The whole file becomes a single document, or one
+/// document per `rows_per_document` rows when set, with the `page`,
+/// `start_row` (1-based) and `rows` keys used by the Python async reader.
+/// Rows are streamed, so large files are never held as raw text.
+#[pyclass(name = "CsvReader")]
+#[derive(Clone)]
+pub struct CsvReader {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub rows_per_document: Option<usize>,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl Default for CsvReader {
+    fn default() -> Self {
+        CsvReader {
+            delimiter: b',',
+            quote: b'"',
+            rows_per_document: None,
+            chunking_strategy: None,
+        }
+    }
+}
+
+impl CsvReader {
+    /// Sets the field delimiter and quote character.
+    pub fn with_dialect(mut self, delimiter: u8, quote: u8) -> Self {
+        self.delimiter = delimiter;
+        self.quote = quote;
+        self
+    }
+
+    /// Splits the file into documents of at most `rows` rows each.
+    pub fn with_rows_per_document(mut self, rows: usize) -> Self {
+        self.rows_per_document = Some(rows.max(1));
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+
+    fn flush(&self, documents: &mut Vec<Document>, rows: &mut Vec<String>, start_row: usize, name: &str) {
+        if rows.is_empty() {
+            return;
+        }
+        let mut document = new_document(rows.join("\n"), name, None);
+        if self.rows_per_document.is_some() {
+            let page = documents.len() + 1;
+            document.id = Some(format!("{}_{}", name, page));
+            document.meta_data.insert("page".to_string(), json!(page));
+        }
+        document.meta_data.insert("start_row".to_string(), json!(start_row));
+        document.meta_data.insert("rows".to_string(), json!(rows.len()));
+        documents.push(document);
+        rows.clear();
+    }
+}
+
+impl Reader for CsvReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut csv = ::csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .from_reader(stream);
+        let mut documents = Vec::new();
+        let mut rows = Vec::new();
+        let mut start_row = 1;
+        for (index, record) in csv.records().enumerate() {
+            let record = record.map_err(|e| {
+                let line = e.position().map_or(index + 1, |position| position.line() as usize);
+                match e.kind() {
+                    ::csv::ErrorKind::Utf8 { .. } => {
+                        ReaderError::InvalidEncoding(format!("line {} is not valid UTF-8", line))
+                    }
+                    ::csv::ErrorKind::Io(_) => ReaderError::Io(e.to_string()),
+                    _ => ReaderError::ParseError(format!("line {}: {}", line, e)),
+                }
+            })?;
+            rows.push(record.iter().collect::<Vec<_>>().join(", "));
+            if self.rows_per_document.is_some_and(|limit| rows.len() >= limit) {
+                self.flush(&mut documents, &mut rows, start_row, name);
+                start_row = index + 2;
+            }
+        }
+        self.flush(&mut documents, &mut rows, start_row, name);
+        Ok(documents)
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl CsvReader {
+    /// Creates a new `CsvReader`.
+    ///
+    /// Args:
+    ///     delimiter (str): Field delimiter. Defaults to ",".
+    ///     quotechar (str): Quote character. Defaults to '"'.
+    ///     rows_per_document (Optional[int]): Split the file into documents of this many rows. Defaults to None.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    ///
+    /// Raises:
+    ///     ValueError: If `delimiter` or `quotechar` is not a single ASCII character.
+    #[new]
+    #[pyo3(signature = (delimiter=",", quotechar="\"", rows_per_document=None, chunking_strategy=None))]
+    fn py_new(
+        delimiter: &str,
+        quotechar: &str,
+        rows_per_document: Option<usize>,
+        chunking_strategy: Option<CodeChunking>,
+    ) -> PyResult<Self> {
+        let single_byte = |value: &str, argument: &str| match value.as_bytes() {
+            [byte] => Ok(*byte),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "{} must be a single ASCII character",
+                argument
+            ))),
+        };
+        Ok(CsvReader {
+            delimiter: single_byte(delimiter, "delimiter")?,
+            quote: single_byte(quotechar, "quotechar")?,
+            rows_per_document: rows_per_document.map(|rows| rows.max(1)),
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        })
+    }
+
+    /// Reads a CSV file.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not valid UTF-8 CSV.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads CSV from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &[u8] = b"name,plan\nacme,\"pro, annual\"\nglobex,free\ninitech,team\n";
+
+    #[test]
+    fn test_single_document_joins_fields() {
+        let documents = CsvReader::default().read_bytes(CSV, "customers.csv").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(
+            documents[0].content,
+            "name, plan\nacme, pro, annual\nglobex, free\ninitech, team"
+        );
+        assert_eq!(documents[0].name, Some("customers".to_string()));
+        assert_eq!(documents[0].meta_data["rows"], json!(4));
+        assert_eq!(documents[0].meta_data["start_row"], json!(1));
+    }
+
+    #[test]
+    fn test_rows_per_document_pages() {
+        let reader = CsvReader::default().with_rows_per_document(3);
+        let documents = reader.read_bytes(CSV, "customers.csv").unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[1].content, "initech, team");
+        assert_eq!(documents[1].meta_data["page"], json!(2));
+        assert_eq!(documents[1].meta_data["start_row"], json!(4));
+        assert_eq!(documents[1].meta_data["rows"], json!(1));
+        assert_eq!(documents[1].id, Some("customers_2".to_string()));
+    }
+
+    #[test]
+    fn test_custom_dialect_and_invalid_utf8() {
+        let reader = CsvReader::default().with_dialect(b';', b'\'');
+        let documents = reader.read_bytes(b"a;'b;c'\n", "semi.csv").unwrap();
+        assert_eq!(documents[0].content, "a, b;c");
+
+        let result = CsvReader::default().read_bytes(b"ok\n\xff\n", "bad.csv");
+        assert_eq!(
+            result,
+            Err(ReaderError::InvalidEncoding("line 2 is not valid UTF-8".to_string()))
+        );
+    }
+}
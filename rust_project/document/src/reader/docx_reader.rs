@@ -0,0 +1,533 @@
+use super::structure::{collapse_whitespace, Block, Outline};
+use super::{Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pyo3::prelude::*;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+use std::io::{BufRead, Cursor, Read};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for Word (`.docx`) files.
+///
+/// Unlike the Python `DocxReader`, which joins paragraph text, the document
+/// structure is kept as Markdown: heading styles become `#` headings,
+/// numbered and bulleted paragraphs become list items, and tables become
+/// Markdown tables. Produces a single document, or one document per heading
+/// section when `split_on_headings` is set. Documents record the `title`
+/// (from the document properties or the Title style) and sections their
+/// `heading`, `heading_level` and `section_path`.
+#[pyclass(name = "DocxReader")]
+#[derive(Clone, Default)]
+pub struct DocxReader {
+    pub split_on_headings: bool,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl DocxReader {
+    /// Produces one document per heading section.
+    pub fn with_split_on_headings(mut self, split_on_headings: bool) -> Self {
+        self.split_on_headings = split_on_headings;
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+}
+
+fn invalid(err: impl std::fmt::Display) -> ReaderError {
+    ReaderError::ParseError(format!("invalid DOCX: {}", err))
+}
+
+/// Reads a part of the package, or `None` if it is absent.
+fn read_part(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>, ReaderError> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(invalid(e)),
+    };
+    let mut xml = String::new();
+    file.read_to_string(&mut xml).map_err(|e| match e.kind() {
+        std::io::ErrorKind::InvalidData => ReaderError::InvalidEncoding(format!("{} is not valid UTF-8", name)),
+        _ => invalid(e),
+    })?;
+    Ok(Some(xml))
+}
+
+/// The unescaped value of the attribute with the given local name.
+fn attribute(element: &BytesStart, local_name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == local_name)
+        .and_then(|attr| attr.unescape_value().ok().map(|value| value.into_owned()))
+}
+
+enum ParagraphKind {
+    Title,
+    Heading(usize),
+}
+
+struct Style {
+    name: String,
+    outline_level: Option<usize>,
+    based_on: Option<String>,
+}
+
+/// Paragraph styles from `word/styles.xml`, used to find headings.
+#[derive(Default)]
+struct Styles(HashMap<String, Style>);
+
+impl Styles {
+    fn parse(xml: &str) -> Result<Self, ReaderError> {
+        let mut styles = HashMap::new();
+        let mut current: Option<(String, Style)> = None;
+        let mut reader = quick_xml::Reader::from_str(xml);
+        loop {
+            match reader.read_event().map_err(invalid)? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"style" => {
+                        current = attribute(&e, b"styleId").map(|id| {
+                            let style = Style { name: String::new(), outline_level: None, based_on: None };
+                            (id, style)
+                        })
+                    }
+                    b"name" => {
+                        if let Some((_, style)) = current.as_mut() {
+                            style.name = attribute(&e, b"val").unwrap_or_default().to_lowercase();
+                        }
+                    }
+                    b"basedOn" => {
+                        if let Some((_, style)) = current.as_mut() {
+                            style.based_on = attribute(&e, b"val");
+                        }
+                    }
+                    b"outlineLvl" => {
+                        if let Some((_, style)) = current.as_mut() {
+                            style.outline_level = attribute(&e, b"val").and_then(|v| v.parse().ok());
+                        }
+                    }
+                    _ => {}
+                },
+                Event::End(e) if e.local_name().as_ref() == b"style" => {
+                    if let Some((id, style)) = current.take() {
+                        styles.insert(id, style);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(Styles(styles))
+    }
+
+    /// Whether a style is the Title style or a heading, following `basedOn`.
+    fn kind(&self, style_id: &str) -> Option<ParagraphKind> {
+        let mut id = style_id;
+        for _ in 0..10 {
+            let Some(style) = self.0.get(id) else {
+                return kind_from_name(&id.to_lowercase());
+            };
+            if let Some(kind) = kind_from_name(&style.name) {
+                return Some(kind);
+            }
+            if let Some(level) = style.outline_level.filter(|&level| level < 9) {
+                return Some(ParagraphKind::Heading(level + 1));
+            }
+            id = style.based_on.as_deref()?;
+        }
+        None
+    }
+}
+
+/// Recognizes the built-in style names (`title`, `heading 2`) and the style
+/// ids Word derives from them (`title`, `heading2`).
+fn kind_from_name(name: &str) -> Option<ParagraphKind> {
+    if name == "title" {
+        return Some(ParagraphKind::Title);
+    }
+    let level = name.strip_prefix("heading")?.trim_start().parse::<usize>().ok()?;
+    (1..=9).contains(&level).then_some(ParagraphKind::Heading(level))
+}
+
+/// List formats from `word/numbering.xml`, used to tell numbered lists from
+/// bulleted ones.
+#[derive(Default)]
+struct Numbering {
+    abstract_ids: HashMap<String, String>,
+    formats: HashMap<(String, usize), String>,
+}
+
+impl Numbering {
+    fn parse(xml: &str) -> Result<Self, ReaderError> {
+        let mut numbering = Numbering::default();
+        let mut abstract_id: Option<String> = None;
+        let mut level = 0;
+        let mut num_id: Option<String> = None;
+        let mut reader = quick_xml::Reader::from_str(xml);
+        loop {
+            match reader.read_event().map_err(invalid)? {
+                Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                    b"abstractNum" => abstract_id = attribute(&e, b"abstractNumId"),
+                    b"lvl" => level = attribute(&e, b"ilvl").and_then(|v| v.parse().ok()).unwrap_or(0),
+                    b"numFmt" => {
+                        if let (Some(id), Some(format)) = (&abstract_id, attribute(&e, b"val")) {
+                            numbering.formats.insert((id.clone(), level), format);
+                        }
+                    }
+                    b"num" => num_id = attribute(&e, b"numId"),
+                    b"abstractNumId" => {
+                        if let (Some(id), Some(target)) = (&num_id, attribute(&e, b"val")) {
+                            numbering.abstract_ids.insert(id.clone(), target);
+                        }
+                    }
+                    _ => {}
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"abstractNum" => abstract_id = None,
+                    b"num" => num_id = None,
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(numbering)
+    }
+
+    fn is_ordered(&self, num_id: &str, level: usize) -> bool {
+        self.abstract_ids
+            .get(num_id)
+            .and_then(|abstract_id| self.formats.get(&(abstract_id.clone(), level)))
+            .is_some_and(|format| format != "bullet" && format != "none")
+    }
+}
+
+#[derive(Default)]
+struct Paragraph {
+    style: Option<String>,
+    outline_level: Option<usize>,
+    num_id: Option<String>,
+    list_level: usize,
+    text: String,
+}
+
+#[derive(Default)]
+struct Table {
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn cell(&mut self) -> Option<&mut String> {
+        self.rows.last_mut().and_then(|row| row.last_mut())
+    }
+}
+
+/// Walks `word/document.xml`, turning paragraphs and tables into blocks.
+fn parse_body(xml: &str, styles: &Styles, numbering: &Numbering, outline: &mut Outline) -> Result<(), ReaderError> {
+    // Text boxes nest paragraphs inside paragraphs, and tables nest in cells.
+    let mut paragraphs: Vec<Paragraph> = Vec::new();
+    let mut tables: Vec<Table> = Vec::new();
+    let mut counters: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut in_text = false;
+    let mut in_properties = false;
+    let mut reader = quick_xml::Reader::from_str(xml);
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) | Event::Empty(e) if in_properties => {
+                let Some(paragraph) = paragraphs.last_mut() else { continue };
+                match e.local_name().as_ref() {
+                    b"pStyle" => paragraph.style = attribute(&e, b"val"),
+                    b"outlineLvl" => paragraph.outline_level = attribute(&e, b"val").and_then(|v| v.parse().ok()),
+                    b"numId" => paragraph.num_id = attribute(&e, b"val").filter(|id| id != "0"),
+                    b"ilvl" => paragraph.list_level = attribute(&e, b"val").and_then(|v| v.parse().ok()).unwrap_or(0),
+                    _ => {}
+                }
+            }
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"p" => paragraphs.push(Paragraph::default()),
+                b"pPr" => in_properties = true,
+                b"t" => in_text = true,
+                b"tbl" => tables.push(Table::default()),
+                b"tr" => {
+                    if let Some(table) = tables.last_mut() {
+                        table.rows.push(Vec::new());
+                    }
+                }
+                b"tc" => {
+                    if let Some(row) = tables.last_mut().and_then(|table| table.rows.last_mut()) {
+                        row.push(String::new());
+                    }
+                }
+                _ => {}
+            },
+            Event::Empty(e) => {
+                let separator = match e.local_name().as_ref() {
+                    b"tab" => ' ',
+                    b"br" | b"cr" => '\n',
+                    _ => continue,
+                };
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push(separator);
+                }
+            }
+            Event::Text(e) if in_text => {
+                if let Some(paragraph) = paragraphs.last_mut() {
+                    paragraph.text.push_str(&e.unescape().map_err(invalid)?);
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => in_text = false,
+                b"pPr" => in_properties = false,
+                b"p" => {
+                    let Some(paragraph) = paragraphs.pop() else { continue };
+                    if let Some(cell) = tables.last_mut().and_then(Table::cell) {
+                        cell.push(' ');
+                        cell.push_str(&paragraph.text);
+                        continue;
+                    }
+                    let block = paragraph_block(paragraph, styles, numbering, &mut counters, outline);
+                    outline.push(block);
+                }
+                b"tbl" => {
+                    let Some(table) = tables.pop() else { continue };
+                    match tables.last_mut().and_then(Table::cell) {
+                        Some(cell) => {
+                            for text in table.rows.iter().flatten() {
+                                cell.push(' ');
+                                cell.push_str(text);
+                            }
+                        }
+                        None => outline.push(Block::Table(table.rows)),
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn paragraph_block(
+    paragraph: Paragraph,
+    styles: &Styles,
+    numbering: &Numbering,
+    counters: &mut HashMap<String, Vec<usize>>,
+    outline: &mut Outline,
+) -> Block {
+    let kind = match paragraph.outline_level.filter(|&level| level < 9) {
+        Some(level) => Some(ParagraphKind::Heading(level + 1)),
+        None => paragraph.style.as_deref().and_then(|style| styles.kind(style)),
+    };
+    match kind {
+        Some(ParagraphKind::Title) => {
+            let text = collapse_whitespace(&paragraph.text);
+            if outline.title.is_none() && !text.is_empty() {
+                outline.title = Some(text.clone());
+            }
+            Block::Heading { level: 1, text }
+        }
+        Some(ParagraphKind::Heading(level)) => Block::Heading {
+            level: level.min(6),
+            text: collapse_whitespace(&paragraph.text),
+        },
+        None => match paragraph.num_id {
+            Some(num_id) => {
+                let depth = paragraph.list_level;
+                let number = numbering.is_ordered(&num_id, depth).then(|| {
+                    let levels = counters.entry(num_id).or_default();
+                    levels.resize(depth + 1, 0);
+                    levels[depth] += 1;
+                    levels[depth]
+                });
+                Block::ListItem {
+                    depth,
+                    number,
+                    text: collapse_whitespace(&paragraph.text),
+                }
+            }
+            None => Block::Paragraph(paragraph.text.trim().to_string()),
+        },
+    }
+}
+
+/// The document title from `docProps/core.xml`.
+fn parse_core_title(xml: &str) -> Result<Option<String>, ReaderError> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut in_title = false;
+    let mut title = String::new();
+    loop {
+        match reader.read_event().map_err(invalid)? {
+            Event::Start(e) if e.local_name().as_ref() == b"title" => in_title = true,
+            Event::End(e) if e.local_name().as_ref() == b"title" => in_title = false,
+            Event::Text(e) if in_title => title.push_str(&e.unescape().map_err(invalid)?),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let title = collapse_whitespace(&title);
+    Ok((!title.is_empty()).then_some(title))
+}
+
+impl Reader for DocxReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes.as_slice())).map_err(invalid)?;
+        let body = read_part(&mut archive, "word/document.xml")?.ok_or_else(|| invalid("missing word/document.xml"))?;
+        let styles = match read_part(&mut archive, "word/styles.xml")? {
+            Some(xml) => Styles::parse(&xml)?,
+            None => Styles::default(),
+        };
+        let numbering = match read_part(&mut archive, "word/numbering.xml")? {
+            Some(xml) => Numbering::parse(&xml)?,
+            None => Numbering::default(),
+        };
+
+        let mut outline = Outline::default();
+        if let Some(xml) = read_part(&mut archive, "docProps/core.xml")? {
+            outline.title = parse_core_title(&xml)?;
+        }
+        parse_body(&body, &styles, &numbering, &mut outline)?;
+        Ok(outline.into_documents(name, self.split_on_headings))
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl DocxReader {
+    /// Creates a new `DocxReader`.
+    ///
+    /// Args:
+    ///     split_on_headings (bool): Produce one document per heading section. Defaults to False.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (split_on_headings=false, chunking_strategy=None))]
+    fn py_new(split_on_headings: bool, chunking_strategy: Option<CodeChunking>) -> Self {
+        DocxReader {
+            split_on_headings,
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads a Word document.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not a valid DOCX package.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads a Word document from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    const W: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main""#;
+
+    fn paragraph(properties: &str, text: &str) -> String {
+        format!("<w:p><w:pPr>{}</w:pPr><w:r><w:t xml:space=\"preserve\">{}</w:t></w:r></w:p>", properties, text)
+    }
+
+    fn build_docx(parts: &[(&str, String)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in parts {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn sample() -> Vec<u8> {
+        let body = [
+            paragraph(r#"<w:pStyle w:val="Title"/>"#, "Onboarding"),
+            paragraph("", "Welcome &amp; hello."),
+            paragraph(r#"<w:pStyle w:val="Kop1"/>"#, "Setup"),
+            paragraph(r#"<w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr>"#, "Install"),
+            paragraph(r#"<w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr>"#, "Configure"),
+            paragraph(r#"<w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr>"#, "Tokens"),
+            paragraph(r#"<w:pStyle w:val="Heading2"/><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs>"#, "Plans"),
+            "<w:tbl><w:tr><w:tc><w:p><w:r><w:t>Plan</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Price</w:t></w:r></w:p></w:tc></w:tr>\
+             <w:tr><w:tc><w:p><w:r><w:t>Pro</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>10</w:t><w:tab/><w:t>USD</w:t></w:r></w:p></w:tc></w:tr></w:tbl>"
+                .to_string(),
+        ]
+        .concat();
+        let styles = format!(
+            r#"<w:styles {}><w:style w:type="paragraph" w:styleId="Kop1"><w:name w:val="heading 1"/></w:style></w:styles>"#,
+            W
+        );
+        let numbering = format!(
+            r#"<w:numbering {}><w:abstractNum w:abstractNumId="7"><w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl><w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/></w:lvl></w:abstractNum><w:num w:numId="1"><w:abstractNumId w:val="7"/></w:num></w:numbering>"#,
+            W
+        );
+        build_docx(&[
+            ("word/document.xml", format!("<w:document {}><w:body>{}</w:body></w:document>", W, body)),
+            ("word/styles.xml", styles),
+            ("word/numbering.xml", numbering),
+        ])
+    }
+
+    #[test]
+    fn test_structure_is_kept_as_markdown() {
+        let documents = DocxReader::default().read_bytes(&sample(), "onboarding.docx").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(
+            documents[0].content,
+            "# Onboarding\n\nWelcome & hello.\n\n# Setup\n\n1. Install\n2. Configure\n  - Tokens\n\n## Plans\n\n| Plan | Price |\n| --- | --- |\n| Pro | 10 USD |"
+        );
+        assert_eq!(documents[0].name, Some("onboarding".to_string()));
+        assert_eq!(documents[0].meta_data["title"], json!("Onboarding"));
+    }
+
+    #[test]
+    fn test_split_on_headings_records_section_path() {
+        let reader = DocxReader::default().with_split_on_headings(true);
+        let documents = reader.read_bytes(&sample(), "onboarding.docx").unwrap();
+        let paths: Vec<_> = documents.iter().map(|d| d.meta_data["section_path"].clone()).collect();
+        assert_eq!(paths, vec![json!(["Onboarding"]), json!(["Setup"]), json!(["Setup", "Plans"])]);
+        assert!(documents[2].content.starts_with("## Plans\n\n| Plan"));
+    }
+
+    #[test]
+    fn test_core_title_and_invalid_packages() {
+        let body = format!("<w:document {}><w:body>{}</w:body></w:document>", W, paragraph("", "Body"));
+        let core = r#"<cp:coreProperties xmlns:cp="cp" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>Handbook</dc:title></cp:coreProperties>"#;
+        let bytes = build_docx(&[("word/document.xml", body), ("docProps/core.xml", core.to_string())]);
+        let documents = DocxReader::default().read_bytes(&bytes, "handbook.docx").unwrap();
+        assert_eq!(documents[0].meta_data["title"], json!("Handbook"));
+        assert_eq!(documents[0].content, "Body");
+
+        let missing = build_docx(&[("other.xml", String::new())]);
+        assert_eq!(
+            DocxReader::default().read_bytes(&missing, "empty.docx"),
+            Err(ReaderError::ParseError("invalid DOCX: missing word/document.xml".to_string()))
+        );
+        assert!(matches!(DocxReader::default().read_bytes(b"plain text", "x.docx"), Err(ReaderError::ParseError(_))));
+    }
+}
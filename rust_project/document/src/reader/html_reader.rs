@@ -0,0 +1,388 @@
+use super::structure::{collapse_whitespace, Block, Outline};
+use super::{Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use ego_tree::NodeRef;
+use pyo3::prelude::*;
+use scraper::{Html, Node};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for HTML pages.
+///
+/// As in the Python `WebsiteReader`, reading starts from the main content
+/// (`<main>`, `<article>` or an element with a `content`, `main-content` or
+/// `post-content` class) when the page has one, and from `<body>` otherwise.
+/// Boilerplate (scripts, styles, navigation, forms, hidden elements and the
+/// page header and footer) is dropped; headings, lists, tables and `<pre>`
+/// blocks are kept as Markdown. Produces a single document, or one document
+/// per heading section when `split_on_headings` is set. Documents record the
+/// page `title` and sections their `heading`, `heading_level` and
+/// `section_path`.
+#[pyclass(name = "HtmlReader")]
+#[derive(Clone, Default)]
+pub struct HtmlReader {
+    pub split_on_headings: bool,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl HtmlReader {
+    /// Produces one document per heading section.
+    pub fn with_split_on_headings(mut self, split_on_headings: bool) -> Self {
+        self.split_on_headings = split_on_headings;
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+}
+
+/// Elements that never hold page content.
+const BOILERPLATE: &[&str] = &[
+    "script", "style", "noscript", "template", "head", "nav", "aside", "form", "iframe", "svg", "canvas",
+    "button", "select", "object", "embed",
+];
+
+/// Elements that start a new paragraph.
+const BLOCKS: &[&str] = &[
+    "p", "div", "section", "article", "main", "header", "footer", "blockquote", "figure", "figcaption",
+    "address", "details", "summary", "dl", "dt", "dd", "li", "hr", "body", "center",
+];
+
+const CONTENT_CLASSES: &[&str] = &["content", "main-content", "post-content"];
+
+/// Nesting depth past which elements are flattened to plain text instead of
+/// walked recursively, so deeply nested pages cannot overflow the stack.
+const MAX_DEPTH: usize = 256;
+
+fn element_name(node: NodeRef<'_, Node>) -> Option<&str> {
+    node.value().as_element().map(|element| element.name())
+}
+
+/// All text below `node`, gathered without recursion.
+fn flat_text(node: NodeRef<'_, Node>) -> String {
+    node.descendants().filter_map(|n| n.value().as_text()).map(|t| &**t).collect()
+}
+
+fn is_main_content(node: NodeRef<'_, Node>) -> bool {
+    node.value().as_element().is_some_and(|element| {
+        matches!(element.name(), "main" | "article") || element.classes().any(|class| CONTENT_CLASSES.contains(&class))
+    })
+}
+
+/// Walks the DOM, turning block-level elements into `Block`s.
+struct Extractor {
+    outline: Outline,
+    inline: String,
+    skip_page_chrome: bool,
+}
+
+impl Extractor {
+    fn is_boilerplate(&self, node: NodeRef<'_, Node>) -> bool {
+        let Some(element) = node.value().as_element() else {
+            return false;
+        };
+        let name = element.name();
+        BOILERPLATE.contains(&name)
+            || (self.skip_page_chrome && matches!(name, "header" | "footer"))
+            || element.attr("hidden").is_some()
+            || element.attr("aria-hidden") == Some("true")
+            || matches!(element.attr("role"), Some("navigation" | "banner" | "contentinfo" | "complementary"))
+    }
+
+    /// Emits the pending inline text as a paragraph, keeping `<br>` breaks.
+    fn flush(&mut self) {
+        let lines: Vec<String> = self
+            .inline
+            .split('\n')
+            .map(collapse_whitespace)
+            .filter(|line| !line.is_empty())
+            .collect();
+        self.inline.clear();
+        self.outline.push(Block::Paragraph(lines.join("\n")));
+    }
+
+    /// The text of an element's content, whitespace collapsed.
+    fn text_of(&self, node: NodeRef<'_, Node>) -> String {
+        let mut text = String::new();
+        self.collect_text(node, &mut text, None, 0);
+        collapse_whitespace(&text)
+    }
+
+    /// Gathers text below `node`; nested lists are set aside in `lists`
+    /// when given, so list items can emit them as deeper items.
+    fn collect_text<'a>(
+        &self,
+        node: NodeRef<'a, Node>,
+        text: &mut String,
+        mut lists: Option<&mut Vec<NodeRef<'a, Node>>>,
+        depth: usize,
+    ) {
+        if depth >= MAX_DEPTH {
+            text.push_str(&flat_text(node));
+            return;
+        }
+        for child in node.children() {
+            match child.value() {
+                Node::Text(content) => text.push_str(content),
+                Node::Element(element) if !self.is_boilerplate(child) => {
+                    let name = element.name();
+                    if let (Some(lists), "ul" | "ol") = (lists.as_deref_mut(), name) {
+                        lists.push(child);
+                        continue;
+                    }
+                    if name == "br" || BLOCKS.contains(&name) || matches!(name, "td" | "th" | "tr") {
+                        text.push(' ');
+                    }
+                    self.collect_text(child, text, lists.as_deref_mut(), depth + 1);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn visit(&mut self, node: NodeRef<'_, Node>, depth: usize) {
+        if depth >= MAX_DEPTH {
+            self.inline.push_str(&flat_text(node));
+            return;
+        }
+        for child in node.children() {
+            match child.value() {
+                Node::Text(content) => self.inline.push_str(content),
+                Node::Element(element) if !self.is_boilerplate(child) => match element.name() {
+                    name @ ("h1" | "h2" | "h3" | "h4" | "h5" | "h6") => {
+                        self.flush();
+                        let level = name[1..].parse().unwrap_or(1);
+                        let text = self.text_of(child);
+                        self.outline.push(Block::Heading { level, text });
+                    }
+                    "ul" | "ol" => {
+                        self.flush();
+                        self.list(child, 0);
+                    }
+                    "table" => {
+                        self.flush();
+                        let mut rows = Vec::new();
+                        self.table_rows(child, &mut rows);
+                        self.outline.push(Block::Table(rows));
+                    }
+                    "pre" => {
+                        self.flush();
+                        let code = flat_text(child);
+                        self.outline.push(Block::Code(code));
+                    }
+                    "br" => self.inline.push('\n'),
+                    "img" | "video" | "audio" | "picture" => {}
+                    name if BLOCKS.contains(&name) => {
+                        self.flush();
+                        self.visit(child, depth + 1);
+                        self.flush();
+                    }
+                    _ => self.visit(child, depth + 1),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn list(&mut self, node: NodeRef<'_, Node>, depth: usize) {
+        let ordered = element_name(node) == Some("ol");
+        let start: usize = node
+            .value()
+            .as_element()
+            .and_then(|element| element.attr("start"))
+            .and_then(|start| start.trim().parse().ok())
+            .unwrap_or(1);
+        let items = node.children().filter(|child| element_name(*child) == Some("li"));
+        for (index, item) in items.enumerate() {
+            let mut text = String::new();
+            let mut nested = Vec::new();
+            // Lists nested past MAX_DEPTH stay in the item's text.
+            let lists = (depth < MAX_DEPTH).then_some(&mut nested);
+            self.collect_text(item, &mut text, lists, 0);
+            self.outline.push(Block::ListItem {
+                depth,
+                number: ordered.then(|| start.saturating_add(index)),
+                text: collapse_whitespace(&text),
+            });
+            for list in nested {
+                self.list(list, depth + 1);
+            }
+        }
+    }
+
+    fn table_rows(&self, node: NodeRef<'_, Node>, rows: &mut Vec<Vec<String>>) {
+        for child in node.children() {
+            match element_name(child) {
+                Some("thead" | "tbody" | "tfoot") => self.table_rows(child, rows),
+                Some("tr") => rows.push(
+                    child
+                        .children()
+                        .filter(|cell| matches!(element_name(*cell), Some("td" | "th")))
+                        .map(|cell| self.text_of(cell))
+                        .collect(),
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses an HTML page into an outline of its main content.
+fn extract(html: &str) -> Outline {
+    let page = Html::parse_document(html);
+    let root = page.tree.root();
+    let title = root
+        .descendants()
+        .find(|node| element_name(*node) == Some("title"))
+        .map(|node| collapse_whitespace(&flat_text(node)))
+        .filter(|title| !title.is_empty());
+
+    let main = root.descendants().find(|node| is_main_content(*node));
+    let body = root.descendants().find(|node| element_name(*node) == Some("body"));
+    let mut extractor = Extractor {
+        outline: Outline { title, blocks: Vec::new() },
+        inline: String::new(),
+        skip_page_chrome: main.is_none(),
+    };
+    extractor.visit(main.or(body).unwrap_or(root), 0);
+    extractor.flush();
+    extractor.outline
+}
+
+impl Reader for HtmlReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        let html = String::from_utf8(bytes).map_err(|e| {
+            let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+            let line = valid.iter().filter(|&&byte| byte == b'\n').count() + 1;
+            ReaderError::InvalidEncoding(format!("line {} is not valid UTF-8", line))
+        })?;
+        Ok(extract(&html).into_documents(name, self.split_on_headings))
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl HtmlReader {
+    /// Creates a new `HtmlReader`.
+    ///
+    /// Args:
+    ///     split_on_headings (bool): Produce one document per heading section. Defaults to False.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (split_on_headings=false, chunking_strategy=None))]
+    fn py_new(split_on_headings: bool, chunking_strategy: Option<CodeChunking>) -> Self {
+        HtmlReader {
+            split_on_headings,
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads an HTML file.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not valid UTF-8.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads HTML from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title> Pricing | Acme </title><style>body { color: red }</style></head>
+<body>
+  <header><a href="/">Home</a></header>
+  <nav><ul><li>Docs</li><li>Blog</li></ul></nav>
+  <h1>Pricing</h1>
+  <p>Plans for   every <b>team</b>.<br>Cancel anytime.</p>
+  <h2>Tiers</h2>
+  <ol start="3"><li>Free</li><li>Pro <ul><li>SSO</li></ul></li></ol>
+  <table><thead><tr><th>Plan</th><th>Price</th></tr></thead>
+    <tbody><tr><td>Pro</td><td>$10</td></tr></tbody></table>
+  <pre>curl acme.io
+  | sh</pre>
+  <div hidden>Secret</div>
+  <script>track()</script>
+  <footer>Copyright</footer>
+</body></html>"#;
+
+    #[test]
+    fn test_structure_kept_and_boilerplate_stripped() {
+        let documents = HtmlReader::default().read_bytes(PAGE.as_bytes(), "pricing.html").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(
+            documents[0].content,
+            "# Pricing\n\nPlans for every team.\nCancel anytime.\n\n## Tiers\n\n3. Free\n4. Pro\n  - SSO\n\n\
+             | Plan | Price |\n| --- | --- |\n| Pro | $10 |\n\n```\ncurl acme.io\n  | sh\n```"
+        );
+        assert_eq!(documents[0].meta_data["title"], json!("Pricing | Acme"));
+        assert_eq!(documents[0].name, Some("pricing".to_string()));
+    }
+
+    #[test]
+    fn test_main_content_and_sections() {
+        let html = "<body><div class='sidebar'>Related</div><article><header><h1>Post</h1></header>\
+                    <p>Intro</p><h2>Details</h2><p>More</p></article></body>";
+        let reader = HtmlReader::default().with_split_on_headings(true);
+        let documents = reader.read_bytes(html.as_bytes(), "post.html").unwrap();
+        let contents: Vec<&str> = documents.iter().map(|d| d.content.as_str()).collect();
+        assert_eq!(contents, vec!["# Post\n\nIntro", "## Details\n\nMore"]);
+        assert_eq!(documents[1].meta_data["section_path"], json!(["Post", "Details"]));
+        assert_eq!(documents[1].meta_data["title"], json!("Post"));
+    }
+
+    #[test]
+    fn test_huge_list_start() {
+        let html = format!("<ol start='{}'><li>a</li><li>b</li></ol>", usize::MAX);
+        let documents = HtmlReader::default().read_bytes(html.as_bytes(), "list.html").unwrap();
+        assert_eq!(documents[0].content, format!("{max}. a\n{max}. b", max = usize::MAX));
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 2_000;
+        let html = format!("{}deep{}", "<div>".repeat(depth), "</div>".repeat(depth));
+        let documents = HtmlReader::default().read_bytes(html.as_bytes(), "deep.html").unwrap();
+        assert_eq!(documents[0].content, "deep");
+
+        let html = format!("<ul>{}x{}</ul>", "<li><ul>".repeat(depth), "</ul></li>".repeat(depth));
+        let documents = HtmlReader::default().read_bytes(html.as_bytes(), "deep.html").unwrap();
+        assert!(documents[0].content.ends_with("x"));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let result = HtmlReader::default().read_bytes(b"<p>ok</p>\n<p>\xff</p>", "bad.html");
+        assert_eq!(
+            result,
+            Err(ReaderError::InvalidEncoding("line 2 is not valid UTF-8".to_string()))
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use super::{new_document, Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pyo3::prelude::*;
+use serde_json::{json, Value as JsonValue};
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for JSON files.
+///
+/// As in the Python `JSONReader`, a top-level array produces one document
+/// per element and anything else a single document, each holding the
+/// serialized value with its 1-based `page` in `meta_data`. With
+/// `split_objects`, a top-level object produces one document per key
+/// instead. Every document records the `json_path` of its value
+/// (`$`, `$[0]`, `$.key`) and, for keyed documents, its `key`.
+#[pyclass(name = "JsonReader")]
+#[derive(Clone, Default)]
+pub struct JsonReader {
+    pub split_objects: bool,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl JsonReader {
+    /// Produces one document per key of a top-level object.
+    pub fn with_split_objects(mut self, split_objects: bool) -> Self {
+        self.split_objects = split_objects;
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+}
+
+impl Reader for JsonReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let value: JsonValue = serde_json::from_reader(stream).map_err(|e| {
+            if e.is_io() {
+                ReaderError::Io(e.to_string())
+            } else {
+                ReaderError::ParseError(e.to_string())
+            }
+        })?;
+        let entries: Vec<(String, Option<String>, JsonValue)> = match value {
+            JsonValue::Array(items) => items
+                .into_iter()
+                .enumerate()
+                .map(|(index, item)| (format!("$[{}]", index), None, item))
+                .collect(),
+            JsonValue::Object(map) if self.split_objects => map
+                .into_iter()
+                .map(|(key, item)| (format!("$.{}", key), Some(key), item))
+                .collect(),
+            other => vec![("$".to_string(), None, other)],
+        };
+
+        let mut documents = Vec::with_capacity(entries.len());
+        for (index, (json_path, key, item)) in entries.into_iter().enumerate() {
+            let page = index + 1;
+            let content = serde_json::to_string(&item).map_err(|e| ReaderError::ParseError(e.to_string()))?;
+            let mut document = new_document(content, name, Some(format!("{}_{}", name, page)));
+            document.meta_data.insert("page".to_string(), json!(page));
+            document.meta_data.insert("json_path".to_string(), json!(json_path));
+            if let Some(key) = key {
+                document.meta_data.insert("key".to_string(), json!(key));
+            }
+            documents.push(document);
+        }
+        Ok(documents)
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl JsonReader {
+    /// Creates a new `JsonReader`.
+    ///
+    /// Args:
+    ///     split_objects (bool): Produce one document per key of a top-level object. Defaults to False.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (split_objects=false, chunking_strategy=None))]
+    fn py_new(split_objects: bool, chunking_strategy: Option<CodeChunking>) -> Self {
+        JsonReader {
+            split_objects,
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads a JSON file.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not valid JSON.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads JSON from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_produces_one_document_per_element() {
+        let documents = JsonReader::default()
+            .read_bytes(br#"[{"a": 1}, "two", [3]]"#, "items.json")
+            .unwrap();
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].content, r#"{"a":1}"#);
+        assert_eq!(documents[1].content, r#""two""#);
+        assert_eq!(documents[2].meta_data["page"], json!(3));
+        assert_eq!(documents[2].meta_data["json_path"], json!("$[2]"));
+        assert_eq!(documents[2].id, Some("items_3".to_string()));
+        assert_eq!(documents[2].name, Some("items".to_string()));
+    }
+
+    #[test]
+    fn test_object_as_single_document_or_split_by_key() {
+        let data = br#"{"billing": {"plan": "pro"}, "owner": "ops"}"#;
+        let single = JsonReader::default().read_bytes(data, "config.json").unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].meta_data["json_path"], json!("$"));
+        assert_eq!(single[0].meta_data["page"], json!(1));
+
+        let split = JsonReader::default().with_split_objects(true).read_bytes(data, "config.json").unwrap();
+        assert_eq!(split.len(), 2);
+        assert_eq!(split[0].meta_data["key"], json!("billing"));
+        assert_eq!(split[0].meta_data["json_path"], json!("$.billing"));
+        assert_eq!(split[0].content, r#"{"plan":"pro"}"#);
+        assert_eq!(split[1].content, r#""ops""#);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let result = JsonReader::default().read_bytes(b"{not json", "bad.json");
+        assert!(matches!(result, Err(ReaderError::ParseError(_))));
+    }
+}
@@ -0,0 +1,215 @@
+use super::{new_document, read_line, Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pyo3::prelude::*;
+use serde_json::json;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for Markdown files.
+///
+/// Produces a single document for the whole file, or one document per
+/// heading section when `split_on_headings` is set. Headings inside fenced
+/// code blocks are ignored. Documents record the file `title` (its first
+/// level-1 heading), their `start_line` and `end_line` and, for sections,
+/// the `heading` and `heading_level` they start with.
+#[pyclass(name = "MarkdownReader")]
+#[derive(Clone, Default)]
+pub struct MarkdownReader {
+    pub split_on_headings: bool,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+struct Section {
+    heading: Option<(usize, String)>,
+    start_line: usize,
+    lines: Vec<String>,
+}
+
+impl MarkdownReader {
+    /// Produces one document per heading section.
+    pub fn with_split_on_headings(mut self, split_on_headings: bool) -> Self {
+        self.split_on_headings = split_on_headings;
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+}
+
+/// Parses an ATX heading (`## Title`) into its level and text.
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    // A closing run of '#' only counts after whitespace, so "# C#" keeps its '#'.
+    let text = rest.trim();
+    let open = text.trim_end_matches('#');
+    let text = if open.is_empty() || open.ends_with([' ', '\t']) { open.trim_end() } else { text };
+    Some((level, text.to_string()))
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+impl Reader for MarkdownReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut sections = vec![Section {
+            heading: None,
+            start_line: 1,
+            lines: Vec::new(),
+        }];
+        let mut title = None;
+        let mut in_fence = false;
+        let mut buffer = String::new();
+        let mut line_number = 1;
+        while read_line(stream, &mut buffer, line_number)? {
+            if is_fence(&buffer) {
+                in_fence = !in_fence;
+            }
+            let heading = if in_fence { None } else { parse_heading(&buffer) };
+            if let Some((level, text)) = &heading {
+                if *level == 1 && title.is_none() {
+                    title = Some(text.clone());
+                }
+            }
+            match heading {
+                Some(heading) if self.split_on_headings => sections.push(Section {
+                    heading: Some(heading),
+                    start_line: line_number,
+                    lines: vec![buffer.clone()],
+                }),
+                _ => {
+                    if let Some(section) = sections.last_mut() {
+                        section.lines.push(buffer.clone());
+                    }
+                }
+            }
+            line_number += 1;
+        }
+
+        let split = self.split_on_headings;
+        let mut documents = Vec::new();
+        for section in sections {
+            if section.lines.iter().all(|line| line.trim().is_empty()) {
+                continue;
+            }
+            let end_line = section.start_line + section.lines.len() - 1;
+            let id = split.then(|| format!("{}_{}", name, documents.len() + 1));
+            let mut document = new_document(section.lines.join("\n"), name, id);
+            if let Some(title) = &title {
+                document.meta_data.insert("title".to_string(), json!(title));
+            }
+            if let Some((level, heading)) = section.heading {
+                document.meta_data.insert("heading".to_string(), json!(heading));
+                document.meta_data.insert("heading_level".to_string(), json!(level));
+            }
+            document.meta_data.insert("start_line".to_string(), json!(section.start_line));
+            document.meta_data.insert("end_line".to_string(), json!(end_line));
+            documents.push(document);
+        }
+        Ok(documents)
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl MarkdownReader {
+    /// Creates a new `MarkdownReader`.
+    ///
+    /// Args:
+    ///     split_on_headings (bool): Produce one document per heading section. Defaults to False.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (split_on_headings=false, chunking_strategy=None))]
+    fn py_new(split_on_headings: bool, chunking_strategy: Option<CodeChunking>) -> Self {
+        MarkdownReader {
+            split_on_headings,
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads a Markdown file.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not valid UTF-8.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads Markdown from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "Intro text.\n\n# Guide\n\nWelcome.\n\n## Install\n\n```sh\n# not a heading\n```\n\n## Usage ##\nRun it.\n";
+
+    #[test]
+    fn test_parse_heading() {
+        assert_eq!(parse_heading("## Install"), Some((2, "Install".to_string())));
+        assert_eq!(parse_heading("### Closed ###"), Some((3, "Closed".to_string())));
+        assert_eq!(parse_heading("# C#"), Some((1, "C#".to_string())));
+        assert_eq!(parse_heading("## F# and C# ##"), Some((2, "F# and C#".to_string())));
+        assert_eq!(parse_heading("# #"), Some((1, String::new())));
+        assert_eq!(parse_heading("#hashtag"), None);
+        assert_eq!(parse_heading("####### too deep"), None);
+        assert_eq!(parse_heading("    # indented code"), None);
+    }
+
+    #[test]
+    fn test_whole_file_with_title() {
+        let documents = MarkdownReader::default().read_bytes(MARKDOWN.as_bytes(), "guide.md").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].name, Some("guide".to_string()));
+        assert_eq!(documents[0].meta_data["title"], json!("Guide"));
+        assert_eq!(documents[0].meta_data["end_line"], json!(14));
+        assert!(!documents[0].meta_data.contains_key("heading"));
+    }
+
+    #[test]
+    fn test_split_on_headings_ignores_code_fences() {
+        let reader = MarkdownReader::default().with_split_on_headings(true);
+        let documents = reader.read_bytes(MARKDOWN.as_bytes(), "guide.md").unwrap();
+        let headings: Vec<Option<&str>> = documents
+            .iter()
+            .map(|d| d.meta_data.get("heading").and_then(|h| h.as_str()))
+            .collect();
+        assert_eq!(headings, vec![None, Some("Guide"), Some("Install"), Some("Usage")]);
+        let install = &documents[2];
+        assert!(install.content.contains("# not a heading"));
+        assert_eq!(install.meta_data["heading_level"], json!(2));
+        assert_eq!(install.meta_data["start_line"], json!(7));
+        assert_eq!(install.meta_data["end_line"], json!(12));
+        assert_eq!(install.id, Some("guide_3".to_string()));
+        assert!(documents.iter().all(|d| d.meta_data["title"] == json!("Guide")));
+    }
+}
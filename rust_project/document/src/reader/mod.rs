@@ -0,0 +1,208 @@
+use crate::chunking::{ChunkingError, ChunkingStrategy};
+use crate::Document;
+use pyo3::exceptions::{PyFileNotFoundError, PyIOError, PyValueError};
+use pyo3::PyErr;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor};
+use std::path::Path;
+use std::sync::Arc;
+
+pub mod csv_reader;
+pub mod docx_reader;
+pub mod html_reader;
+pub mod json_reader;
+pub mod markdown_reader;
+pub mod pdf_reader;
+mod structure;
+pub mod text_reader;
+
+pub use csv_reader::CsvReader;
+pub use docx_reader::DocxReader;
+pub use html_reader::HtmlReader;
+pub use json_reader::JsonReader;
+pub use markdown_reader::MarkdownReader;
+pub use pdf_reader::PdfReader;
+pub use text_reader::TextReader;
+
+/// A chunking strategy that can be shared between readers and threads.
+pub type SharedChunkingStrategy = Arc<dyn ChunkingStrategy + Send + Sync>;
+
+// Define a custom error type for reader operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReaderError {
+    FileNotFound(String),
+    Io(String),
+    InvalidEncoding(String),
+    ParseError(String),
+    Chunking(ChunkingError),
+}
+
+impl fmt::Display for ReaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReaderError::FileNotFound(path) => write!(f, "Could not find file: {}", path),
+            ReaderError::Io(msg) => write!(f, "I/O error: {}", msg),
+            ReaderError::InvalidEncoding(msg) => write!(f, "Invalid encoding: {}", msg),
+            ReaderError::ParseError(msg) => write!(f, "Parse error: {}", msg),
+            ReaderError::Chunking(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ReaderError {}
+
+impl From<io::Error> for ReaderError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::InvalidData => ReaderError::InvalidEncoding(err.to_string()),
+            _ => ReaderError::Io(err.to_string()),
+        }
+    }
+}
+
+impl From<ChunkingError> for ReaderError {
+    fn from(err: ChunkingError) -> Self {
+        ReaderError::Chunking(err)
+    }
+}
+
+impl From<ReaderError> for PyErr {
+    fn from(err: ReaderError) -> PyErr {
+        match err {
+            ReaderError::FileNotFound(_) => PyFileNotFoundError::new_err(err.to_string()),
+            ReaderError::Io(_) => PyIOError::new_err(err.to_string()),
+            _ => PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// Reads a source (a file or a byte buffer) into `Document`s.
+///
+/// Mirrors Python's `agno.document.reader.base.Reader`. Implementors only
+/// provide `parse`; the `read_*` methods take care of opening the source,
+/// naming the documents after the file and applying the chunking strategy.
+pub trait Reader {
+    /// Parses a stream into documents, without chunking.
+    ///
+    /// `name` is the document name, usually the file stem.
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError>;
+
+    /// The chunking strategy applied to parsed documents, if any.
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        None
+    }
+
+    /// Parses a stream and chunks the resulting documents.
+    fn read_stream(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let documents = self.parse(stream, name)?;
+        match self.chunking_strategy() {
+            Some(strategy) => {
+                let mut chunks = Vec::new();
+                for document in &documents {
+                    chunks.extend(strategy.chunk(document)?);
+                }
+                Ok(chunks)
+            }
+            None => Ok(documents),
+        }
+    }
+
+    /// Reads a file, naming its documents after the file stem.
+    fn read_path(&self, path: &Path) -> Result<Vec<Document>, ReaderError> {
+        let file = File::open(path).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => ReaderError::FileNotFound(path.display().to_string()),
+            _ => ReaderError::from(err),
+        })?;
+        self.read_stream(&mut BufReader::new(file), &document_name(path))
+    }
+
+    /// Reads an in-memory file; `file_name` may include an extension.
+    fn read_bytes(&self, bytes: &[u8], file_name: &str) -> Result<Vec<Document>, ReaderError> {
+        self.read_stream(&mut Cursor::new(bytes), &document_name(Path::new(file_name)))
+    }
+}
+
+/// The document name for a file: its stem, as in the Python readers.
+pub fn document_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Reads one line into `buffer` (without its terminator), tagging invalid
+/// UTF-8 with the 1-based line number. Returns `false` at end of stream.
+pub(crate) fn read_line(stream: &mut dyn BufRead, buffer: &mut String, line_number: usize) -> Result<bool, ReaderError> {
+    buffer.clear();
+    match stream.read_line(buffer) {
+        Ok(0) => Ok(false),
+        Ok(_) => {
+            if buffer.ends_with('\n') {
+                buffer.pop();
+                if buffer.ends_with('\r') {
+                    buffer.pop();
+                }
+            }
+            Ok(true)
+        }
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => Err(ReaderError::InvalidEncoding(format!(
+            "line {} is not valid UTF-8",
+            line_number
+        ))),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Creates a document with the given content, name and (optional) id.
+pub(crate) fn new_document(content: String, name: &str, id: Option<String>) -> Document {
+    Document {
+        content,
+        id,
+        name: Some(name.to_string()),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunking::CodeChunking;
+
+    #[test]
+    fn test_read_path_names_documents_and_reports_missing_files() {
+        let dir = std::env::temp_dir().join(format!("document_reader_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "hello\nworld\n").unwrap();
+
+        let documents = TextReader::default().read_path(&path).unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].name, Some("notes".to_string()));
+        assert_eq!(documents[0].content, "hello\nworld");
+
+        let missing = TextReader::default().read_path(&dir.join("missing.txt"));
+        assert!(matches!(missing, Err(ReaderError::FileNotFound(_))));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_read_bytes_applies_chunking_strategy() {
+        let strategy = CodeChunking::new(5000, 2, 0).unwrap();
+        let reader = TextReader::default().with_chunking_strategy(Arc::new(strategy));
+        let chunks = reader.read_bytes(b"a\nb\nc\n", "letters.txt").unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].content, "a\nb");
+        assert_eq!(chunks[1].id, Some("letters_2".to_string()));
+        assert_eq!(chunks[1].name, Some("letters".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_utf8_reports_line_number() {
+        let result = TextReader::default().read_bytes(b"ok\n\xff\xfe\n", "bad.txt");
+        assert_eq!(
+            result,
+            Err(ReaderError::InvalidEncoding("line 2 is not valid UTF-8".to_string()))
+        );
+    }
+}
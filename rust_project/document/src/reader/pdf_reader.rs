@@ -0,0 +1,623 @@
+use super::{new_document, Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pdf_extract::{MediaBox, OutputDev, OutputError, Transform};
+use pyo3::prelude::*;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for PDF files.
+///
+/// As in the Python `PDFReader`, each page becomes a document with its
+/// 1-based `page` in `meta_data` and the id `{name}_{page}`. Text is laid
+/// out from glyph positions rather than content-stream order, so words are
+/// spaced by their gaps and multi-column pages are read column by column.
+/// Pages without text become empty documents. Pages that cannot be decoded
+/// are skipped and reported by [`PdfReader::extract`], and by `parse` under
+/// [`FAILED_PAGES_KEY`] in the `meta_data` of every document of the file;
+/// reading only fails when no page could be decoded.
+#[pyclass(name = "PdfReader")]
+#[derive(Clone)]
+pub struct PdfReader {
+    pub password: Option<String>,
+    pub detect_columns: bool,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl Default for PdfReader {
+    fn default() -> Self {
+        PdfReader {
+            password: None,
+            detect_columns: true,
+            chunking_strategy: None,
+        }
+    }
+}
+
+/// Key under which `parse` lists the pages it skipped, as `{"page", "reason"}`
+/// objects.
+pub const FAILED_PAGES_KEY: &str = "failed_pages";
+
+/// A page that could not be turned into a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageFailure {
+    pub page: u32,
+    pub reason: String,
+}
+
+/// The result of extracting a PDF: one document per decoded page, plus the
+/// pages that were skipped.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PdfExtraction {
+    pub documents: Vec<Document>,
+    pub failed_pages: Vec<PageFailure>,
+}
+
+impl PdfReader {
+    /// Decrypts encrypted files with `password`.
+    pub fn with_password(mut self, password: impl Into<String>) -> Self {
+        self.password = Some(password.into());
+        self
+    }
+
+    /// Enables or disables multi-column ordering (enabled by default).
+    pub fn with_detect_columns(mut self, detect_columns: bool) -> Self {
+        self.detect_columns = detect_columns;
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+
+    /// Extracts every page of a PDF, collecting the pages that failed.
+    ///
+    /// Only errors when the file itself cannot be loaded or decrypted.
+    pub fn extract(&self, bytes: &[u8], name: &str) -> Result<PdfExtraction, ReaderError> {
+        let mut pdf = pdf_extract::Document::load_mem(bytes)
+            .map_err(|e| ReaderError::ParseError(format!("invalid PDF: {}", e)))?;
+        if pdf.is_encrypted() {
+            pdf.decrypt(self.password.as_deref().unwrap_or(""))
+                .map_err(|e| ReaderError::ParseError(format!("could not decrypt PDF: {}", e)))?;
+        }
+
+        let mut extraction = PdfExtraction::default();
+        for page in pdf.get_pages().into_keys() {
+            match self.extract_page(&pdf, page) {
+                Ok(content) => {
+                    let mut document = new_document(content, name, Some(format!("{}_{}", name, page)));
+                    document.meta_data.insert("page".to_string(), json!(page));
+                    extraction.documents.push(document);
+                }
+                Err(reason) => extraction.failed_pages.push(PageFailure { page, reason }),
+            }
+        }
+        Ok(extraction)
+    }
+
+    fn extract_page(&self, pdf: &pdf_extract::Document, page: u32) -> Result<String, String> {
+        let mut collector = GlyphCollector::default();
+        // pdf-extract panics on some malformed pages; keep them from taking
+        // the rest of the file down with them.
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            pdf_extract::output_doc_page(pdf, &mut collector, page)
+        }));
+        match outcome {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return Err(e.to_string()),
+            Err(payload) => return Err(panic_message(payload.as_ref())),
+        }
+        Ok(layout_page(collector.glyphs, self.detect_columns))
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string());
+    format!("could not decode page: {}", message)
+}
+
+impl Reader for PdfReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        let extraction = self.extract(&bytes, name)?;
+        if extraction.documents.is_empty() && !extraction.failed_pages.is_empty() {
+            let pages: Vec<String> = extraction
+                .failed_pages
+                .iter()
+                .map(|failure| format!("page {}: {}", failure.page, failure.reason))
+                .collect();
+            return Err(ReaderError::ParseError(format!("no page could be decoded ({})", pages.join("; "))));
+        }
+        let mut documents = extraction.documents;
+        if !extraction.failed_pages.is_empty() {
+            let failed_pages: Vec<_> = extraction
+                .failed_pages
+                .iter()
+                .map(|failure| json!({"page": failure.page, "reason": failure.reason}))
+                .collect();
+            for document in &mut documents {
+                document.meta_data.insert(FAILED_PAGES_KEY.to_string(), json!(failed_pages));
+            }
+        }
+        Ok(documents)
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+/// A single positioned glyph, in page space (y grows upwards).
+#[derive(Debug, Clone)]
+struct Glyph {
+    x: f64,
+    end: f64,
+    y: f64,
+    size: f64,
+    text: String,
+}
+
+#[derive(Default)]
+struct GlyphCollector {
+    glyphs: Vec<Glyph>,
+}
+
+impl OutputDev for GlyphCollector {
+    fn begin_page(&mut self, _page: u32, _media_box: &MediaBox, _art_box: Option<(f64, f64, f64, f64)>) -> Result<(), OutputError> {
+        self.glyphs.clear();
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(&mut self, trm: &Transform, width: f64, _spacing: f64, font_size: f64, text: &str) -> Result<(), OutputError> {
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        let size = (font_size * (trm.m11 + trm.m21) * font_size * (trm.m12 + trm.m22)).abs().sqrt();
+        let size = if size.is_finite() && size > 0.0 { size } else { 1.0 };
+        self.glyphs.push(Glyph {
+            x: trm.m31,
+            end: trm.m31 + width * size,
+            y: trm.m32,
+            size,
+            text: text.to_string(),
+        });
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+/// A run of glyphs on one line with no wide horizontal gap.
+#[derive(Debug, Clone)]
+struct Fragment {
+    x0: f64,
+    x1: f64,
+    text: String,
+}
+
+#[derive(Debug, Clone)]
+struct Line {
+    y: f64,
+    size: f64,
+    fragments: Vec<Fragment>,
+}
+
+/// Gap between glyphs, as a fraction of the font size, read as a space.
+const WORD_GAP: f64 = 0.15;
+/// Gap, as a fraction of the font size, that separates fragments.
+const FRAGMENT_GAP: f64 = 1.0;
+/// Vertical distance between lines, in line sizes, read as a paragraph break.
+const PARAGRAPH_GAP: f64 = 1.8;
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    values[values.len() / 2]
+}
+
+/// Groups glyphs into lines (top to bottom) of fragments (left to right).
+fn build_lines(mut glyphs: Vec<Glyph>) -> Vec<Line> {
+    glyphs.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+    let mut rows: Vec<Vec<Glyph>> = Vec::new();
+    for glyph in glyphs {
+        match rows.last_mut() {
+            Some(row) if (row[0].y - glyph.y).abs() <= 0.5 * row[0].size.max(glyph.size) => row.push(glyph),
+            _ => rows.push(vec![glyph]),
+        }
+    }
+
+    rows.into_iter()
+        .map(|mut row| {
+            row.sort_by(|a, b| a.x.total_cmp(&b.x));
+            let y = row[0].y;
+            let size = median(row.iter().map(|g| g.size).collect());
+            let mut fragments: Vec<Fragment> = Vec::new();
+            let mut previous: Option<&Glyph> = None;
+            for glyph in &row {
+                if let Some(prev) = previous {
+                    // Fake bold draws the same glyph twice at almost the same spot.
+                    if prev.text == glyph.text && (glyph.x - prev.x).abs() < 0.1 * size {
+                        continue;
+                    }
+                }
+                let gap = previous.map(|prev| glyph.x - prev.end);
+                match (gap, fragments.last_mut()) {
+                    (Some(gap), Some(fragment)) if gap <= FRAGMENT_GAP * size => {
+                        if gap > WORD_GAP * size {
+                            fragment.text.push(' ');
+                        }
+                        fragment.text.push_str(&glyph.text);
+                        fragment.x1 = fragment.x1.max(glyph.end);
+                    }
+                    _ => fragments.push(Fragment {
+                        x0: glyph.x,
+                        x1: glyph.end,
+                        text: glyph.text.clone(),
+                    }),
+                }
+                previous = Some(glyph);
+            }
+            Line { y, size, fragments }
+        })
+        .collect()
+}
+
+/// Finds the left edges of text columns, or nothing for single-column pages.
+///
+/// Columns are clusters of fragment start positions that hold a sizeable
+/// share of the fragments, sit well apart, and are mostly filled by their
+/// fragments; the last condition keeps tables from being read as columns.
+fn detect_columns(lines: &[Line]) -> Vec<f64> {
+    let fragments: Vec<&Fragment> = lines.iter().flat_map(|line| &line.fragments).collect();
+    if fragments.len() < 6 {
+        return Vec::new();
+    }
+    let size = median(lines.iter().map(|line| line.size).collect());
+    let mut starts: Vec<f64> = fragments.iter().map(|f| f.x0).collect();
+    starts.sort_by(f64::total_cmp);
+
+    let mut clusters: Vec<(f64, f64, usize)> = Vec::new();
+    for x in starts {
+        match clusters.last_mut() {
+            Some((_, last, count)) if x - *last <= size => {
+                *last = x;
+                *count += 1;
+            }
+            _ => clusters.push((x, x, 1)),
+        }
+    }
+    let min_count = 3.max(fragments.len() * 15 / 100);
+    let mut columns: Vec<f64> = Vec::new();
+    for (start, _, count) in clusters {
+        if count >= min_count && columns.last().is_none_or(|last| start - last >= 3.0 * size) {
+            columns.push(start);
+        }
+    }
+    if columns.len() < 2 {
+        return Vec::new();
+    }
+
+    for index in 0..columns.len() {
+        let members: Vec<&&Fragment> = fragments
+            .iter()
+            .filter(|f| column_of(f, &columns, size) == Some(index))
+            .collect();
+        let right = members.iter().map(|f| f.x1).fold(f64::MIN, f64::max);
+        let span = right - columns[index];
+        let typical = median(members.iter().map(|f| f.x1 - f.x0).collect());
+        if members.is_empty() || typical < 0.5 * span {
+            return Vec::new();
+        }
+    }
+    columns
+}
+
+/// The column a fragment sits in, or `None` if it crosses a column boundary.
+fn column_of(fragment: &Fragment, columns: &[f64], tolerance: f64) -> Option<usize> {
+    let index = columns
+        .iter()
+        .rposition(|&start| fragment.x0 >= start - tolerance)
+        .unwrap_or(0);
+    match columns.get(index + 1) {
+        Some(&next) if fragment.x1 > next => None,
+        _ => Some(index),
+    }
+}
+
+/// Appends a block of `(y, size, text)` lines after a blank line; wide
+/// vertical gaps inside the block become paragraph breaks too.
+fn join_block(out: &mut String, block: &[(f64, f64, String)]) {
+    if block.is_empty() {
+        return;
+    }
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    for (index, (y, size, text)) in block.iter().enumerate() {
+        if index > 0 {
+            let (prev_y, prev_size, _) = &block[index - 1];
+            let gap = prev_y - y;
+            out.push_str(if gap > PARAGRAPH_GAP * prev_size.max(*size) { "\n\n" } else { "\n" });
+        }
+        out.push_str(text);
+    }
+}
+
+fn line_text(fragments: &[&Fragment]) -> String {
+    fragments.iter().map(|f| f.text.as_str()).collect::<Vec<_>>().join(" ")
+}
+
+/// Lays out a page's glyphs as text in reading order.
+fn layout_page(glyphs: Vec<Glyph>, detect: bool) -> String {
+    let lines = build_lines(glyphs);
+    let columns = if detect { detect_columns(&lines) } else { Vec::new() };
+    let mut out = String::new();
+
+    if columns.len() < 2 {
+        let block: Vec<(f64, f64, String)> = lines
+            .iter()
+            .map(|line| (line.y, line.size, line_text(&line.fragments.iter().collect::<Vec<_>>())))
+            .collect();
+        join_block(&mut out, &block);
+        return out;
+    }
+
+    // Lines crossing a column boundary (titles, full-width figures) split the
+    // page into bands; each band is read column by column.
+    let tolerance = median(lines.iter().map(|line| line.size).collect());
+    let mut band: BTreeMap<usize, Vec<(f64, f64, String)>> = BTreeMap::new();
+    for line in &lines {
+        let placed: Option<Vec<usize>> = line
+            .fragments
+            .iter()
+            .map(|fragment| column_of(fragment, &columns, tolerance))
+            .collect();
+        match placed {
+            Some(placed) => {
+                let mut by_column: BTreeMap<usize, Vec<&Fragment>> = BTreeMap::new();
+                for (fragment, column) in line.fragments.iter().zip(placed) {
+                    by_column.entry(column).or_default().push(fragment);
+                }
+                for (column, fragments) in by_column {
+                    band.entry(column).or_default().push((line.y, line.size, line_text(&fragments)));
+                }
+            }
+            None => {
+                for block in std::mem::take(&mut band).values() {
+                    join_block(&mut out, block);
+                }
+                let text = line_text(&line.fragments.iter().collect::<Vec<_>>());
+                join_block(&mut out, &[(line.y, line.size, text)]);
+            }
+        }
+    }
+    for block in band.values() {
+        join_block(&mut out, block);
+    }
+    out
+}
+
+#[pymethods]
+impl PdfReader {
+    /// Creates a new `PdfReader`.
+    ///
+    /// Args:
+    ///     password (Optional[str]): Password used to decrypt encrypted files. Defaults to None.
+    ///     detect_columns (bool): Read multi-column pages column by column. Defaults to True.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (password=None, detect_columns=true, chunking_strategy=None))]
+    fn py_new(password: Option<String>, detect_columns: bool, chunking_strategy: Option<CodeChunking>) -> Self {
+        PdfReader {
+            password,
+            detect_columns,
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads a PDF file, one document per page.
+    ///
+    /// Pages that cannot be decoded are skipped and listed in the `failed_pages` meta_data key of every document.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not a PDF, cannot be decrypted, or no page could be decoded.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads a PDF from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+
+    /// Extracts a PDF from bytes, reporting the pages that could not be decoded.
+    ///
+    /// Documents are not chunked.
+    ///
+    /// Args:
+    ///     data (bytes): The PDF file.
+    ///     file_name (str): Name used for the documents.
+    ///
+    /// Returns:
+    ///     Tuple[List[Document], Dict[int, str]]: The page documents and the reason each failed page was skipped.
+    ///
+    /// Raises:
+    ///     ValueError: If the file is not a PDF or cannot be decrypted.
+    #[pyo3(name = "extract")]
+    fn extract_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<(Vec<Document>, BTreeMap<u32, String>)> {
+        let name = super::document_name(std::path::Path::new(file_name));
+        let extraction = py.allow_threads(|| self.extract(data, &name))?;
+        let failed = extraction.failed_pages.into_iter().map(|f| (f.page, f.reason)).collect();
+        Ok((extraction.documents, failed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pdf_extract::content::{Content, Operation};
+    use pdf_extract::{dictionary, Object, Stream};
+
+    /// Texts drawn on a test page at their (x, y), or `None` for a page
+    /// that cannot be decoded.
+    type TestPage<'a> = Option<Vec<(f64, f64, &'a str)>>;
+
+    /// Builds a PDF with one page per entry, in 10pt Helvetica.
+    fn build_pdf(pages: Vec<TestPage>) -> Vec<u8> {
+        let mut pdf = pdf_extract::Document::with_version("1.5");
+        let pages_id = pdf.new_object_id();
+        let font_id = pdf.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let resources_id = pdf.add_object(dictionary! { "Font" => dictionary! { "F1" => font_id } });
+        let mut kids: Vec<Object> = Vec::new();
+        for texts in pages {
+            let readable = texts.is_some();
+            let contents = match texts {
+                Some(texts) => {
+                    let mut operations = Vec::new();
+                    for (x, y, text) in texts {
+                        operations.push(Operation::new("BT", vec![]));
+                        operations.push(Operation::new("Tf", vec!["F1".into(), 10.into()]));
+                        operations.push(Operation::new("Td", vec![x.into(), y.into()]));
+                        operations.push(Operation::new("Tj", vec![Object::string_literal(text)]));
+                        operations.push(Operation::new("ET", vec![]));
+                    }
+                    let content = Content { operations };
+                    Object::Reference(pdf.add_object(Stream::new(dictionary! {}, content.encode().unwrap())))
+                }
+                None => Object::Reference((9999, 0)),
+            };
+            let mut page = dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => contents,
+            };
+            if !readable {
+                // pdf-extract panics on a media box that is not made of numbers.
+                page.set("MediaBox", vec![Object::from("Broken")]);
+            }
+            let page_id = pdf.add_object(page);
+            kids.push(page_id.into());
+        }
+        let count = kids.len() as i64;
+        pdf.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => count,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = pdf.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        pdf.trailer.set("Root", catalog_id);
+        let mut bytes = Vec::new();
+        pdf.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_one_document_per_page() {
+        let bytes = build_pdf(vec![
+            Some(vec![(72.0, 760.0, "Quarterly report"), (72.0, 746.0, "Revenue grew.")]),
+            Some(vec![(72.0, 760.0, "Second page")]),
+        ]);
+        let documents = PdfReader::default().read_bytes(&bytes, "report.pdf").unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].content, "Quarterly report\nRevenue grew.");
+        assert_eq!(documents[0].name, Some("report".to_string()));
+        assert_eq!(documents[1].id, Some("report_2".to_string()));
+        assert_eq!(documents[1].meta_data["page"], json!(2));
+    }
+
+    #[test]
+    fn test_two_columns_are_read_in_order() {
+        let title = "Reading order of two column layouts in portable documents";
+        let mut texts = vec![(72.0, 780.0, title)];
+        let left = ["left column opens here", "and it carries on for", "a few lines of prose", "before it ends."];
+        let right = ["right column follows on", "once the left column", "has been read from top", "to bottom."];
+        for (row, (l, r)) in left.iter().zip(right.iter()).enumerate() {
+            let y = 740.0 - 12.0 * row as f64;
+            texts.push((72.0, y, l));
+            texts.push((320.0, y, r));
+        }
+        let bytes = build_pdf(vec![Some(texts)]);
+
+        let documents = PdfReader::default().read_bytes(&bytes, "paper.pdf").unwrap();
+        let expected = [title, &left.join("\n"), &right.join("\n")].join("\n\n");
+        assert_eq!(documents[0].content, expected);
+
+        let flat = PdfReader::default().with_detect_columns(false).read_bytes(&bytes, "paper.pdf").unwrap();
+        assert!(flat[0].content.contains("left column opens here right column follows on"));
+    }
+
+    #[test]
+    fn test_undecodable_pages_are_reported() {
+        let bytes = build_pdf(vec![Some(vec![(72.0, 760.0, "Readable")]), None, Some(vec![])]);
+        let extraction = PdfReader::default().extract(&bytes, "mixed").unwrap();
+        let pages: Vec<_> = extraction.documents.iter().map(|d| (d.meta_data["page"].clone(), d.content.as_str())).collect();
+        assert_eq!(pages, vec![(json!(1), "Readable"), (json!(3), "")]);
+        let failed: Vec<u32> = extraction.failed_pages.iter().map(|f| f.page).collect();
+        assert_eq!(failed, vec![2]);
+
+        // Readers that only return documents list the skipped pages in their meta_data.
+        let documents = PdfReader::default().read_bytes(&bytes, "mixed.pdf").unwrap();
+        assert_eq!(documents.len(), 2);
+        let reason = &extraction.failed_pages[0].reason;
+        for document in &documents {
+            assert_eq!(document.meta_data[FAILED_PAGES_KEY], json!([{"page": 2, "reason": reason}]));
+        }
+        let clean = PdfReader::default().read_bytes(&build_pdf(vec![Some(vec![(72.0, 760.0, "Fine")])]), "clean.pdf").unwrap();
+        assert!(!clean[0].meta_data.contains_key(FAILED_PAGES_KEY));
+
+        // Blank pages are empty, not failed.
+        let blank = PdfReader::default().read_bytes(&build_pdf(vec![Some(vec![]), Some(vec![])]), "blank.pdf").unwrap();
+        assert_eq!(blank.iter().map(|d| d.content.as_str()).collect::<Vec<_>>(), ["", ""]);
+
+        let unreadable = build_pdf(vec![None]);
+        let result = PdfReader::default().read_bytes(&unreadable, "broken.pdf");
+        assert!(matches!(result, Err(ReaderError::ParseError(message)) if message.starts_with("no page could be decoded")));
+        assert!(matches!(PdfReader::default().read_bytes(b"not a pdf", "x.pdf"), Err(ReaderError::ParseError(_))));
+    }
+}
@@ -0,0 +1,210 @@
+use super::new_document;
+use crate::Document;
+use serde_json::json;
+
+/// A structural element of a rich document, rendered as Markdown.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Block {
+    Heading { level: usize, text: String },
+    Paragraph(String),
+    /// `number` is the item's position in an ordered list, `None` for bullets.
+    ListItem { depth: usize, number: Option<usize>, text: String },
+    Table(Vec<Vec<String>>),
+    Code(String),
+}
+
+impl Block {
+    fn render(&self) -> String {
+        match self {
+            Block::Heading { level, text } => format!("{} {}", "#".repeat(*level), text),
+            Block::Paragraph(text) => text.clone(),
+            Block::ListItem { depth, number, text } => {
+                let marker = number.map_or("-".to_string(), |n| format!("{}.", n));
+                format!("{}{} {}", "  ".repeat(*depth), marker, text)
+            }
+            Block::Table(rows) => render_table(rows),
+            Block::Code(code) => format!("```\n{}\n```", code.trim_end_matches('\n')),
+        }
+    }
+}
+
+fn render_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let render_row = |row: &[String]| {
+        let cells: Vec<String> = (0..columns)
+            .map(|index| {
+                let cell = row.get(index).map_or("", String::as_str);
+                collapse_whitespace(cell).replace('|', "\\|")
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    for (index, row) in rows.iter().enumerate() {
+        lines.push(render_row(row));
+        if index == 0 {
+            lines.push(format!("|{}", " --- |".repeat(columns)));
+        }
+    }
+    lines.join("\n")
+}
+
+/// Collapses every run of whitespace into a single space and trims the ends.
+pub(crate) fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The `(level, text)` headings leading to a section.
+type HeadingPath = Vec<(usize, String)>;
+
+/// The blocks of a rich document, in reading order, and its title.
+#[derive(Debug, Default)]
+pub(crate) struct Outline {
+    pub title: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+impl Outline {
+    /// Appends a block, dropping ones without any text.
+    pub(crate) fn push(&mut self, block: Block) {
+        let empty = match &block {
+            Block::Heading { text, .. } | Block::Paragraph(text) | Block::ListItem { text, .. } => {
+                text.trim().is_empty()
+            }
+            Block::Table(rows) => rows.iter().flatten().all(|cell| cell.trim().is_empty()),
+            Block::Code(code) => code.trim().is_empty(),
+        };
+        if !empty {
+            self.blocks.push(block);
+        }
+    }
+
+    /// Renders the outline as a single document, or as one document per
+    /// heading section when `split_on_headings` is set.
+    ///
+    /// Documents record the `title` (falling back to the first level-1
+    /// heading); sections also record their `heading`, `heading_level` and
+    /// `section_path`, the headings leading to them from the top.
+    pub(crate) fn into_documents(self, name: &str, split_on_headings: bool) -> Vec<Document> {
+        let title = self.title.filter(|title| !title.trim().is_empty()).or_else(|| {
+            self.blocks.iter().find_map(|block| match block {
+                Block::Heading { level: 1, text } => Some(text.clone()),
+                _ => None,
+            })
+        });
+        let with_title = |mut document: Document| {
+            if let Some(title) = &title {
+                document.meta_data.insert("title".to_string(), json!(title));
+            }
+            document
+        };
+
+        if !split_on_headings {
+            if self.blocks.is_empty() {
+                return Vec::new();
+            }
+            return vec![with_title(new_document(render_blocks(&self.blocks), name, None))];
+        }
+
+        let mut sections: Vec<(HeadingPath, Vec<Block>)> = vec![(Vec::new(), Vec::new())];
+        let mut path: HeadingPath = Vec::new();
+        for block in self.blocks {
+            if let Block::Heading { level, text } = &block {
+                while path.last().is_some_and(|(parent, _)| parent >= level) {
+                    path.pop();
+                }
+                path.push((*level, text.clone()));
+                sections.push((path.clone(), Vec::new()));
+            }
+            if let Some((_, blocks)) = sections.last_mut() {
+                blocks.push(block);
+            }
+        }
+
+        let mut documents = Vec::new();
+        for (path, blocks) in sections {
+            if blocks.is_empty() {
+                continue;
+            }
+            let id = format!("{}_{}", name, documents.len() + 1);
+            let mut document = with_title(new_document(render_blocks(&blocks), name, Some(id)));
+            if let Some((level, heading)) = path.last() {
+                let section_path: Vec<&str> = path.iter().map(|(_, text)| text.as_str()).collect();
+                document.meta_data.insert("heading".to_string(), json!(heading));
+                document.meta_data.insert("heading_level".to_string(), json!(level));
+                document.meta_data.insert("section_path".to_string(), json!(section_path));
+            }
+            documents.push(document);
+        }
+        documents
+    }
+}
+
+/// Joins blocks with blank lines, keeping consecutive list items together.
+fn render_blocks(blocks: &[Block]) -> String {
+    let mut out = String::new();
+    let mut previous_was_item = false;
+    for block in blocks {
+        let is_item = matches!(block, Block::ListItem { .. });
+        if !out.is_empty() {
+            out.push_str(if is_item && previous_was_item { "\n" } else { "\n\n" });
+        }
+        out.push_str(&block.render());
+        previous_was_item = is_item;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heading(level: usize, text: &str) -> Block {
+        Block::Heading { level, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_render_blocks() {
+        let blocks = vec![
+            heading(1, "Guide"),
+            Block::Paragraph("Intro.".to_string()),
+            Block::ListItem { depth: 0, number: Some(1), text: "first".to_string() },
+            Block::ListItem { depth: 1, number: None, text: "nested".to_string() },
+            Block::Table(vec![
+                vec!["Plan".to_string(), "Price".to_string()],
+                vec!["a|b".to_string()],
+            ]),
+        ];
+        assert_eq!(
+            render_blocks(&blocks),
+            "# Guide\n\nIntro.\n\n1. first\n  - nested\n\n| Plan | Price |\n| --- | --- |\n| a\\|b |  |"
+        );
+    }
+
+    #[test]
+    fn test_sections_record_their_path() {
+        let mut outline = Outline::default();
+        outline.push(Block::Paragraph("Preface".to_string()));
+        outline.push(heading(1, "Guide"));
+        outline.push(heading(2, "Install"));
+        outline.push(Block::Paragraph("   ".to_string()));
+        outline.push(heading(3, "Linux"));
+        outline.push(heading(2, "Usage"));
+        let documents = outline.into_documents("guide", true);
+
+        let paths: Vec<_> = documents.iter().map(|d| d.meta_data.get("section_path").cloned()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                None,
+                Some(json!(["Guide"])),
+                Some(json!(["Guide", "Install"])),
+                Some(json!(["Guide", "Install", "Linux"])),
+                Some(json!(["Guide", "Usage"])),
+            ]
+        );
+        assert!(documents.iter().all(|d| d.meta_data["title"] == json!("Guide")));
+        assert_eq!(documents[4].id, Some("guide_5".to_string()));
+        assert_eq!(documents[4].meta_data["heading_level"], json!(2));
+    }
+}
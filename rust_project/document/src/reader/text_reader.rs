@@ -0,0 +1,159 @@
+use super::{new_document, read_line, Reader, ReaderError, SharedChunkingStrategy};
+use crate::chunking::CodeChunking;
+use crate::Document;
+use pyo3::prelude::*;
+use serde_json::json;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Reader for plain text files.
+///
+/// Produces a single document for the whole file, or one document per
+/// `lines_per_document` lines when set, so very large files are streamed
+/// line by line. Each document records `start_line` and `end_line`
+/// (1-based, inclusive) in its `meta_data`; windowed documents also record
+/// their `page`. Line endings are normalized to `\n`.
+#[pyclass(name = "TextReader")]
+#[derive(Clone, Default)]
+pub struct TextReader {
+    pub lines_per_document: Option<usize>,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+}
+
+impl TextReader {
+    /// Splits the file into documents of at most `lines` lines each.
+    pub fn with_lines_per_document(mut self, lines: usize) -> Self {
+        self.lines_per_document = Some(lines.max(1));
+        self
+    }
+
+    /// Chunks every document read with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+
+    fn flush(
+        &self,
+        documents: &mut Vec<Document>,
+        lines: &mut Vec<String>,
+        start_line: usize,
+        name: &str,
+    ) {
+        if lines.is_empty() {
+            return;
+        }
+        let end_line = start_line + lines.len() - 1;
+        let mut document = new_document(lines.join("\n"), name, None);
+        if self.lines_per_document.is_some() {
+            let page = documents.len() + 1;
+            document.id = Some(format!("{}_{}", name, page));
+            document.meta_data.insert("page".to_string(), json!(page));
+        }
+        document.meta_data.insert("start_line".to_string(), json!(start_line));
+        document.meta_data.insert("end_line".to_string(), json!(end_line));
+        documents.push(document);
+        lines.clear();
+    }
+}
+
+impl Reader for TextReader {
+    fn parse(&self, stream: &mut dyn BufRead, name: &str) -> Result<Vec<Document>, ReaderError> {
+        let mut documents = Vec::new();
+        let mut lines = Vec::new();
+        let mut buffer = String::new();
+        let mut start_line = 1;
+        let mut line_number = 1;
+        while read_line(stream, &mut buffer, line_number)? {
+            lines.push(buffer.clone());
+            if self.lines_per_document.is_some_and(|limit| lines.len() >= limit) {
+                self.flush(&mut documents, &mut lines, start_line, name);
+                start_line = line_number + 1;
+            }
+            line_number += 1;
+        }
+        self.flush(&mut documents, &mut lines, start_line, name);
+        Ok(documents)
+    }
+
+    fn chunking_strategy(&self) -> Option<&SharedChunkingStrategy> {
+        self.chunking_strategy.as_ref()
+    }
+}
+
+#[pymethods]
+impl TextReader {
+    /// Creates a new `TextReader`.
+    ///
+    /// Args:
+    ///     lines_per_document (Optional[int]): Split the file into documents of this many lines. Defaults to None.
+    ///     chunking_strategy (Optional[CodeChunking]): Strategy applied to every document read. Defaults to None.
+    #[new]
+    #[pyo3(signature = (lines_per_document=None, chunking_strategy=None))]
+    fn py_new(lines_per_document: Option<usize>, chunking_strategy: Option<CodeChunking>) -> Self {
+        TextReader {
+            lines_per_document: lines_per_document.map(|lines| lines.max(1)),
+            chunking_strategy: chunking_strategy.map(|strategy| Arc::new(strategy) as SharedChunkingStrategy),
+        }
+    }
+
+    /// Reads a text file.
+    ///
+    /// Args:
+    ///     path (Union[str, Path]): Path to the file.
+    ///
+    /// Returns:
+    ///     List[Document]: The documents (or chunks) read from the file.
+    ///
+    /// Raises:
+    ///     FileNotFoundError: If the file does not exist.
+    ///     ValueError: If the file is not valid UTF-8.
+    #[pyo3(name = "read")]
+    fn read_py(&self, py: Python<'_>, path: PathBuf) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_path(&path))?)
+    }
+
+    /// Reads text from bytes, naming the documents after `file_name`.
+    #[pyo3(name = "read_bytes")]
+    fn read_bytes_py(&self, py: Python<'_>, data: &[u8], file_name: &str) -> PyResult<Vec<Document>> {
+        Ok(py.allow_threads(|| self.read_bytes(data, file_name))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_document_with_line_range() {
+        let documents = TextReader::default().read_bytes(b"one\r\ntwo\r\nthree", "story.txt").unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].content, "one\ntwo\nthree");
+        assert_eq!(documents[0].id, None);
+        assert_eq!(documents[0].meta_data["start_line"], json!(1));
+        assert_eq!(documents[0].meta_data["end_line"], json!(3));
+        assert!(!documents[0].meta_data.contains_key("page"));
+    }
+
+    #[test]
+    fn test_lines_per_document_windows() {
+        let reader = TextReader::default().with_lines_per_document(2);
+        let documents = reader.read_bytes(b"1\n2\n3\n4\n5\n", "numbers.txt").unwrap();
+        let ranges: Vec<(String, &str)> = documents
+            .iter()
+            .map(|d| (format!("{}-{}", d.meta_data["start_line"], d.meta_data["end_line"]), d.content.as_str()))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![("1-2".to_string(), "1\n2"), ("3-4".to_string(), "3\n4"), ("5-5".to_string(), "5")]
+        );
+        assert_eq!(documents[2].id, Some("numbers_3".to_string()));
+        assert_eq!(documents[2].meta_data["page"], json!(3));
+    }
+
+    #[test]
+    fn test_empty_input() {
+        assert!(TextReader::default().read_bytes(b"", "empty.txt").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+//! Documents for the document tests.
+
+test_support::document_fixtures!(crate::Document);
@@ -0,0 +1,432 @@
+//! Schema versions of serialized documents, and migrations from older ones.
+//!
+//! Serialized documents carry a `schema_version` key, written first. When
+//! deserialization finds the current version or a newer one there, it reads
+//! the fields straight into the typed value. Otherwise (documents written
+//! before versioning have no key and count as version 1) it collects them as
+//! JSON, runs the migrations from the stored version up to the current one,
+//! and only then reads the migrated fields. Keys the current version does
+//! not know are kept in `extra` and written back, and so is the version of a
+//! newer document, so an older build passing one along neither strips its new
+//! fields nor makes a newer build migrate them again.
+
+use crate::Document;
+use serde::de::{DeserializeSeed, Error as _, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The key holding the schema version of a serialized value.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// The version of values serialized before versioning was introduced.
+pub const UNVERSIONED: u32 = 1;
+
+/// The schema version `Document` is serialized with.
+///
+/// - 1: the original format, without `schema_version`.
+/// - 2: adds `schema_version`; a null `meta_data` becomes an empty map, and
+///   the `embedder` key written by the Python dataclass is dropped.
+pub const DOCUMENT_SCHEMA_VERSION: u32 = 2;
+
+/// Upgrades a serialized value by one version, in place.
+pub type Migration = fn(&mut Map<String, JsonValue>) -> Result<(), String>;
+
+// Define a custom error type for schema versioning
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionError {
+    NotAnObject,
+    InvalidVersion(JsonValue),
+    Migration { from: u32, message: String },
+}
+
+impl fmt::Display for VersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionError::NotAnObject => write!(f, "Expected a JSON object"),
+            VersionError::InvalidVersion(value) => {
+                write!(f, "Invalid {}: {}, expected a positive integer", SCHEMA_VERSION_KEY, value)
+            }
+            VersionError::Migration { from, message } => {
+                write!(f, "Migration from schema version {} failed: {}", from, message)
+            }
+        }
+    }
+}
+
+impl Error for VersionError {}
+
+fn parse_version(value: JsonValue) -> Result<u32, VersionError> {
+    value
+        .as_u64()
+        .and_then(|version| u32::try_from(version).ok())
+        .filter(|version| *version >= UNVERSIONED)
+        .ok_or(VersionError::InvalidVersion(value))
+}
+
+/// Brings `object` to the version after the last of `migrations`, where
+/// `migrations[i]` upgrades version `i + 1`. Removes the version key and
+/// returns the version the object had.
+///
+/// Objects from a newer version than the migrations know are left as they
+/// are: their new keys end up among the unknown fields.
+pub fn migrate(object: &mut Map<String, JsonValue>, migrations: &[Migration]) -> Result<u32, VersionError> {
+    let version = match object.remove(SCHEMA_VERSION_KEY) {
+        None => UNVERSIONED,
+        Some(value) => parse_version(value)?,
+    };
+    run_migrations(object, version, migrations)?;
+    Ok(version)
+}
+
+/// Runs the `migrations` from `version` on, see [`migrate`].
+fn run_migrations(object: &mut Map<String, JsonValue>, version: u32, migrations: &[Migration]) -> Result<(), VersionError> {
+    let first = (version - UNVERSIONED) as usize;
+    for (index, migration) in migrations.iter().enumerate().skip(first) {
+        migration(object).map_err(|message| VersionError::Migration { from: index as u32 + UNVERSIONED, message })?;
+    }
+    Ok(())
+}
+
+/// The version a value read as `stored` keeps, if newer than `current`.
+pub fn newer_version(stored: u32, current: u32) -> Option<u32> {
+    (stored > current).then_some(stored)
+}
+
+/// The version to write a value with: the newer version it was read with,
+/// or else `current`.
+pub fn written_version(newer: Option<u32>, current: u32) -> u32 {
+    newer.map_or(current, |newer| newer.max(current))
+}
+
+/// Migrates a serialized document to [`DOCUMENT_SCHEMA_VERSION`], returning
+/// it without the version key.
+pub fn migrate_document(value: JsonValue) -> Result<JsonValue, VersionError> {
+    let JsonValue::Object(mut object) = value else {
+        return Err(VersionError::NotAnObject);
+    };
+    migrate(&mut object, DOCUMENT_MIGRATIONS)?;
+    Ok(JsonValue::Object(object))
+}
+
+const DOCUMENT_MIGRATIONS: &[Migration] = &[document_v1_to_v2];
+
+fn document_v1_to_v2(object: &mut Map<String, JsonValue>) -> Result<(), String> {
+    if object.get("meta_data").is_some_and(JsonValue::is_null) {
+        object.insert("meta_data".to_string(), JsonValue::Object(Map::new()));
+    }
+    // The Python dataclass serializes its embedder object, which is not data.
+    object.remove("embedder");
+    Ok(())
+}
+
+/// Serialized form of a `Document`, borrowing its fields.
+#[derive(Serialize)]
+struct DocumentOut<'a> {
+    schema_version: u32,
+    content: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'a str>,
+    meta_data: &'a HashMap<String, JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    embedding: Option<&'a [f32]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<&'a HashMap<String, JsonValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reranking_score: Option<f64>,
+    #[serde(flatten)]
+    extra: &'a HashMap<String, JsonValue>,
+}
+
+impl Serialize for Document {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        DocumentOut {
+            schema_version: written_version(self.schema_version, DOCUMENT_SCHEMA_VERSION),
+            content: &self.content,
+            id: self.id.as_deref(),
+            name: self.name.as_deref(),
+            meta_data: &self.meta_data,
+            embedding: self.embedding.as_deref(),
+            usage: self.usage.as_ref(),
+            reranking_score: self.reranking_score,
+            extra: &self.extra,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Versioned for Document {
+    const VERSION: u32 = DOCUMENT_SCHEMA_VERSION;
+    const MIGRATIONS: &'static [Migration] = DOCUMENT_MIGRATIONS;
+    const REQUIRED: &'static [&'static str] = &["content"];
+
+    fn read_field<'de, D: Deserializer<'de>>(&mut self, key: String, value: D) -> Result<(), D::Error> {
+        match key.as_str() {
+            "content" => self.content = String::deserialize(value)?,
+            "id" => self.id = Option::deserialize(value)?,
+            "name" => self.name = Option::deserialize(value)?,
+            // A null map reads as an empty one, in every version.
+            "meta_data" => self.meta_data = Option::deserialize(value)?.unwrap_or_default(),
+            "embedding" => self.embedding = Option::deserialize(value)?,
+            "usage" => self.usage = Option::deserialize(value)?,
+            "reranking_score" => self.reranking_score = Option::deserialize(value)?,
+            _ => {
+                self.extra.insert(key, JsonValue::deserialize(value)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_newer_version(&mut self, version: Option<u32>) {
+        self.schema_version = version;
+    }
+}
+
+impl Document {
+    /// `extra` with the schema version of a document from a newer version,
+    /// for layouts that store the unknown fields as one JSON object.
+    pub(crate) fn versioned_extra(&self) -> HashMap<String, JsonValue> {
+        let mut extra = self.extra.clone();
+        if let Some(version) = self.schema_version {
+            extra.insert(SCHEMA_VERSION_KEY.to_string(), JsonValue::from(version));
+        }
+        extra
+    }
+
+    /// Restores `extra` and the schema version from an object written by
+    /// [`Document::versioned_extra`].
+    pub(crate) fn set_versioned_extra(&mut self, mut extra: HashMap<String, JsonValue>) -> Result<(), VersionError> {
+        let version = extra.remove(SCHEMA_VERSION_KEY).map(parse_version).transpose()?;
+        self.schema_version = version.and_then(|version| newer_version(version, DOCUMENT_SCHEMA_VERSION));
+        self.extra = extra;
+        Ok(())
+    }
+}
+
+/// A value serialized with a schema version, whose fields
+/// [`VersionedVisitor`] reads one at a time.
+pub trait Versioned: Default {
+    /// The schema version the value is serialized with.
+    const VERSION: u32;
+
+    /// The migrations from every older version, as for [`migrate`].
+    const MIGRATIONS: &'static [Migration];
+
+    /// The keys a serialized value must have.
+    const REQUIRED: &'static [&'static str];
+
+    /// Reads the field `key` of the current version from `value`, keeping
+    /// unknown keys among the unknown fields.
+    fn read_field<'de, D: Deserializer<'de>>(&mut self, key: String, value: D) -> Result<(), D::Error>;
+
+    /// Records the version of a value read from a newer version.
+    fn set_newer_version(&mut self, version: Option<u32>);
+}
+
+/// Deserializes a map of fields into `T`.
+///
+/// Once the `schema_version` key shows the current version or a newer one,
+/// the following fields are read straight into `T`. Fields before that key,
+/// and all fields of an older version, are collected as JSON first; those of
+/// an older version are migrated before they are read.
+///
+/// Failing inside `visit_map` lets the deserializer attach the position of
+/// the map to the error, e.g. the line of a bad document in a JSON array.
+pub struct VersionedVisitor<T>(PhantomData<T>);
+
+impl<T> VersionedVisitor<T> {
+    pub fn new() -> Self {
+        VersionedVisitor(PhantomData)
+    }
+}
+
+impl<T> Default for VersionedVisitor<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads one map value into `value` through [`Versioned::read_field`].
+struct FieldSeed<'a, T> {
+    value: &'a mut T,
+    key: String,
+}
+
+impl<'de, T: Versioned> DeserializeSeed<'de> for FieldSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        self.value.read_field(self.key, deserializer)
+    }
+}
+
+impl<'de, T: Versioned> Visitor<'de> for VersionedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a map of fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let mut value = T::default();
+        let mut found = vec![false; T::REQUIRED.len()];
+        let mut pending = Map::new();
+        let mut version = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == SCHEMA_VERSION_KEY {
+                if version.is_some() {
+                    return Err(A::Error::duplicate_field(SCHEMA_VERSION_KEY));
+                }
+                version = Some(parse_version(map.next_value()?).map_err(A::Error::custom)?);
+                continue;
+            }
+            if let Some(index) = T::REQUIRED.iter().position(|required| *required == key) {
+                found[index] = true;
+            }
+            match version {
+                Some(stored) if stored >= T::VERSION => map.next_value_seed(FieldSeed { value: &mut value, key })?,
+                _ => {
+                    pending.insert(key, map.next_value()?);
+                }
+            }
+        }
+
+        let version = version.unwrap_or(UNVERSIONED);
+        if version < T::VERSION {
+            run_migrations(&mut pending, version, T::MIGRATIONS).map_err(A::Error::custom)?;
+            // Migrations may add or rename required keys.
+            found = T::REQUIRED.iter().map(|required| pending.contains_key(*required)).collect();
+        }
+        if let Some(index) = found.iter().position(|found| !found) {
+            return Err(A::Error::missing_field(T::REQUIRED[index]));
+        }
+        for (key, field) in pending {
+            value.read_field(key, field).map_err(A::Error::custom)?;
+        }
+        value.set_newer_version(newer_version(version, T::VERSION));
+        Ok(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(VersionedVisitor::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Documents in the format written before versioning.
+    const DOCUMENTS_V1: &str = include_str!("../fixtures/documents_v1.json");
+
+    #[test]
+    fn test_v1_fixtures_are_migrated() {
+        let documents: Vec<Document> = serde_json::from_str(DOCUMENTS_V1).unwrap();
+        assert_eq!(documents.len(), 3);
+
+        assert_eq!(documents[0].content, "Full document");
+        assert_eq!(documents[0].meta_data["source"], serde_json::json!("test"));
+        assert_eq!(documents[0].usage.as_ref().unwrap()["tokens"], serde_json::json!(120));
+        assert_eq!(documents[0].reranking_score, Some(0.88));
+        assert!(documents[0].extra.is_empty());
+
+        assert_eq!(documents[1].id, None);
+        assert!(documents[1].meta_data.is_empty());
+
+        // Written by the Python dataclass: every field present, nulls included.
+        assert!(documents[2].meta_data.is_empty());
+        assert_eq!(documents[2].embedding, Some(vec![0.25, 0.5]));
+        assert!(documents[2].extra.is_empty(), "{:?}", documents[2].extra);
+
+        let json = serde_json::to_value(&documents[1]).unwrap();
+        assert_eq!(json[SCHEMA_VERSION_KEY], serde_json::json!(DOCUMENT_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_newer_versions_keep_unknown_fields() {
+        let json = r#"{"schema_version": 9, "content": "From the future", "created_at": 1700000000, "tags": ["a"]}"#;
+        let document: Document = serde_json::from_str(json).unwrap();
+        assert_eq!(document.content, "From the future");
+        assert_eq!(document.extra["created_at"], serde_json::json!(1700000000));
+        assert_eq!(document.schema_version, Some(9));
+
+        let rewritten = serde_json::to_value(&document).unwrap();
+        assert_eq!(rewritten["tags"], serde_json::json!(["a"]));
+        assert_eq!(rewritten[SCHEMA_VERSION_KEY], serde_json::json!(9));
+        assert_eq!(serde_json::from_value::<Document>(rewritten).unwrap(), document);
+
+        // Older and current versions are written as the current one.
+        let current: Document = serde_json::from_str(r#"{"schema_version": 2, "content": "now"}"#).unwrap();
+        assert_eq!(current, Document::new("now"));
+        let null_meta_data: Document = serde_json::from_str(r#"{"schema_version": 2, "content": "now", "meta_data": null}"#).unwrap();
+        assert_eq!(null_meta_data, current);
+        let mut document = document;
+        document.set_versioned_extra(document.versioned_extra()).unwrap();
+        assert_eq!(document.schema_version, Some(9));
+    }
+
+    #[test]
+    fn test_fields_are_read_in_any_order() {
+        let expected = Document {
+            id: Some("d".to_string()),
+            embedding: Some(vec![0.1, 0.2]),
+            extra: HashMap::from([("tags".to_string(), serde_json::json!(["a"]))]),
+            ..Document::new("text")
+        };
+        for json in [
+            r#"{"schema_version": 2, "content": "text", "id": "d", "embedding": [0.1, 0.2], "tags": ["a"]}"#,
+            r#"{"content": "text", "id": "d", "schema_version": 2, "embedding": [0.1, 0.2], "tags": ["a"]}"#,
+            r#"{"content": "text", "id": "d", "embedding": [0.1, 0.2], "tags": ["a"], "embedder": null}"#,
+        ] {
+            assert_eq!(serde_json::from_str::<Document>(json).unwrap(), expected, "{}", json);
+        }
+
+        for json in [r#"{"schema_version": 2, "id": "d"}"#, r#"{"id": "d", "schema_version": 2}"#, r#"{"id": "d"}"#] {
+            let err = serde_json::from_str::<Document>(json).unwrap_err();
+            assert!(err.to_string().contains("missing field `content`"), "{}", err);
+        }
+        let err = serde_json::from_str::<Document>(r#"{"schema_version": 2, "content": "x", "schema_version": 2}"#).unwrap_err();
+        assert!(err.to_string().contains("duplicate field `schema_version`"), "{}", err);
+        let err = serde_json::from_str::<Document>(r#"{"schema_version": 2, "content": 1}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid type"), "{}", err);
+    }
+
+    #[test]
+    fn test_invalid_versions_are_rejected() {
+        for version in ["0", "-1", "\"2\"", "1.5"] {
+            let json = format!(r#"{{"schema_version": {}, "content": "x"}}"#, version);
+            let err = serde_json::from_str::<Document>(&json).unwrap_err();
+            assert!(err.to_string().contains("Invalid schema_version"), "{}", err);
+        }
+        assert_eq!(migrate_document(serde_json::json!([1])), Err(VersionError::NotAnObject));
+    }
+
+    #[test]
+    fn test_migrations_run_from_the_stored_version() {
+        fn add_a(object: &mut Map<String, JsonValue>) -> Result<(), String> {
+            object.insert("a".to_string(), serde_json::json!(1));
+            Ok(())
+        }
+        fn fail(_: &mut Map<String, JsonValue>) -> Result<(), String> {
+            Err("no b".to_string())
+        }
+        let mut object = Map::new();
+        object.insert(SCHEMA_VERSION_KEY.to_string(), serde_json::json!(2));
+        assert_eq!(migrate(&mut object, &[fail, add_a]), Ok(2));
+        assert_eq!(object.get("a"), Some(&serde_json::json!(1)));
+        assert!(!object.contains_key(SCHEMA_VERSION_KEY));
+
+        let mut object = Map::new();
+        assert_eq!(
+            migrate(&mut object, &[fail, add_a]),
+            Err(VersionError::Migration { from: 1, message: "no b".to_string() })
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::EmbedderError;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Embeddings on disk, keyed by a hash of the model and the text.
+///
+/// Each vector is a file of little-endian `f32`s, named after its key and
+/// spread over 256 subdirectories. Files are written under a temporary
+/// name and renamed, so a reader never sees a partial vector, and several
+/// processes can share a cache directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingCache {
+    dir: PathBuf,
+}
+
+impl EmbeddingCache {
+    /// Uses `dir` as the cache, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, EmbedderError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| EmbedderError::Cache(format!("{}: {}", dir.display(), e)))?;
+        Ok(EmbeddingCache { dir })
+    }
+
+    /// The directory holding the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The key of `text` embedded by the model `model_id`.
+    pub fn key(model_id: &str, text: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(model_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(text.as_bytes());
+        hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(&key[..2]).join(key)
+    }
+
+    /// The cached vector under `key`. Missing and unreadable entries are
+    /// both `None`, so a damaged file is simply embedded again.
+    pub async fn get(&self, key: &str) -> Option<Vec<f32>> {
+        let bytes = tokio::fs::read(self.path(key)).await.ok()?;
+        if bytes.is_empty() || bytes.len() % 4 != 0 {
+            return None;
+        }
+        Some(bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+    }
+
+    /// Stores `vector` under `key`, replacing any previous entry.
+    pub async fn put(&self, key: &str, vector: &[f32]) -> Result<(), EmbedderError> {
+        let path = self.path(key);
+        let error = |e: std::io::Error| EmbedderError::Cache(format!("{}: {}", path.display(), e));
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(error)?;
+        }
+        let bytes: Vec<u8> = vector.iter().flat_map(|value| value.to_le_bytes()).collect();
+        let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+        tokio::fs::write(&temporary, bytes).await.map_err(error)?;
+        tokio::fs::rename(&temporary, &path).await.map_err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_get() {
+        let dir = std::env::temp_dir().join(format!("embedder_cache_{}", std::process::id()));
+        let cache = EmbeddingCache::open(&dir).unwrap();
+        let key = EmbeddingCache::key("model-a", "hello");
+        assert_ne!(key, EmbeddingCache::key("model-b", "hello"));
+        assert_ne!(EmbeddingCache::key("ab", "c"), EmbeddingCache::key("a", "bc"));
+
+        assert_eq!(cache.get(&key).await, None);
+        cache.put(&key, &[0.5, -1.25, 3.0]).await.unwrap();
+        assert_eq!(cache.get(&key).await, Some(vec![0.5, -1.25, 3.0]));
+
+        std::fs::write(cache.path(&key), [1, 2, 3]).unwrap();
+        assert_eq!(cache.get(&key).await, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,324 @@
+use crate::{Embedder, EmbedderError, Embeddings, Usage};
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// The OpenAI API, the default base URL.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// An embedder for any server speaking the OpenAI `/embeddings` protocol:
+/// OpenAI itself, Azure, Together, Ollama, vLLM, text-embeddings-inference
+/// and others.
+///
+/// The API key goes in the `Authorization` header as a bearer token by
+/// default; [`HttpEmbedder::with_auth_header`] names another header, such
+/// as Azure's `api-key`, which then carries the bare key.
+#[derive(Debug, Clone)]
+pub struct HttpEmbedder {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    dimensions: Option<usize>,
+    api_key: Option<String>,
+    auth_header: String,
+    timeout: Duration,
+}
+
+#[derive(Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [&'a str],
+    encoding_format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorBody,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+impl HttpEmbedder {
+    /// An embedder for `model` on the OpenAI API, without an API key and
+    /// with a 60 second timeout.
+    pub fn new(model: &str) -> Self {
+        HttpEmbedder {
+            client: reqwest::Client::new(),
+            base_url: OPENAI_BASE_URL.to_string(),
+            model: model.to_string(),
+            dimensions: None,
+            api_key: None,
+            auth_header: "Authorization".to_string(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the URL the `/embeddings` path is appended to, e.g.
+    /// `http://localhost:11434/v1`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Asks for vectors of `dimensions`, for models that can shorten them.
+    pub fn with_dimensions(mut self, dimensions: Option<usize>) -> Self {
+        self.dimensions = dimensions;
+        self
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Sets the header carrying the API key.
+    pub fn with_auth_header(mut self, auth_header: &str) -> Self {
+        self.auth_header = auth_header.to_string();
+        self
+    }
+
+    /// Sets how long a request may take, connection included.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn request(&self, texts: &[&str]) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .post(format!("{}/embeddings", self.base_url))
+            .timeout(self.timeout)
+            .json(&EmbeddingRequest {
+                model: &self.model,
+                input: texts,
+                encoding_format: "float",
+                dimensions: self.dimensions,
+            });
+        match &self.api_key {
+            Some(key) if self.auth_header.eq_ignore_ascii_case("authorization") => request.bearer_auth(key),
+            Some(key) => request.header(self.auth_header.as_str(), key),
+            None => request,
+        }
+    }
+}
+
+/// How long the server asked to wait: OpenAI's `retry-after-ms`, or the
+/// standard `Retry-After` in seconds. Waits too long for a `Duration`,
+/// e.g. "inf", read as `Duration::MAX`.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let seconds = match header("retry-after-ms").and_then(|value| value.trim().parse::<f64>().ok()) {
+        Some(millis) => millis / 1000.0,
+        None => header(RETRY_AFTER.as_str())?.trim().parse::<f64>().ok()?,
+    };
+    if seconds.is_nan() {
+        return None;
+    }
+    Some(Duration::try_from_secs_f64(seconds.max(0.0)).unwrap_or(Duration::MAX))
+}
+
+fn request_error(err: reqwest::Error) -> EmbedderError {
+    // reqwest's messages leave out the cause, e.g. "connection refused".
+    let mut message = err.to_string();
+    let mut source = std::error::Error::source(&err);
+    while let Some(cause) = source {
+        message = format!("{}: {}", message, cause);
+        source = cause.source();
+    }
+    if err.is_decode() {
+        EmbedderError::InvalidResponse(message)
+    } else {
+        EmbedderError::Request(message)
+    }
+}
+
+#[async_trait]
+impl Embedder for HttpEmbedder {
+    fn model_id(&self) -> String {
+        match self.dimensions {
+            Some(dimensions) => format!("{}@{}/{}", self.model, self.base_url, dimensions),
+            None => format!("{}@{}", self.model, self.base_url),
+        }
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError> {
+        let response = self.request(texts).send().await.map_err(request_error)?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(EmbedderError::RateLimited { retry_after: retry_after(response.headers()) });
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let message = match serde_json::from_str::<ErrorResponse>(&body) {
+                Ok(error) => error.error.message,
+                Err(_) => body,
+            };
+            return Err(EmbedderError::Server { status: status.as_u16(), message });
+        }
+
+        let body = response.bytes().await.map_err(request_error)?;
+        let response: EmbeddingResponse =
+            serde_json::from_slice(&body).map_err(|e| EmbedderError::InvalidResponse(e.to_string()))?;
+        let mut vectors: Vec<Option<Vec<f32>>> = vec![None; texts.len()];
+        for data in response.data {
+            match vectors.get_mut(data.index) {
+                Some(slot @ None) => *slot = Some(data.embedding),
+                _ => {
+                    return Err(EmbedderError::InvalidResponse(format!(
+                        "unexpected embedding index {} for {} inputs",
+                        data.index,
+                        texts.len()
+                    )))
+                }
+            }
+        }
+        let vectors = vectors
+            .into_iter()
+            .enumerate()
+            .map(|(index, vector)| {
+                vector.ok_or_else(|| EmbedderError::InvalidResponse(format!("missing embedding for input {}", index)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Embeddings { vectors, usage: response.usage })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{closed_url, embeddings_server, StubResponse};
+    use crate::{EmbeddingPipeline, RetryPolicy};
+    use document::Document;
+
+    #[tokio::test]
+    async fn test_request_and_response() {
+        let server = embeddings_server(Vec::new()).await;
+        let embedder = HttpEmbedder::new("text-embedding-3-small")
+            .with_base_url(&format!("{}/v1/", server.url()))
+            .with_api_key(Some("sk-test".to_string()))
+            .with_dimensions(Some(2));
+        let embeddings = embedder.embed(&["one", "three words here"]).await.unwrap();
+        // The stub answers in reverse order; vectors follow the inputs.
+        assert_eq!(embeddings.vectors, vec![vec![3.0, 0.0], vec![16.0, 1.0]]);
+        assert_eq!(embeddings.usage, Some(Usage { prompt_tokens: 4, total_tokens: 4 }));
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v1/embeddings");
+        assert_eq!(request.headers["authorization"], "Bearer sk-test");
+        assert_eq!(
+            request.body,
+            serde_json::json!({
+                "model": "text-embedding-3-small",
+                "input": ["one", "three words here"],
+                "encoding_format": "float",
+                "dimensions": 2
+            })
+        );
+
+        let azure = HttpEmbedder::new("ada")
+            .with_base_url(&server.url())
+            .with_api_key(Some("key".to_string()))
+            .with_auth_header("api-key");
+        azure.embed(&["x"]).await.unwrap();
+        let request = &server.requests()[1];
+        assert_eq!(request.headers["api-key"], "key");
+        assert!(!request.headers.contains_key("authorization"));
+        assert!(request.body.get("dimensions").is_none());
+        assert_ne!(azure.model_id(), embedder.model_id());
+    }
+
+    #[tokio::test]
+    async fn test_error_statuses() {
+        let server = embeddings_server(vec![
+            StubResponse::status(429).with_header("retry-after", "2"),
+            StubResponse::status(429).with_header("retry-after-ms", "250"),
+            StubResponse::status(503).with_body(r#"{"error": {"message": "overloaded"}}"#),
+            StubResponse::status(400).with_body("bad input"),
+            StubResponse::status(200).with_body(r#"{"data": [{"embedding": [1.0], "index": 3}]}"#),
+            StubResponse::status(200).with_body("not json"),
+            StubResponse::status(429).with_header("retry-after", "1e300"),
+            StubResponse::status(429).with_header("retry-after-ms", "inf"),
+            StubResponse::status(429).with_header("retry-after", "-5"),
+        ])
+        .await;
+        let embedder = HttpEmbedder::new("m").with_base_url(&server.url());
+        let mut errors = Vec::new();
+        for _ in 0..9 {
+            errors.push(embedder.embed(&["x"]).await.unwrap_err());
+        }
+        assert_eq!(errors[0], EmbedderError::RateLimited { retry_after: Some(Duration::from_secs(2)) });
+        assert_eq!(errors[1], EmbedderError::RateLimited { retry_after: Some(Duration::from_millis(250)) });
+        assert_eq!(errors[2], EmbedderError::Server { status: 503, message: "overloaded".to_string() });
+        assert_eq!(errors[3], EmbedderError::Server { status: 400, message: "bad input".to_string() });
+        assert!(matches!(&errors[4], EmbedderError::InvalidResponse(msg) if msg.contains("index 3")), "{:?}", errors[4]);
+        assert!(matches!(errors[5], EmbedderError::InvalidResponse(_)));
+        assert_eq!(errors[6], EmbedderError::RateLimited { retry_after: Some(Duration::MAX) });
+        assert_eq!(errors[7], EmbedderError::RateLimited { retry_after: Some(Duration::MAX) });
+        assert_eq!(errors[8], EmbedderError::RateLimited { retry_after: Some(Duration::ZERO) });
+    }
+
+    #[tokio::test]
+    async fn test_timeouts_and_refused_connections() {
+        let server = embeddings_server(vec![StubResponse::status(200).with_delay(Duration::from_secs(5))]).await;
+        let embedder = HttpEmbedder::new("m").with_base_url(&server.url()).with_timeout(Duration::from_millis(100));
+        let err = embedder.embed(&["x"]).await.unwrap_err();
+        assert!(matches!(&err, EmbedderError::Request(_)) && err.is_retryable(), "{:?}", err);
+
+        let url = closed_url().await;
+        let err = HttpEmbedder::new("m").with_base_url(&url).embed(&["x"]).await.unwrap_err();
+        assert!(matches!(err, EmbedderError::Request(_)), "{:?}", err);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_fills_documents() {
+        let server = embeddings_server(vec![StubResponse::status(502)]).await;
+        let embedder = HttpEmbedder::new("m").with_base_url(&server.url());
+        let retry = RetryPolicy { initial_backoff: Duration::from_millis(10), ..Default::default() };
+        let pipeline = EmbeddingPipeline::new(embedder).with_batch_size(2).with_retry(retry);
+        let mut documents: Vec<Document> = ["a b", "c", "d e f"]
+            .iter()
+            .map(|content| Document::new(*content))
+            .collect();
+        let report = pipeline.embed_documents(&mut documents).await.unwrap();
+        assert_eq!((report.embedded, report.retries), (3, 1));
+        assert_eq!(report.prompt_tokens, 6);
+        assert_eq!(documents[2].embedding, Some(vec![5.0, 0.0]));
+        assert_eq!(documents[2].usage.as_ref().unwrap()["prompt_tokens"], serde_json::json!(3));
+    }
+}
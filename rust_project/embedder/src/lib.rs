@@ -0,0 +1,150 @@
+//! Embedders turn text into vectors, over HTTP (`HttpEmbedder`) or with a
+//! model running in process (`LocalEmbedder`). The `EmbeddingPipeline`
+//! drives any of them over many documents, with batching, concurrency, rate
+//! limits, retries and an on-disk cache.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod cache;
+pub mod http;
+pub mod local;
+pub mod pipeline;
+pub mod python;
+pub mod rate_limit;
+#[cfg(test)]
+mod testing;
+
+pub use cache::EmbeddingCache;
+pub use http::HttpEmbedder;
+pub use local::LocalEmbedder;
+pub use pipeline::{EmbeddingPipeline, EmbeddingReport, RetryPolicy};
+pub use python::{PyEmbedder, PyEmbeddingPipeline, PyHttpEmbedder, PyLocalEmbedder};
+pub use rate_limit::TokenBucket;
+
+// Define a custom error type for embedding
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbedderError {
+    /// The request did not get a response, e.g. a refused connection or a timeout.
+    Request(String),
+    /// The provider asked to slow down, possibly saying for how long.
+    RateLimited { retry_after: Option<Duration> },
+    /// The provider answered with an error status.
+    Server { status: u16, message: String },
+    InvalidResponse(String),
+    Cache(String),
+    /// The files of a local model are missing or invalid.
+    Model(String),
+    /// Any other failure of the embedder; not retried.
+    Embedder(String),
+}
+
+impl EmbedderError {
+    /// Whether the request may succeed if sent again: failed requests, rate
+    /// limits, and server errors (5xx).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            EmbedderError::Request(_) | EmbedderError::RateLimited { .. } => true,
+            EmbedderError::Server { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for EmbedderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EmbedderError::Request(msg) => write!(f, "Request error: {}", msg),
+            EmbedderError::RateLimited { retry_after: Some(delay) } => {
+                write!(f, "Rate limited, retry after {:.1}s", delay.as_secs_f64())
+            }
+            EmbedderError::RateLimited { retry_after: None } => write!(f, "Rate limited"),
+            EmbedderError::Server { status, message } => write!(f, "Server error {}: {}", status, message),
+            EmbedderError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            EmbedderError::Cache(msg) => write!(f, "Cache error: {}", msg),
+            EmbedderError::Model(msg) => write!(f, "Model error: {}", msg),
+            EmbedderError::Embedder(msg) => write!(f, "Embedder error: {}", msg),
+        }
+    }
+}
+
+impl Error for EmbedderError {}
+
+/// Tokens a request was billed for, as reported by the provider.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Usage {
+    pub prompt_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// The vectors of one request, in the order of its texts.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Embeddings {
+    pub vectors: Vec<Vec<f32>>,
+    pub usage: Option<Usage>,
+}
+
+/// Mirrors Python's `agno.embedder.base.Embedder`, but embeds a batch of
+/// texts per call.
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Identifies the model, so that cached embeddings of another model are
+    /// never reused.
+    fn model_id(&self) -> String;
+
+    /// The length of the vectors, if known before the first request.
+    fn dimensions(&self) -> Option<usize> {
+        None
+    }
+
+    /// Estimates the tokens of `text`, for the tokens-per-minute limit and
+    /// for splitting a request's usage between its texts.
+    fn estimate_tokens(&self, text: &str) -> u64 {
+        // About four bytes per token for English text.
+        (text.len() as u64).div_ceil(4).max(1)
+    }
+
+    /// Embeds `texts` in one request.
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError>;
+}
+
+#[async_trait]
+impl<E: Embedder + ?Sized> Embedder for Arc<E> {
+    fn model_id(&self) -> String {
+        (**self).model_id()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        (**self).dimensions()
+    }
+
+    fn estimate_tokens(&self, text: &str) -> u64 {
+        (**self).estimate_tokens(text)
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError> {
+        (**self).embed(texts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_errors() {
+        assert!(EmbedderError::Request("refused".to_string()).is_retryable());
+        assert!(EmbedderError::RateLimited { retry_after: None }.is_retryable());
+        assert!(EmbedderError::Server { status: 503, message: String::new() }.is_retryable());
+        assert!(!EmbedderError::Server { status: 400, message: String::new() }.is_retryable());
+        assert!(!EmbedderError::InvalidResponse(String::new()).is_retryable());
+        assert_eq!(
+            EmbedderError::RateLimited { retry_after: Some(Duration::from_millis(1500)) }.to_string(),
+            "Rate limited, retry after 1.5s"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{Embedder, EmbedderError, Embeddings, Usage};
+use async_trait::async_trait;
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config, DTYPE};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Arc;
+use tokenizers::{Tokenizer, TruncationParams};
+
+/// The model and tokenizer, shared with the blocking tasks running them.
+struct Encoder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    pad_id: u32,
+}
+
+/// A BERT-style sentence encoder running on the CPU, such as
+/// `all-MiniLM-L6-v2` or `bge-small-en-v1.5`, with no Python or ONNX
+/// runtime involved.
+///
+/// The model directory holds the files of a Hugging Face model repo:
+/// `config.json`, `tokenizer.json` and `model.safetensors`. Sentence
+/// vectors are the mean of the token vectors, scaled to unit length unless
+/// [`LocalEmbedder::with_normalize`] turns that off. Texts longer than the
+/// model's positions are truncated.
+#[derive(Clone)]
+pub struct LocalEmbedder {
+    encoder: Arc<Encoder>,
+    name: String,
+    dimensions: usize,
+    normalize: bool,
+}
+
+impl LocalEmbedder {
+    /// Loads the model in `dir`.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, EmbedderError> {
+        let dir = dir.as_ref();
+        let read = |file: &str| {
+            let path = dir.join(file);
+            std::fs::read(&path).map_err(|e| EmbedderError::Model(format!("{}: {}", path.display(), e)))
+        };
+        let invalid =
+            |file: &str, e: &dyn std::fmt::Display| EmbedderError::Model(format!("{}: {}", dir.join(file).display(), e));
+
+        let config: Config = serde_json::from_slice(&read("config.json")?).map_err(|e| invalid("config.json", &e))?;
+        let mut tokenizer = Tokenizer::from_bytes(read("tokenizer.json")?).map_err(|e| invalid("tokenizer.json", &e))?;
+        tokenizer.with_padding(None);
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings,
+                ..Default::default()
+            }))
+            .map_err(|e| invalid("tokenizer.json", &e))?;
+
+        let weights = read("model.safetensors")?;
+        // The hash tells apart models in directories of the same name, and
+        // fine-tuned weights from the original ones.
+        let hash: String = Sha256::digest(&weights).iter().take(6).map(|byte| format!("{:02x}", byte)).collect();
+        let vb = VarBuilder::from_buffered_safetensors(weights, DTYPE, &Device::Cpu)
+            .map_err(|e| invalid("model.safetensors", &e))?;
+        let model = BertModel::load(vb, &config).map_err(|e| invalid("model.safetensors", &e))?;
+
+        let dir_name = dir.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        Ok(LocalEmbedder {
+            encoder: Arc::new(Encoder { model, tokenizer, pad_id: config.pad_token_id as u32 }),
+            name: format!("local:{}:{}", dir_name, hash),
+            dimensions: config.hidden_size,
+            normalize: true,
+        })
+    }
+
+    /// Whether to scale vectors to unit length. On by default, which makes
+    /// the dot product of two vectors their cosine similarity.
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+}
+
+impl Encoder {
+    /// Embeds `texts` in one forward pass, returning their vectors and
+    /// token count.
+    fn embed(&self, texts: Vec<String>, normalize: bool) -> Result<(Vec<Vec<f32>>, u64), EmbedderError> {
+        let error = |e: &dyn std::fmt::Display| EmbedderError::Embedder(e.to_string());
+        if texts.is_empty() {
+            return Ok((Vec::new(), 0));
+        }
+        let encodings = self.tokenizer.encode_batch(texts, true).map_err(|e| error(&e))?;
+        let length = encodings.iter().map(|encoding| encoding.get_ids().len()).max().unwrap_or(0);
+        let mut ids = Vec::with_capacity(encodings.len() * length);
+        let mut mask = Vec::with_capacity(encodings.len() * length);
+        for encoding in &encodings {
+            let tokens = encoding.get_ids();
+            ids.extend_from_slice(tokens);
+            ids.resize(ids.len() + length - tokens.len(), self.pad_id);
+            mask.extend(std::iter::repeat_n(1u32, tokens.len()));
+            mask.resize(mask.len() + length - tokens.len(), 0);
+        }
+        let tokens = mask.iter().map(|&m| u64::from(m)).sum();
+
+        let forward = || -> candle_core::Result<Vec<Vec<f32>>> {
+            let shape = (encodings.len(), length);
+            let ids = Tensor::from_vec(ids, shape, &Device::Cpu)?;
+            let mask = Tensor::from_vec(mask, shape, &Device::Cpu)?;
+            let hidden = self.model.forward(&ids, &ids.zeros_like()?, Some(&mask))?;
+            // Mean of the token vectors, leaving out the padding.
+            let mask = mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+            let pooled = hidden.broadcast_mul(&mask)?.sum(1)?.broadcast_div(&mask.sum(1)?)?;
+            let pooled = if normalize {
+                pooled.broadcast_div(&pooled.sqr()?.sum_keepdim(1)?.sqrt()?)?
+            } else {
+                pooled
+            };
+            pooled.to_vec2()
+        };
+        Ok((forward().map_err(|e| error(&e))?, tokens))
+    }
+}
+
+#[async_trait]
+impl Embedder for LocalEmbedder {
+    fn model_id(&self) -> String {
+        if self.normalize {
+            self.name.clone()
+        } else {
+            format!("{}:unnormalized", self.name)
+        }
+    }
+
+    /// The model's hidden size.
+    fn dimensions(&self) -> Option<usize> {
+        Some(self.dimensions)
+    }
+
+    fn estimate_tokens(&self, text: &str) -> u64 {
+        match self.encoder.tokenizer.encode(text, true) {
+            Ok(encoding) => encoding.get_ids().len() as u64,
+            Err(_) => (text.len() as u64).div_ceil(4).max(1),
+        }
+    }
+
+    /// Runs the model on the blocking thread pool, so that the runtime keeps
+    /// serving other tasks meanwhile.
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError> {
+        let encoder = self.encoder.clone();
+        let texts: Vec<String> = texts.iter().map(|text| text.to_string()).collect();
+        let normalize = self.normalize;
+        let (vectors, tokens) = tokio::task::spawn_blocking(move || encoder.embed(texts, normalize))
+            .await
+            .map_err(|e| EmbedderError::Embedder(e.to_string()))??;
+        Ok(Embeddings { vectors, usage: Some(Usage { prompt_tokens: tokens, total_tokens: tokens }) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::write_tiny_model;
+
+    fn norm(vector: &[f32]) -> f32 {
+        vector.iter().map(|value| value * value).sum::<f32>().sqrt()
+    }
+
+    fn close(a: &[f32], b: &[f32]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[tokio::test]
+    async fn test_embeds_mean_pooled_unit_vectors() {
+        let dir = write_tiny_model("embeds");
+        let embedder = LocalEmbedder::load(&dir).unwrap();
+        assert_eq!(embedder.dimensions(), Some(16));
+
+        let embeddings = embedder.embed(&["rust is fast", "the cat sat on the mat", "rust is fast"]).await.unwrap();
+        let vectors = &embeddings.vectors;
+        assert_eq!(vectors.len(), 3);
+        assert!(vectors.iter().all(|vector| vector.len() == 16 && (norm(vector) - 1.0).abs() < 1e-5));
+        assert!(close(&vectors[0], &vectors[2]));
+        assert!(!close(&vectors[0], &vectors[1]));
+        // Three and six words, plus [CLS] and [SEP] each.
+        assert_eq!(embeddings.usage, Some(Usage { prompt_tokens: 18, total_tokens: 18 }));
+        assert_eq!(embedder.estimate_tokens("rust is fast"), 5);
+
+        // Padding next to a longer text does not change the vector.
+        let alone = embedder.embed(&["rust is fast"]).await.unwrap();
+        assert!(close(&alone.vectors[0], &vectors[0]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_truncates_and_skips_normalization() {
+        let dir = write_tiny_model("truncates");
+        let embedder = LocalEmbedder::load(&dir).unwrap().with_normalize(false);
+        let long = "rust is fast ".repeat(20);
+        let embeddings = embedder.embed(&[long.as_str()]).await.unwrap();
+        assert_eq!(embeddings.usage.unwrap().prompt_tokens, 16);
+        assert!((norm(&embeddings.vectors[0]) - 1.0).abs() > 1e-3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_model_ids() {
+        let dir = write_tiny_model("ids");
+        let first = LocalEmbedder::load(&dir).unwrap();
+        assert_eq!(first.model_id(), LocalEmbedder::load(&dir).unwrap().model_id());
+        assert!(first.model_id().starts_with("local:embedder_model_ids_"));
+        assert_ne!(first.model_id(), first.clone().with_normalize(false).model_id());
+
+        // New random weights in the same directory.
+        write_tiny_model("ids");
+        assert_ne!(first.model_id(), LocalEmbedder::load(&dir).unwrap().model_id());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = write_tiny_model("errors");
+        std::fs::write(dir.join("model.safetensors"), b"not safetensors").unwrap();
+        let err = LocalEmbedder::load(&dir).err().unwrap();
+        assert!(matches!(&err, EmbedderError::Model(msg) if msg.contains("model.safetensors")), "{}", err);
+
+        std::fs::remove_file(dir.join("config.json")).unwrap();
+        let err = LocalEmbedder::load(&dir).err().unwrap();
+        assert!(matches!(&err, EmbedderError::Model(msg) if msg.contains("config.json")), "{}", err);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
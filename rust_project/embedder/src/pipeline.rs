@@ -0,0 +1,460 @@
+use crate::{EmbedderError, Embedder, EmbeddingCache, Embeddings, TokenBucket, Usage};
+use document::Document;
+use futures::stream::{self, StreamExt};
+use pyo3::prelude::*;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// How failed requests are retried: up to `max_retries` times, waiting
+/// `initial_backoff` before the first retry and twice as long before each
+/// next one, up to `max_backoff`. A rate limited request waits as long as
+/// the server asked instead, when that is longer, but never more than
+/// `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy { max_retries: 0, ..Default::default() }
+    }
+
+    /// The wait before retry number `attempt` (0 for the first retry).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// What a pipeline run did.
+///
+/// This struct is exposed to Python as the `EmbeddingReport` class.
+#[pyclass(name = "EmbeddingReport", get_all)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EmbeddingReport {
+    /// Documents embedded by the embedder.
+    pub embedded: usize,
+    /// Documents whose embedding came from the cache.
+    pub cached: usize,
+    /// Requests sent, retries included.
+    pub requests: usize,
+    pub retries: usize,
+    /// Tokens billed, as reported by the embedder.
+    pub prompt_tokens: u64,
+    pub total_tokens: u64,
+}
+
+#[pymethods]
+impl EmbeddingReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "EmbeddingReport(embedded={}, cached={}, requests={}, retries={}, prompt_tokens={}, total_tokens={})",
+            self.embedded, self.cached, self.requests, self.retries, self.prompt_tokens, self.total_tokens
+        )
+    }
+}
+
+/// Request counters shared by the concurrent batches of a run.
+#[derive(Default)]
+struct Counters {
+    requests: AtomicUsize,
+    retries: AtomicUsize,
+}
+
+/// Embeds documents in batches with an [`Embedder`].
+///
+/// Documents with the same content are embedded once. Batches are sent
+/// `concurrency` at a time, each request waiting for the request and token
+/// rate limits, and retried with exponential backoff when it fails with a
+/// retryable error. With a cache, embeddings are looked up before
+/// requesting them and stored as they arrive, so an interrupted run resumes
+/// where it stopped.
+pub struct EmbeddingPipeline<E> {
+    embedder: E,
+    batch_size: usize,
+    concurrency: usize,
+    retry: RetryPolicy,
+    request_limit: Option<TokenBucket>,
+    token_limit: Option<TokenBucket>,
+    cache: Option<EmbeddingCache>,
+}
+
+impl<E: Embedder> EmbeddingPipeline<E> {
+    /// A pipeline sending batches of 100 texts, 4 at a time, with the default
+    /// retry policy, no rate limits and no cache.
+    pub fn new(embedder: E) -> Self {
+        EmbeddingPipeline {
+            embedder,
+            batch_size: 100,
+            concurrency: 4,
+            retry: RetryPolicy::default(),
+            request_limit: None,
+            token_limit: None,
+            cache: None,
+        }
+    }
+
+    /// Sets the number of texts per request (at least one).
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the number of requests in flight at once (at least one).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Limits the requests sent per minute, retries included.
+    pub fn with_requests_per_minute(mut self, limit: Option<u32>) -> Self {
+        self.request_limit = limit.map(TokenBucket::per_minute);
+        self
+    }
+
+    /// Limits the tokens sent per minute, as estimated by the embedder.
+    pub fn with_tokens_per_minute(mut self, limit: Option<u32>) -> Self {
+        self.token_limit = limit.map(TokenBucket::per_minute);
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Option<EmbeddingCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn embedder(&self) -> &E {
+        &self.embedder
+    }
+
+    /// Sets the `embedding` of every document, and `prompt_tokens` and
+    /// `total_tokens` in the `usage` of those the embedder was called for.
+    ///
+    /// When a request reports usage for several texts, it is split between
+    /// them by their estimated tokens. On error, the documents of batches
+    /// that completed keep their embeddings.
+    pub async fn embed_documents(&self, documents: &mut [Document]) -> Result<EmbeddingReport, EmbedderError> {
+        let model_id = self.embedder.model_id();
+        let mut report = EmbeddingReport::default();
+
+        // The distinct texts left to embed, with their cache key and the documents holding them.
+        let mut pending: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for (index, document) in documents.iter_mut().enumerate() {
+            let key = EmbeddingCache::key(&model_id, &document.content);
+            if let Some(vector) = self.cached(&key).await {
+                document.embedding = Some(vector);
+                report.cached += 1;
+                continue;
+            }
+            match positions.get(&key) {
+                Some(&position) => pending[position].1.push(index),
+                None => {
+                    positions.insert(key.clone(), pending.len());
+                    pending.push((key, vec![index]));
+                }
+            }
+        }
+
+        let texts: Vec<String> = pending.iter().map(|(_, indices)| documents[indices[0]].content.clone()).collect();
+        let counters = Counters::default();
+        let mut batches = stream::iter(texts.chunks(self.batch_size).enumerate())
+            .map(|(number, batch)| {
+                let counters = &counters;
+                async move { (number * self.batch_size, self.embed_batch(batch, counters).await) }
+            })
+            .buffer_unordered(self.concurrency);
+
+        let mut result = Ok(());
+        while let Some((start, embeddings)) = batches.next().await {
+            let embeddings = match embeddings {
+                Ok(embeddings) => embeddings,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            };
+            let batch = &texts[start..start + embeddings.vectors.len()];
+            let usages = embeddings.usage.map(|usage| self.split_usage(usage, batch));
+            if let Some(usage) = embeddings.usage {
+                report.prompt_tokens += usage.prompt_tokens;
+                report.total_tokens += usage.total_tokens;
+            }
+            for (offset, vector) in embeddings.vectors.into_iter().enumerate() {
+                let (key, indices) = &pending[start + offset];
+                if let Some(cache) = &self.cache {
+                    cache.put(key, &vector).await?;
+                }
+                for &index in indices {
+                    let document = &mut documents[index];
+                    if let Some(usages) = &usages {
+                        record_usage(document, usages[offset]);
+                    }
+                    document.embedding = Some(vector.clone());
+                }
+                report.embedded += indices.len();
+            }
+        }
+        drop(batches);
+        report.requests = counters.requests.into_inner();
+        report.retries = counters.retries.into_inner();
+        result.map(|_| report)
+    }
+
+    /// The cached vector under `key`, if it has the embedder's dimensions.
+    async fn cached(&self, key: &str) -> Option<Vec<f32>> {
+        let vector = self.cache.as_ref()?.get(key).await?;
+        match self.embedder.dimensions() {
+            Some(dimensions) if dimensions != vector.len() => None,
+            _ => Some(vector),
+        }
+    }
+
+    /// Sends one batch, waiting for the rate limits and retrying.
+    async fn embed_batch(&self, batch: &[String], counters: &Counters) -> Result<Embeddings, EmbedderError> {
+        let texts: Vec<&str> = batch.iter().map(String::as_str).collect();
+        let tokens: u64 = texts.iter().map(|text| self.embedder.estimate_tokens(text)).sum();
+        let mut attempt = 0;
+        loop {
+            if let Some(limit) = &self.request_limit {
+                limit.acquire(1.0).await;
+            }
+            if let Some(limit) = &self.token_limit {
+                limit.acquire(tokens as f64).await;
+            }
+            counters.requests.fetch_add(1, Ordering::Relaxed);
+            match self.embedder.embed(&texts).await {
+                Ok(embeddings) => return self.check(embeddings, texts.len()),
+                Err(err) if err.is_retryable() && attempt < self.retry.max_retries => {
+                    let backoff = self.retry.backoff(attempt);
+                    let delay = match err {
+                        EmbedderError::RateLimited { retry_after: Some(retry_after) } => {
+                            retry_after.min(self.retry.max_backoff).max(backoff)
+                        }
+                        _ => backoff,
+                    };
+                    counters.retries.fetch_add(1, Ordering::Relaxed);
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Checks that a response has one vector per text, of the expected length.
+    fn check(&self, embeddings: Embeddings, texts: usize) -> Result<Embeddings, EmbedderError> {
+        if embeddings.vectors.len() != texts {
+            return Err(EmbedderError::InvalidResponse(format!(
+                "expected {} embeddings, got {}",
+                texts,
+                embeddings.vectors.len()
+            )));
+        }
+        if let Some(dimensions) = self.embedder.dimensions() {
+            if let Some(vector) = embeddings.vectors.iter().find(|vector| vector.len() != dimensions) {
+                return Err(EmbedderError::InvalidResponse(format!(
+                    "expected {} dimensions, got {}",
+                    dimensions,
+                    vector.len()
+                )));
+            }
+        }
+        Ok(embeddings)
+    }
+
+    /// Splits the usage of a request between its texts, in proportion to
+    /// their estimated tokens. The shares add up to the reported totals.
+    fn split_usage(&self, usage: Usage, texts: &[String]) -> Vec<Usage> {
+        let estimates: Vec<u64> = texts.iter().map(|text| self.embedder.estimate_tokens(text)).collect();
+        let prompt = split(usage.prompt_tokens, &estimates);
+        let total = split(usage.total_tokens, &estimates);
+        prompt
+            .into_iter()
+            .zip(total)
+            .map(|(prompt_tokens, total_tokens)| Usage { prompt_tokens, total_tokens })
+            .collect()
+    }
+}
+
+/// Splits `amount` in proportion to `weights`, rounding the running total so
+/// the parts add up to `amount`.
+fn split(amount: u64, weights: &[u64]) -> Vec<u64> {
+    let sum: u128 = weights.iter().map(|&weight| u128::from(weight)).sum::<u128>().max(1);
+    let mut cumulative = 0u128;
+    let mut previous = 0u64;
+    weights
+        .iter()
+        .map(|&weight| {
+            cumulative += u128::from(weight);
+            let rounded = ((u128::from(amount) * cumulative + sum / 2) / sum) as u64;
+            let part = rounded - previous;
+            previous = rounded;
+            part
+        })
+        .collect()
+}
+
+/// Stores the tokens a document was embedded with in its `usage`, keeping
+/// any other keys.
+fn record_usage(document: &mut Document, usage: Usage) {
+    let entries = document.usage.get_or_insert_with(HashMap::new);
+    entries.insert("prompt_tokens".to_string(), JsonValue::from(usage.prompt_tokens));
+    entries.insert("total_tokens".to_string(), JsonValue::from(usage.total_tokens));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{documents, MockEmbedder};
+    use tokio::time::Instant;
+
+    #[tokio::test]
+    async fn test_batches_deduplicates_and_records_usage() {
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new()).with_batch_size(2);
+        let mut docs = documents(&["alpha", "beta", "alpha", "gamma delta", "epsilon"]);
+        docs[1].usage = Some(HashMap::from([("model".to_string(), serde_json::json!("mock"))]));
+
+        let report = pipeline.embed_documents(&mut docs).await.unwrap();
+        assert_eq!(report.embedded, 5);
+        assert_eq!((report.requests, report.retries, report.cached), (2, 0, 0));
+        let mut batches = pipeline.embedder().batches();
+        batches.sort();
+        assert_eq!(batches, vec![vec!["alpha", "beta"], vec!["gamma delta", "epsilon"]]);
+
+        for document in &docs {
+            assert_eq!(document.embedding, Some(MockEmbedder::vector(&document.content)));
+        }
+        assert_eq!(docs[0].usage, docs[2].usage);
+        assert_eq!(docs[1].usage.as_ref().unwrap()["model"], serde_json::json!("mock"));
+        let prompt_tokens: u64 = [0, 1, 3, 4].iter().map(|&i| docs[i].usage.as_ref().unwrap()["prompt_tokens"].as_u64().unwrap()).sum();
+        assert_eq!(prompt_tokens, report.prompt_tokens);
+    }
+
+    #[tokio::test]
+    async fn test_runs_batches_concurrently() {
+        let contents: Vec<String> = (0..12).map(|i| format!("text {}", i)).collect();
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new()).with_batch_size(2).with_concurrency(3);
+        let mut docs = documents(&contents);
+        let report = pipeline.embed_documents(&mut docs).await.unwrap();
+        assert_eq!(report.requests, 6);
+        assert_eq!(pipeline.embedder().max_in_flight(), 3);
+        assert!(docs.iter().all(|document| document.embedding.is_some()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_with_backoff() {
+        let embedder = MockEmbedder::new().failing_with(vec![
+            EmbedderError::Server { status: 503, message: "busy".to_string() },
+            EmbedderError::RateLimited { retry_after: Some(Duration::from_secs(5)) },
+        ]);
+        let retry = RetryPolicy { max_retries: 2, initial_backoff: Duration::from_secs(1), max_backoff: Duration::from_secs(60) };
+        let pipeline = EmbeddingPipeline::new(embedder).with_retry(retry);
+        let mut docs = documents(&["one"]);
+        let start = Instant::now();
+        let report = pipeline.embed_documents(&mut docs).await.unwrap();
+        assert_eq!((report.requests, report.retries), (3, 2));
+        // 1s of backoff, then the 5s the server asked for.
+        assert_eq!(start.elapsed().as_secs(), 6);
+
+        let embedder = MockEmbedder::new().failing_with(vec![EmbedderError::Server { status: 400, message: "bad".to_string() }]);
+        let pipeline = EmbeddingPipeline::new(embedder).with_retry(retry);
+        let err = pipeline.embed_documents(&mut documents(&["one"])).await.unwrap_err();
+        assert_eq!(err, EmbedderError::Server { status: 400, message: "bad".to_string() });
+        assert_eq!(pipeline.embedder().batches().len(), 1);
+
+        assert_eq!(retry.backoff(0), Duration::from_secs(1));
+        assert_eq!(retry.backoff(3), Duration::from_secs(8));
+        assert_eq!(retry.backoff(40), Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_after_is_capped_at_max_backoff() {
+        let embedder = MockEmbedder::new().failing_with(vec![EmbedderError::RateLimited { retry_after: Some(Duration::MAX) }]);
+        let retry = RetryPolicy { max_retries: 1, initial_backoff: Duration::from_secs(1), max_backoff: Duration::from_secs(60) };
+        let pipeline = EmbeddingPipeline::new(embedder).with_retry(retry);
+        let start = Instant::now();
+        pipeline.embed_documents(&mut documents(&["one"])).await.unwrap();
+        assert_eq!(start.elapsed().as_secs(), 60);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limits_requests_and_tokens() {
+        let contents: Vec<String> = (0..5).map(|i| format!("doc {}", i)).collect();
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new())
+            .with_batch_size(1)
+            .with_requests_per_minute(Some(2));
+        let start = Instant::now();
+        pipeline.embed_documents(&mut documents(&contents)).await.unwrap();
+        // Two at once, then one every 30 seconds.
+        assert_eq!(start.elapsed().as_secs(), 90);
+
+        // "doc N" is estimated at 2 tokens: two requests fit in the first minute.
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new())
+            .with_batch_size(1)
+            .with_tokens_per_minute(Some(4));
+        let start = Instant::now();
+        pipeline.embed_documents(&mut documents(&contents[..3])).await.unwrap();
+        assert_eq!(start.elapsed().as_secs(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_cache_skips_embedded_texts() {
+        let dir = std::env::temp_dir().join(format!("embedder_pipeline_{}", std::process::id()));
+        let cache = EmbeddingCache::open(&dir).unwrap();
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new()).with_cache(Some(cache.clone()));
+        let mut docs = documents(&["cached", "also cached"]);
+        pipeline.embed_documents(&mut docs).await.unwrap();
+
+        let mut docs = documents(&["cached", "new", "also cached"]);
+        let report = pipeline.embed_documents(&mut docs).await.unwrap();
+        assert_eq!((report.cached, report.embedded), (2, 1));
+        assert_eq!(pipeline.embedder().batches().last().unwrap(), &vec!["new".to_string()]);
+        assert_eq!(docs[0].embedding, Some(MockEmbedder::vector("cached")));
+        assert_eq!(docs[0].usage, None);
+
+        // Another model does not reuse the vectors.
+        let other = EmbeddingPipeline::new(MockEmbedder::new().with_model_id("other")).with_cache(Some(cache));
+        assert_eq!(other.embed_documents(&mut docs).await.unwrap().cached, 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_malformed_responses() {
+        let pipeline = EmbeddingPipeline::new(MockEmbedder::new().with_dimensions(3));
+        let err = pipeline.embed_documents(&mut documents(&["x"])).await.unwrap_err();
+        assert_eq!(err, EmbedderError::InvalidResponse("expected 3 dimensions, got 2".to_string()));
+    }
+
+    #[test]
+    fn test_split_adds_up() {
+        assert_eq!(split(10, &[1, 1, 1]), vec![3, 4, 3]);
+        assert_eq!(split(7, &[5]), vec![7]);
+        assert_eq!(split(0, &[2, 3]), vec![0, 0]);
+        assert_eq!(split(100, &[1, 3]).iter().sum::<u64>(), 100);
+    }
+}
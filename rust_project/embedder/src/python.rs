@@ -0,0 +1,427 @@
+use crate::{
+    Embedder, EmbedderError, EmbeddingCache, EmbeddingPipeline, EmbeddingReport, Embeddings, HttpEmbedder, LocalEmbedder,
+    RetryPolicy, Usage,
+};
+use async_trait::async_trait;
+use document::py_json::py_dict_to_json;
+use document::Document;
+use pyo3::exceptions::{PyConnectionError, PyIOError, PyRuntimeError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+impl From<EmbedderError> for PyErr {
+    fn from(err: EmbedderError) -> PyErr {
+        match err {
+            EmbedderError::Request(_) => PyConnectionError::new_err(err.to_string()),
+            EmbedderError::InvalidResponse(_) => PyValueError::new_err(err.to_string()),
+            EmbedderError::Cache(_) | EmbedderError::Model(_) => PyIOError::new_err(err.to_string()),
+            _ => PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// An `Embedder` backed by a Python object with the methods of Python's
+/// `agno.embedder.base.Embedder`.
+///
+/// Texts are embedded one call at a time with `get_embedding_and_usage`, or
+/// `get_embedding` if the object has no usage method. `ConnectionError` and
+/// `TimeoutError` are retried, other exceptions are not. The model id is the
+/// object's `id` attribute, or its class name.
+#[derive(Debug)]
+pub struct PyEmbedder {
+    object: Py<PyAny>,
+    model_id: String,
+    dimensions: Option<usize>,
+}
+
+impl PyEmbedder {
+    pub fn new(object: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let model_id = match object.getattr("id").and_then(|id| id.extract::<String>()) {
+            Ok(id) => id,
+            Err(_) => object.get_type().qualname()?,
+        };
+        let dimensions = object
+            .getattr("dimensions")
+            .ok()
+            .and_then(|dimensions| dimensions.extract::<Option<usize>>().ok())
+            .flatten();
+        Ok(PyEmbedder { object: object.clone().unbind(), model_id, dimensions })
+    }
+
+    /// The wrapped Python object.
+    pub fn object(&self) -> &Py<PyAny> {
+        &self.object
+    }
+
+    fn embed_one(&self, py: Python<'_>, text: &str) -> PyResult<(Vec<f32>, Option<Usage>)> {
+        let object = self.object.bind(py);
+        if !object.hasattr("get_embedding_and_usage")? {
+            return Ok((object.call_method1("get_embedding", (text,))?.extract()?, None));
+        }
+        let (vector, usage): (Vec<f32>, Option<Bound<'_, PyDict>>) =
+            object.call_method1("get_embedding_and_usage", (text,))?.extract()?;
+        let usage = usage.map(|usage| py_dict_to_json(&usage)).transpose()?.map(|usage| {
+            let tokens = |key: &str| usage.get(key).and_then(|value| value.as_u64()).unwrap_or(0);
+            Usage { prompt_tokens: tokens("prompt_tokens"), total_tokens: tokens("total_tokens") }
+        });
+        Ok((vector, usage))
+    }
+}
+
+#[async_trait]
+impl Embedder for PyEmbedder {
+    fn model_id(&self) -> String {
+        self.model_id.clone()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError> {
+        Python::with_gil(|py| {
+            let mut embeddings = Embeddings::default();
+            for text in texts {
+                let (vector, usage) = self.embed_one(py, text).map_err(|err| {
+                    if err.is_instance_of::<PyConnectionError>(py) || err.is_instance_of::<PyTimeoutError>(py) {
+                        EmbedderError::Request(err.to_string())
+                    } else {
+                        EmbedderError::Embedder(err.to_string())
+                    }
+                })?;
+                embeddings.vectors.push(vector);
+                if let Some(usage) = usage {
+                    let total = embeddings.usage.get_or_insert_with(Usage::default);
+                    total.prompt_tokens += usage.prompt_tokens;
+                    total.total_tokens += usage.total_tokens;
+                }
+            }
+            Ok(embeddings)
+        })
+    }
+}
+
+/// Wraps a Python embedder object for the pipeline. Embedders implemented
+/// in Rust are used directly, without going through Python.
+pub fn embedder_from_py(object: &Bound<'_, PyAny>) -> PyResult<Arc<dyn Embedder>> {
+    if let Ok(embedder) = object.downcast::<PyHttpEmbedder>() {
+        return Ok(embedder.borrow().inner.clone());
+    }
+    if let Ok(embedder) = object.downcast::<PyLocalEmbedder>() {
+        return Ok(embedder.borrow().inner.clone());
+    }
+    Ok(Arc::new(PyEmbedder::new(object)?))
+}
+
+/// The runtime the Python classes block on, built on first use and shared
+/// by every call so each one does not start its own.
+fn blocking_runtime() -> PyResult<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("embedder-python")
+        .enable_all()
+        .build()
+        .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Runs the future made by `future` to completion on the shared runtime,
+/// releasing the GIL meanwhile.
+fn block_on<F, Fut>(py: Python<'_>, future: F) -> PyResult<Fut::Output>
+where
+    F: FnOnce() -> Fut + Send,
+    Fut: Future,
+    Fut::Output: Send,
+{
+    let runtime = blocking_runtime()?;
+    Ok(py.allow_threads(|| runtime.block_on(future())))
+}
+
+/// Embeds one text with a Rust embedder, for the `get_embedding_and_usage`
+/// methods of the embedder classes.
+fn embed_one(py: Python<'_>, embedder: &dyn Embedder, text: &str) -> PyResult<(Vec<f32>, Option<Usage>)> {
+    let texts = [text];
+    let embeddings = block_on(py, || embedder.embed(&texts))??;
+    let vector = embeddings
+        .vectors
+        .into_iter()
+        .next()
+        .ok_or_else(|| EmbedderError::InvalidResponse("no embedding returned".to_string()))?;
+    Ok((vector, embeddings.usage))
+}
+
+/// An embedder for servers speaking the OpenAI `/embeddings` protocol.
+///
+/// This struct is exposed to Python as the `HttpEmbedder` class.
+#[pyclass(name = "HttpEmbedder")]
+pub struct PyHttpEmbedder {
+    inner: Arc<HttpEmbedder>,
+}
+
+#[pymethods]
+impl PyHttpEmbedder {
+    /// Creates a new `HttpEmbedder` instance.
+    ///
+    /// Args:
+    ///     id (str): The model name, e.g. "text-embedding-3-small".
+    ///     base_url (Optional[str]): The URL `/embeddings` is appended to. Defaults to the OpenAI API.
+    ///     api_key (Optional[str]): The API key. Defaults to None, for servers without authentication.
+    ///     dimensions (Optional[int]): Length of the vectors to ask for. Defaults to None.
+    ///     auth_header (str): The header carrying the API key, as a bearer token for
+    ///         "Authorization" and as is otherwise. Defaults to "Authorization".
+    ///     timeout (float): Seconds a request may take. Defaults to 60.
+    ///
+    /// Raises:
+    ///     PyValueError: If the timeout is not a positive number.
+    #[new]
+    #[pyo3(signature = (id, base_url=None, api_key=None, dimensions=None, auth_header="Authorization", timeout=60.0))]
+    fn py_new(
+        id: &str,
+        base_url: Option<&str>,
+        api_key: Option<String>,
+        dimensions: Option<usize>,
+        auth_header: &str,
+        timeout: f64,
+    ) -> PyResult<Self> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .ok()
+            .filter(|timeout| !timeout.is_zero())
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid timeout: {}", timeout)))?;
+        let mut inner = HttpEmbedder::new(id)
+            .with_api_key(api_key)
+            .with_dimensions(dimensions)
+            .with_auth_header(auth_header)
+            .with_timeout(timeout);
+        if let Some(base_url) = base_url {
+            inner = inner.with_base_url(base_url);
+        }
+        Ok(PyHttpEmbedder { inner: Arc::new(inner) })
+    }
+
+    #[getter]
+    fn id(&self) -> &str {
+        self.inner.model()
+    }
+
+    #[getter]
+    fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    #[getter]
+    fn dimensions(&self) -> Option<usize> {
+        self.inner.dimensions()
+    }
+
+    /// Embeds one text.
+    ///
+    /// Args:
+    ///     text (str): The text to embed.
+    ///
+    /// Returns:
+    ///     List[float]: The embedding.
+    ///
+    /// Raises:
+    ///     PyConnectionError: If the server cannot be reached or times out.
+    ///     PyValueError: If the response is malformed.
+    ///     PyRuntimeError: If the server answers with an error.
+    fn get_embedding(&self, py: Python<'_>, text: &str) -> PyResult<Vec<f32>> {
+        Ok(self.get_embedding_and_usage(py, text)?.0)
+    }
+
+    /// Embeds one text and reports the tokens it was billed for.
+    ///
+    /// Args:
+    ///     text (str): The text to embed.
+    ///
+    /// Returns:
+    ///     Tuple[List[float], Optional[Dict[str, int]]]: The embedding, and `prompt_tokens` and
+    ///         `total_tokens` if the server reports them.
+    ///
+    /// Raises:
+    ///     PyConnectionError: If the server cannot be reached or times out.
+    ///     PyValueError: If the response is malformed.
+    ///     PyRuntimeError: If the server answers with an error.
+    fn get_embedding_and_usage(&self, py: Python<'_>, text: &str) -> PyResult<(Vec<f32>, Option<Usage>)> {
+        embed_one(py, self.inner.as_ref(), text)
+    }
+}
+
+/// A BERT-style sentence encoder running on the CPU.
+///
+/// This struct is exposed to Python as the `LocalEmbedder` class.
+#[pyclass(name = "LocalEmbedder")]
+pub struct PyLocalEmbedder {
+    inner: Arc<LocalEmbedder>,
+}
+
+#[pymethods]
+impl PyLocalEmbedder {
+    /// Loads a model from a directory, releasing the GIL meanwhile.
+    ///
+    /// Args:
+    ///     path (Union[str, os.PathLike]): Directory with the `config.json`, `tokenizer.json` and
+    ///         `model.safetensors` files of a Hugging Face model, e.g. `all-MiniLM-L6-v2`.
+    ///     normalize (bool): Whether to scale vectors to unit length. Defaults to True.
+    ///
+    /// Raises:
+    ///     PyIOError: If the model files are missing or invalid.
+    #[new]
+    #[pyo3(signature = (path, normalize=true))]
+    fn py_new(py: Python<'_>, path: PathBuf, normalize: bool) -> PyResult<Self> {
+        let inner = py.allow_threads(|| LocalEmbedder::load(&path))?.with_normalize(normalize);
+        Ok(PyLocalEmbedder { inner: Arc::new(inner) })
+    }
+
+    /// Identifies the model by its directory name and a hash of its weights.
+    #[getter]
+    fn id(&self) -> String {
+        self.inner.model_id()
+    }
+
+    #[getter]
+    fn dimensions(&self) -> Option<usize> {
+        self.inner.dimensions()
+    }
+
+    /// Embeds one text.
+    ///
+    /// Args:
+    ///     text (str): The text to embed.
+    ///
+    /// Returns:
+    ///     List[float]: The embedding.
+    ///
+    /// Raises:
+    ///     PyRuntimeError: If the text cannot be tokenized or the model fails.
+    fn get_embedding(&self, py: Python<'_>, text: &str) -> PyResult<Vec<f32>> {
+        Ok(self.get_embedding_and_usage(py, text)?.0)
+    }
+
+    /// Embeds one text and counts its tokens.
+    ///
+    /// Args:
+    ///     text (str): The text to embed.
+    ///
+    /// Returns:
+    ///     Tuple[List[float], Optional[Dict[str, int]]]: The embedding, and the tokens of the text as
+    ///         `prompt_tokens` and `total_tokens`.
+    ///
+    /// Raises:
+    ///     PyRuntimeError: If the text cannot be tokenized or the model fails.
+    fn get_embedding_and_usage(&self, py: Python<'_>, text: &str) -> PyResult<(Vec<f32>, Option<Usage>)> {
+        embed_one(py, self.inner.as_ref(), text)
+    }
+}
+
+impl IntoPy<PyObject> for Usage {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let dict = PyDict::new_bound(py);
+        let _ = dict.set_item("prompt_tokens", self.prompt_tokens);
+        let _ = dict.set_item("total_tokens", self.total_tokens);
+        dict.into_py(py)
+    }
+}
+
+/// Embeds documents in batches, with concurrency, rate limits, retries and
+/// an on-disk cache.
+///
+/// This struct is exposed to Python as the `EmbeddingPipeline` class.
+#[pyclass(name = "EmbeddingPipeline")]
+pub struct PyEmbeddingPipeline {
+    inner: EmbeddingPipeline<Arc<dyn Embedder>>,
+    last_report: Option<EmbeddingReport>,
+}
+
+#[pymethods]
+impl PyEmbeddingPipeline {
+    /// Creates a new `EmbeddingPipeline` instance.
+    ///
+    /// Args:
+    ///     embedder (Embedder): An `HttpEmbedder` or `LocalEmbedder`, or any object with the methods
+    ///         of `agno.embedder.base.Embedder`.
+    ///     batch_size (int): Number of texts per request. Defaults to 100.
+    ///     concurrency (int): Number of requests in flight at once. Defaults to 4.
+    ///     requests_per_minute (Optional[int]): Request rate limit, retries included. Defaults to None.
+    ///     tokens_per_minute (Optional[int]): Token rate limit, by estimated tokens. Defaults to None.
+    ///     max_retries (int): Retries of a failed request, with exponential backoff. Defaults to 3.
+    ///     cache_dir (Optional[Union[str, os.PathLike]]): Directory caching embeddings by model and
+    ///         content. Defaults to None.
+    ///
+    /// Raises:
+    ///     PyIOError: If the cache directory cannot be created.
+    #[new]
+    #[pyo3(signature = (
+        embedder,
+        batch_size=100,
+        concurrency=4,
+        requests_per_minute=None,
+        tokens_per_minute=None,
+        max_retries=3,
+        cache_dir=None
+    ))]
+    fn py_new(
+        embedder: &Bound<'_, PyAny>,
+        batch_size: usize,
+        concurrency: usize,
+        requests_per_minute: Option<u32>,
+        tokens_per_minute: Option<u32>,
+        max_retries: u32,
+        cache_dir: Option<PathBuf>,
+    ) -> PyResult<Self> {
+        let cache = cache_dir.map(EmbeddingCache::open).transpose()?;
+        let inner = EmbeddingPipeline::new(embedder_from_py(embedder)?)
+            .with_batch_size(batch_size)
+            .with_concurrency(concurrency)
+            .with_requests_per_minute(requests_per_minute)
+            .with_tokens_per_minute(tokens_per_minute)
+            .with_retry(RetryPolicy { max_retries, ..Default::default() })
+            .with_cache(cache);
+        Ok(PyEmbeddingPipeline { inner, last_report: None })
+    }
+
+    /// Embeds documents, releasing the GIL while waiting on requests.
+    ///
+    /// Args:
+    ///     documents (List[Document]): The documents to embed.
+    ///
+    /// Returns:
+    ///     List[Document]: Copies of the documents with `embedding` set, and `prompt_tokens` and
+    ///         `total_tokens` in `usage` for the ones sent to the embedder.
+    ///
+    /// Raises:
+    ///     PyConnectionError: If a request fails after its retries.
+    ///     PyValueError: If the embedder returns malformed embeddings.
+    ///     PyIOError: If the cache cannot be written.
+    ///     PyRuntimeError: If the embedder fails otherwise.
+    fn embed_documents(&mut self, py: Python<'_>, mut documents: Vec<Document>) -> PyResult<Vec<Document>> {
+        let inner = &self.inner;
+        let report = block_on(py, || inner.embed_documents(&mut documents))??;
+        self.last_report = Some(report);
+        Ok(documents)
+    }
+
+    /// What the last `embed_documents` call did, or None before the first one.
+    #[getter]
+    fn last_report(&self) -> Option<EmbeddingReport> {
+        self.last_report
+    }
+}
+
+/// Adds the classes of the `embedder` module to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyEmbeddingPipeline>()?;
+    m.add_class::<PyHttpEmbedder>()?;
+    m.add_class::<PyLocalEmbedder>()?;
+    m.add_class::<EmbeddingReport>()?;
+    Ok(())
+}
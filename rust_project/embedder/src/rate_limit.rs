@@ -0,0 +1,110 @@
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// A token bucket: holds up to `capacity` tokens and regains
+/// `refill_per_second` of them every second. Taking tokens that are not
+/// there waits until they are.
+///
+/// Requests are served in the order they ask, so a large request is not
+/// starved by a stream of small ones.
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_second: f64,
+    /// Tokens left at the given instant; negative while requests wait.
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    /// A full bucket of `capacity` tokens, refilled at `refill_per_second`.
+    /// A bucket with no refill rate never regains tokens, so once it is
+    /// empty, takes wait forever.
+    pub fn new(capacity: f64, refill_per_second: f64) -> Self {
+        TokenBucket {
+            capacity: capacity.max(f64::MIN_POSITIVE),
+            refill_per_second: refill_per_second.max(f64::MIN_POSITIVE),
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    /// A bucket allowing `limit` tokens per minute, up to `limit` at once.
+    pub fn per_minute(limit: u32) -> Self {
+        let limit = f64::from(limit.max(1));
+        TokenBucket::new(limit, limit / 60.0)
+    }
+
+    /// How many tokens the bucket holds when full.
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /// Takes `cost` tokens, waiting until the bucket has them. A cost above
+    /// the capacity is taken as the capacity, so it does not wait forever.
+    pub async fn acquire(&self, cost: f64) {
+        let wait = {
+            let mut state = self.state.lock().await;
+            let now = Instant::now();
+            let (tokens, since) = *state;
+            let refilled = (tokens + now.duration_since(since).as_secs_f64() * self.refill_per_second).min(self.capacity);
+            // Reserve the tokens now, so later callers queue behind this one.
+            let left = refilled - cost.min(self.capacity);
+            *state = (left, now);
+            if left >= 0.0 {
+                Duration::ZERO
+            } else {
+                // Waits too long for a Duration, e.g. with no refill rate, never end.
+                Duration::try_from_secs_f64(-left / self.refill_per_second).unwrap_or(Duration::MAX)
+            }
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_bursts_up_to_capacity_then_waits() {
+        let bucket = TokenBucket::new(2.0, 1.0);
+        let start = Instant::now();
+        bucket.acquire(1.0).await;
+        bucket.acquire(1.0).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        bucket.acquire(1.0).await;
+        assert_eq!(start.elapsed().as_secs_f64().round(), 1.0);
+        bucket.acquire(2.0).await;
+        assert_eq!(start.elapsed().as_secs_f64().round(), 3.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_concurrent_callers_share_the_rate() {
+        let bucket = std::sync::Arc::new(TokenBucket::per_minute(60));
+        let start = Instant::now();
+        let waiters: Vec<_> = (0..70)
+            .map(|_| {
+                let bucket = bucket.clone();
+                tokio::spawn(async move { bucket.acquire(1.0).await })
+            })
+            .collect();
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        // 60 at once, then one per second.
+        assert_eq!(start.elapsed().as_secs_f64().round(), 10.0);
+        // Oversized costs are clamped to the capacity.
+        bucket.acquire(1000.0).await;
+        assert_eq!(start.elapsed().as_secs_f64().round(), 70.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_zero_refill_rate_waits_forever() {
+        let bucket = TokenBucket::new(1.0, 0.0);
+        bucket.acquire(1.0).await;
+        let waited = tokio::time::timeout(Duration::from_secs(3600), bucket.acquire(1.0)).await;
+        assert!(waited.is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! A mock `Embedder` for the pipeline tests, a stub embeddings server for
+//! the HTTP embedder tests, and a tiny random BERT model for the local ones.
+
+use crate::{Embedder, EmbedderError, Embeddings, Usage};
+use async_trait::async_trait;
+use serde_json::{json, Value as JsonValue};
+use candle_core::{DType, Device};
+use candle_nn::{VarBuilder, VarMap};
+use candle_transformers::models::bert::{BertModel, Config};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use test_support::StubRequest;
+
+pub use test_support::{closed_url, StubResponse, StubServer};
+
+test_support::document_fixtures!(document::Document);
+
+/// Embeds each text as `[length, byte sum]` after a short delay, failing
+/// with the queued errors first. Records every batch it receives, and how
+/// many requests were in flight at most.
+#[derive(Debug)]
+pub struct MockEmbedder {
+    model_id: String,
+    dimensions: Option<usize>,
+    failures: Mutex<Vec<EmbedderError>>,
+    batches: Mutex<Vec<Vec<String>>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+impl MockEmbedder {
+    pub fn new() -> Self {
+        MockEmbedder {
+            model_id: "mock".to_string(),
+            dimensions: None,
+            failures: Mutex::new(Vec::new()),
+            batches: Mutex::new(Vec::new()),
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_model_id(mut self, model_id: &str) -> Self {
+        self.model_id = model_id.to_string();
+        self
+    }
+
+    /// Claims `dimensions`, whatever the vectors are.
+    pub fn with_dimensions(mut self, dimensions: usize) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Fails the next requests with `errors`, in order.
+    pub fn failing_with(self, errors: Vec<EmbedderError>) -> Self {
+        *self.failures.lock().unwrap() = errors;
+        self
+    }
+
+    pub fn vector(text: &str) -> Vec<f32> {
+        vec![text.len() as f32, text.bytes().map(u32::from).sum::<u32>() as f32]
+    }
+
+    pub fn batches(&self) -> Vec<Vec<String>> {
+        self.batches.lock().unwrap().clone()
+    }
+
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Embedder for MockEmbedder {
+    fn model_id(&self) -> String {
+        self.model_id.clone()
+    }
+
+    fn dimensions(&self) -> Option<usize> {
+        self.dimensions
+    }
+
+    async fn embed(&self, texts: &[&str]) -> Result<Embeddings, EmbedderError> {
+        self.batches.lock().unwrap().push(texts.iter().map(|text| text.to_string()).collect());
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        let failure = {
+            let mut failures = self.failures.lock().unwrap();
+            (!failures.is_empty()).then(|| failures.remove(0))
+        };
+        if let Some(err) = failure {
+            return Err(err);
+        }
+        let tokens: u64 = texts.iter().map(|text| self.estimate_tokens(text)).sum();
+        Ok(Embeddings {
+            vectors: texts.iter().map(|text| MockEmbedder::vector(text)).collect(),
+            usage: Some(Usage { prompt_tokens: tokens, total_tokens: tokens }),
+        })
+    }
+}
+
+/// An OpenAI-style embeddings response embedding each input as
+/// `[length, index]`, listed in reverse order, and counting words as tokens.
+pub fn embeddings_response(request: &StubRequest) -> StubResponse {
+    let inputs: Vec<&str> = request.body["input"]
+        .as_array()
+        .map(|inputs| inputs.iter().filter_map(JsonValue::as_str).collect())
+        .unwrap_or_default();
+    let data: Vec<JsonValue> = inputs
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, input)| json!({"object": "embedding", "index": index, "embedding": [input.len(), index]}))
+        .collect();
+    let tokens: usize = inputs.iter().map(|input| input.split_whitespace().count()).sum();
+    let body = json!({
+        "object": "list",
+        "data": data,
+        "model": request.body["model"],
+        "usage": {"prompt_tokens": tokens, "total_tokens": tokens},
+    });
+    StubResponse::status(200).with_body(&body.to_string())
+}
+
+/// A `StubServer` answering with `responses` in order, then with
+/// `embeddings_response`.
+pub async fn embeddings_server(responses: Vec<StubResponse>) -> StubServer {
+    StubServer::start(responses, embeddings_response).await
+}
+
+/// Writes a two-layer BERT model with 16 dimensions and random weights, and
+/// the 16-word tokenizer of `fixtures/tiny_bert`, to a temporary directory.
+pub fn write_tiny_model(name: &str) -> PathBuf {
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/tiny_bert");
+    let dir = std::env::temp_dir().join(format!("embedder_model_{}_{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    for file in ["config.json", "tokenizer.json"] {
+        std::fs::copy(fixtures.join(file), dir.join(file)).unwrap();
+    }
+    let config: Config = serde_json::from_slice(&std::fs::read(dir.join("config.json")).unwrap()).unwrap();
+    let weights = VarMap::new();
+    BertModel::load(VarBuilder::from_varmap(&weights, DType::F32, &Device::Cpu), &config).unwrap();
+    weights.save(dir.join("model.safetensors")).unwrap();
+    dir
+}
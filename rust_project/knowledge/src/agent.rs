@@ -0,0 +1,564 @@
+use crate::schema::{MetaDataIndex, SchemaError};
+use document::MetaDataSchema;
+use crate::{DirectoryLoader, DocumentKnowledgeBase};
+use async_trait::async_trait;
+use document::chunking::ChunkingError;
+use document::reader::SharedChunkingStrategy;
+use document::Document;
+use serde_json::Value as JsonValue;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use vectordb::{VectorDb, VectorDbError};
+
+/// Number of documents a search returns when no count is given.
+pub const DEFAULT_NUM_DOCUMENTS: usize = 5;
+/// Number of loaded documents after which the vector db is optimized.
+pub const DEFAULT_OPTIMIZE_ON: usize = 1000;
+
+// Define a custom error type for knowledge base operations
+#[derive(Debug)]
+pub enum KnowledgeError {
+    Chunking(ChunkingError),
+    Schema(SchemaError),
+    VectorDb(VectorDbError),
+}
+
+impl fmt::Display for KnowledgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KnowledgeError::Chunking(err) => write!(f, "{}", err),
+            KnowledgeError::Schema(err) => write!(f, "{}", err),
+            KnowledgeError::VectorDb(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for KnowledgeError {}
+
+impl From<ChunkingError> for KnowledgeError {
+    fn from(err: ChunkingError) -> Self {
+        KnowledgeError::Chunking(err)
+    }
+}
+
+impl From<SchemaError> for KnowledgeError {
+    fn from(err: SchemaError) -> Self {
+        KnowledgeError::Schema(err)
+    }
+}
+
+impl From<VectorDbError> for KnowledgeError {
+    fn from(err: VectorDbError) -> Self {
+        KnowledgeError::VectorDb(err)
+    }
+}
+
+/// How the documents of a source are written by [`Knowledge::load`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadPolicy {
+    /// Follows the `upsert` and `skip_existing` arguments of `load`.
+    #[default]
+    Inherit,
+    /// Always upserts, falling back to inserting new documents when the
+    /// vector db cannot upsert.
+    Upsert,
+    /// Only inserts documents the vector db does not hold yet.
+    InsertNew,
+    /// Inserts every document.
+    Insert,
+    /// Never loads the documents.
+    Skip,
+}
+
+impl LoadPolicy {
+    /// The `(upsert, skip_existing)` to load with, or `None` to skip loading.
+    pub fn resolve(self, upsert: bool, skip_existing: bool) -> Option<(bool, bool)> {
+        match self {
+            LoadPolicy::Inherit => Some((upsert, skip_existing)),
+            LoadPolicy::Upsert => Some((true, true)),
+            LoadPolicy::InsertNew => Some((false, true)),
+            LoadPolicy::Insert => Some((false, false)),
+            LoadPolicy::Skip => None,
+        }
+    }
+}
+
+/// Anything that yields documents to load into a knowledge base, one list
+/// at a time.
+pub trait DocumentSource {
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_>;
+
+    /// Yields each list with the policy it is loaded with.
+    fn policy_lists(&self) -> Box<dyn Iterator<Item = (LoadPolicy, Vec<Document>)> + '_> {
+        Box::new(self.document_lists().map(|documents| (LoadPolicy::Inherit, documents)))
+    }
+}
+
+impl DocumentSource for DocumentKnowledgeBase {
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_> {
+        Box::new(self.document_lists_iter())
+    }
+}
+
+/// Yields one list per file. Files that fail to load are skipped; use
+/// [`DirectoryLoader::document_lists_iter`] directly to see why.
+impl DocumentSource for DirectoryLoader {
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_> {
+        Box::new(self.document_lists_iter())
+    }
+}
+
+/// Knowledge an agent can load into a vector db and search.
+///
+/// Mirrors Python's `agno.knowledge.agent.AgentKnowledge`.
+#[async_trait]
+pub trait Knowledge {
+    /// Yields the documents of the knowledge base, one list at a time.
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_>;
+
+    /// Loads the documents into the vector db, returning how many were
+    /// written.
+    ///
+    /// `recreate` drops the collection first. `upsert` upserts documents
+    /// when the vector db supports it; otherwise they are inserted, skipping
+    /// ones the vector db already holds if `skip_existing` is set. Sources
+    /// with their own [`LoadPolicy`] override `upsert` and `skip_existing`.
+    fn load(&mut self, recreate: bool, upsert: bool, skip_existing: bool) -> Result<usize, KnowledgeError>;
+
+    /// The metadata keys of the documents, which searches may filter on.
+    fn metadata_index(&self) -> &MetaDataIndex;
+
+    /// Returns the documents most relevant to `query`, matching `filters`.
+    /// Filtering on a key outside the metadata index is an error.
+    fn search(
+        &self,
+        query: &str,
+        num_documents: Option<usize>,
+        filters: Option<HashMap<String, JsonValue>>,
+    ) -> Result<Vec<Document>, KnowledgeError>;
+    async fn async_search(
+        &self,
+        query: &str,
+        num_documents: Option<usize>,
+        filters: Option<HashMap<String, JsonValue>>,
+    ) -> Result<Vec<Document>, KnowledgeError>;
+
+    /// Checks if the vector db collection exists.
+    fn exists(&self) -> Result<bool, KnowledgeError>;
+
+    /// Clears the vector db, returning whether it held anything.
+    fn delete(&mut self) -> Result<bool, KnowledgeError>;
+}
+
+/// A knowledge base that loads the documents of a source into a vector db.
+///
+/// The chunking strategy, if any, is applied to every document the source
+/// yields; leave it unset for sources that chunk on their own.
+///
+/// Loading records the metadata of every document in the metadata index,
+/// which searches check their filters against. When the vector db was
+/// loaded earlier, call [`KnowledgeBase::initialize_metadata_index`] to
+/// learn the keys without loading again.
+pub struct KnowledgeBase<S, D> {
+    pub source: S,
+    pub vector_db: D,
+    pub num_documents: usize,
+    pub optimize_on: Option<usize>,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+    pub metadata_index: MetaDataIndex,
+}
+
+impl<S: DocumentSource, D: VectorDb> KnowledgeBase<S, D> {
+    /// Creates a knowledge base over `source`, stored in `vector_db`.
+    pub fn new(source: S, vector_db: D) -> Self {
+        KnowledgeBase {
+            source,
+            vector_db,
+            num_documents: DEFAULT_NUM_DOCUMENTS,
+            optimize_on: Some(DEFAULT_OPTIMIZE_ON),
+            chunking_strategy: None,
+            metadata_index: MetaDataIndex::inferred(),
+        }
+    }
+
+    /// Sets how many documents a search returns by default.
+    pub fn with_num_documents(mut self, num_documents: usize) -> Self {
+        self.num_documents = num_documents;
+        self
+    }
+
+    /// Sets after how many loaded documents the vector db is optimized;
+    /// `None` never optimizes.
+    pub fn with_optimize_on(mut self, optimize_on: Option<usize>) -> Self {
+        self.optimize_on = optimize_on;
+        self
+    }
+
+    /// Chunks every document of the source with `strategy` before loading.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+
+    /// Validates document metadata against `schema`, whose fields become
+    /// the keys searches may filter on.
+    pub fn with_metadata_schema(mut self, schema: MetaDataSchema) -> Self {
+        self.metadata_index = MetaDataIndex::declared(schema);
+        self
+    }
+
+    /// Records the metadata of every document of the source without loading
+    /// anything.
+    pub fn initialize_metadata_index(&mut self) -> Result<(), KnowledgeError> {
+        for documents in self.source.document_lists() {
+            let documents = chunk_documents(self.chunking_strategy.as_ref(), documents)?;
+            for document in &documents {
+                self.metadata_index.record(&document.meta_data)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits each document with `strategy`, if there is one.
+pub(crate) fn chunk_documents(
+    strategy: Option<&SharedChunkingStrategy>,
+    documents: Vec<Document>,
+) -> Result<Vec<Document>, ChunkingError> {
+    match strategy {
+        Some(strategy) => {
+            let mut chunks = Vec::new();
+            for document in &documents {
+                chunks.extend(strategy.chunk(document)?);
+            }
+            Ok(chunks)
+        }
+        None => Ok(documents),
+    }
+}
+
+/// Drops the documents the vector db already holds, and repeated content
+/// within `documents`.
+pub fn filter_existing_documents<D: VectorDb + ?Sized>(
+    vector_db: &D,
+    documents: Vec<Document>,
+) -> Result<Vec<Document>, VectorDbError> {
+    let mut seen_content = HashSet::new();
+    let mut filtered = Vec::with_capacity(documents.len());
+    for document in documents {
+        if seen_content.contains(&document.content) || vector_db.doc_exists(&document)? {
+            continue;
+        }
+        seen_content.insert(document.content.clone());
+        filtered.push(document);
+    }
+    Ok(filtered)
+}
+
+/// Like [`filter_existing_documents`], but asks the vector db with
+/// `async_doc_exists`, without blocking the runtime.
+pub async fn async_filter_existing_documents<D: VectorDb + ?Sized>(
+    vector_db: &D,
+    documents: Vec<Document>,
+) -> Result<Vec<Document>, VectorDbError> {
+    let mut seen_content = HashSet::new();
+    let mut filtered = Vec::with_capacity(documents.len());
+    for document in documents {
+        if seen_content.contains(&document.content) || vector_db.async_doc_exists(&document).await? {
+            continue;
+        }
+        seen_content.insert(document.content.clone());
+        filtered.push(document);
+    }
+    Ok(filtered)
+}
+
+/// Writes `documents` to the vector db as [`Knowledge::load`] does, each with
+/// its `meta_data` as filters, returning how many were written.
+pub fn load_documents<D: VectorDb + ?Sized>(
+    vector_db: &mut D,
+    documents: Vec<Document>,
+    upsert: bool,
+    skip_existing: bool,
+) -> Result<usize, VectorDbError> {
+    if upsert && vector_db.upsert_available() {
+        for document in &documents {
+            vector_db.upsert(std::slice::from_ref(document), Some(document.meta_data.clone()))?;
+        }
+        return Ok(documents.len());
+    }
+    let documents = if skip_existing {
+        filter_existing_documents(vector_db, documents)?
+    } else {
+        documents
+    };
+    for document in &documents {
+        vector_db.insert(std::slice::from_ref(document), Some(document.meta_data.clone()))?;
+    }
+    Ok(documents.len())
+}
+
+/// Writes `documents` like [`load_documents`], through the async methods of
+/// the vector db.
+pub async fn async_load_documents<D: VectorDb + ?Sized>(
+    vector_db: &mut D,
+    documents: Vec<Document>,
+    upsert: bool,
+    skip_existing: bool,
+) -> Result<usize, VectorDbError> {
+    if upsert && vector_db.upsert_available() {
+        for document in &documents {
+            vector_db.async_upsert(std::slice::from_ref(document), Some(document.meta_data.clone())).await?;
+        }
+        return Ok(documents.len());
+    }
+    let documents = if skip_existing {
+        async_filter_existing_documents(vector_db, documents).await?
+    } else {
+        documents
+    };
+    for document in &documents {
+        vector_db.async_insert(std::slice::from_ref(document), Some(document.meta_data.clone())).await?;
+    }
+    Ok(documents.len())
+}
+
+/// Drops the collection if `recreate` is set, then creates it if missing.
+pub(crate) fn prepare_collection<D: VectorDb + ?Sized>(vector_db: &mut D, recreate: bool) -> Result<(), VectorDbError> {
+    if recreate {
+        vector_db.drop_db()?;
+    }
+    if !vector_db.db_exists()? {
+        vector_db.create()?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl<S, D> Knowledge for KnowledgeBase<S, D>
+where
+    S: DocumentSource + Send + Sync,
+    D: VectorDb + Send + Sync,
+{
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_> {
+        self.source.document_lists()
+    }
+
+    fn load(&mut self, recreate: bool, upsert: bool, skip_existing: bool) -> Result<usize, KnowledgeError> {
+        prepare_collection(&mut self.vector_db, recreate)?;
+        let mut loaded = 0;
+        for (policy, documents) in self.source.policy_lists() {
+            let resolved = policy.resolve(upsert, skip_existing);
+            // Skipped documents are not chunked, but their keys are still
+            // ones the vector db may hold.
+            let documents = match resolved {
+                Some(_) => chunk_documents(self.chunking_strategy.as_ref(), documents)?,
+                None => documents,
+            };
+            for document in &documents {
+                self.metadata_index.record(&document.meta_data)?;
+            }
+            let Some((upsert, skip_existing)) = resolved else {
+                continue;
+            };
+            loaded += load_documents(&mut self.vector_db, documents, upsert, skip_existing)?;
+        }
+        if self.optimize_on.is_some_and(|optimize_on| loaded >= optimize_on) {
+            self.vector_db.optimize()?;
+        }
+        Ok(loaded)
+    }
+
+    fn metadata_index(&self) -> &MetaDataIndex {
+        &self.metadata_index
+    }
+
+    fn search(
+        &self,
+        query: &str,
+        num_documents: Option<usize>,
+        filters: Option<HashMap<String, JsonValue>>,
+    ) -> Result<Vec<Document>, KnowledgeError> {
+        if let Some(filters) = &filters {
+            self.metadata_index.validate_filters(filters)?;
+        }
+        let limit = num_documents.unwrap_or(self.num_documents) as u32;
+        Ok(self.vector_db.search(query, limit, filters)?)
+    }
+
+    async fn async_search(
+        &self,
+        query: &str,
+        num_documents: Option<usize>,
+        filters: Option<HashMap<String, JsonValue>>,
+    ) -> Result<Vec<Document>, KnowledgeError> {
+        if let Some(filters) = &filters {
+            self.metadata_index.validate_filters(filters)?;
+        }
+        let limit = num_documents.unwrap_or(self.num_documents) as u32;
+        match self.vector_db.async_search(query, limit, filters.clone()).await {
+            Err(VectorDbError::NotImplemented) => self.search(query, num_documents, filters),
+            result => Ok(result?),
+        }
+    }
+
+    fn exists(&self) -> Result<bool, KnowledgeError> {
+        Ok(self.vector_db.db_exists()?)
+    }
+
+    fn delete(&mut self) -> Result<bool, KnowledgeError> {
+        Ok(self.vector_db.delete()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{doc, with_meta_data, InMemoryDb};
+    use reranker::{LexicalReranker, RerankingDb};
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn knowledge_base() -> KnowledgeBase<DocumentKnowledgeBase, InMemoryDb> {
+        let source = DocumentKnowledgeBase::new(vec![
+            with_meta_data(doc("a", "rust ownership and borrowing"), "topic", json!("rust")),
+            with_meta_data(doc("b", "python generators"), "topic", json!("python")),
+            with_meta_data(doc("c", "rust async runtimes"), "topic", json!("rust")),
+        ]);
+        KnowledgeBase::new(source, InMemoryDb::default())
+    }
+
+    #[test]
+    fn test_load_creates_and_inserts() {
+        let mut kb = knowledge_base();
+        assert!(!kb.exists().unwrap());
+        assert_eq!(kb.load(false, false, true).unwrap(), 3);
+        assert!(kb.exists().unwrap());
+        assert_eq!(kb.vector_db.log, vec!["insert a", "insert b", "insert c"]);
+    }
+
+    #[test]
+    fn test_load_skips_existing_documents() {
+        let mut kb = knowledge_base();
+        kb.load(false, false, true).unwrap();
+        kb.source.add_document(with_meta_data(doc("d", "python generators"), "topic", json!("python")));
+        kb.source.add_document(with_meta_data(doc("e", "go channels"), "topic", json!("go")));
+        assert_eq!(kb.load(false, false, true).unwrap(), 1);
+        assert!(kb.vector_db.documents.contains_key("e"));
+        assert!(!kb.vector_db.documents.contains_key("d"));
+
+        // Without skipping, re-inserting the same ids fails.
+        assert!(matches!(kb.load(false, false, false), Err(KnowledgeError::VectorDb(_))));
+    }
+
+    #[test]
+    fn test_load_upserts_or_recreates() {
+        let mut kb = knowledge_base();
+        kb.vector_db = InMemoryDb::with_upsert();
+        kb.load(false, false, true).unwrap();
+        assert_eq!(kb.load(false, true, true).unwrap(), 3);
+        assert_eq!(kb.vector_db.log[3..], ["upsert a", "upsert b", "upsert c"]);
+
+        kb.vector_db.log.clear();
+        assert_eq!(kb.load(true, false, true).unwrap(), 3);
+        assert_eq!(kb.vector_db.log, vec!["drop", "insert a", "insert b", "insert c"]);
+    }
+
+    #[test]
+    fn test_load_optimizes_once_enough_documents_are_loaded() {
+        let mut kb = knowledge_base().with_optimize_on(Some(3));
+        kb.load(false, false, true).unwrap();
+        assert_eq!(kb.vector_db.optimized, 1);
+        // Nothing new is loaded the second time.
+        kb.load(false, false, true).unwrap();
+        assert_eq!(kb.vector_db.optimized, 1);
+
+        let mut kb = knowledge_base().with_optimize_on(None);
+        kb.load(false, false, true).unwrap();
+        assert_eq!(kb.vector_db.optimized, 0);
+    }
+
+    #[test]
+    fn test_load_applies_chunking_strategy() {
+        struct SplitSentences;
+        impl document::chunking::ChunkingStrategy for SplitSentences {
+            fn chunk(&self, document: &Document) -> Result<Vec<Document>, ChunkingError> {
+                Ok(document
+                    .content
+                    .split(" and ")
+                    .enumerate()
+                    .map(|(index, part)| Document {
+                        id: document.id.as_ref().map(|id| format!("{}_{}", id, index + 1)),
+                        content: part.to_string(),
+                        ..document.clone()
+                    })
+                    .collect())
+            }
+        }
+
+        let mut kb = knowledge_base().with_chunking_strategy(Arc::new(SplitSentences));
+        assert_eq!(kb.load(false, false, true).unwrap(), 4);
+        assert_eq!(kb.vector_db.documents["a_2"].content, "borrowing");
+    }
+
+    #[test]
+    fn test_search_with_filters() {
+        let mut kb = knowledge_base().with_num_documents(1);
+        kb.load(false, false, true).unwrap();
+
+        assert_eq!(kb.search("rust", None, None).unwrap().len(), 1);
+        assert_eq!(kb.search("rust", Some(10), None).unwrap().len(), 2);
+
+        let filters = HashMap::from([("topic".to_string(), json!("python"))]);
+        let found = kb.search("rust python", Some(10), Some(filters)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id.as_deref(), Some("b"));
+
+        let filters = HashMap::from([("year".to_string(), json!(2024))]);
+        assert!(matches!(
+            kb.search("rust", None, Some(filters)),
+            Err(KnowledgeError::Schema(SchemaError::UnknownFilterKey { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_metadata_schema() {
+        let mut kb = knowledge_base();
+        let filters = HashMap::from([("topic".to_string(), json!("rust"))]);
+        // Nothing is known before the metadata has been seen.
+        assert!(kb.search("rust", None, Some(filters.clone())).is_err());
+        kb.initialize_metadata_index().unwrap();
+        assert_eq!(kb.metadata_index().filter_keys(), vec!["topic"]);
+        assert!(kb.search("rust", None, Some(filters)).unwrap().is_empty());
+
+        let schema = MetaDataSchema::new().with_optional("topic", document::MetaDataType::Integer);
+        let mut kb = knowledge_base().with_metadata_schema(schema);
+        assert!(matches!(kb.load(false, false, true), Err(KnowledgeError::Schema(_))));
+        assert!(kb.vector_db.documents.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_async_search() {
+        let mut kb = knowledge_base();
+        kb.load(false, false, true).unwrap();
+        let found = kb.async_search("async runtimes", None, None).await.unwrap();
+        assert_eq!(found[0].id.as_deref(), Some("c"));
+        assert!(kb.delete().unwrap());
+        assert!(kb.search("rust", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_through_reranker() {
+        let source = knowledge_base().source;
+        let vector_db = RerankingDb::new(InMemoryDb::default(), LexicalReranker::new()).with_candidates(Some(10));
+        let mut kb = KnowledgeBase::new(source, vector_db);
+        assert_eq!(kb.load(false, false, true).unwrap(), 3);
+
+        let found = kb.search("rust runtimes", Some(2), None).unwrap();
+        let ids: Vec<_> = found.iter().map(|d| d.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["c", "a"]);
+        assert!(found[0].reranking_score.unwrap() > found[1].reranking_score.unwrap());
+        assert_eq!(kb.vector_db.vector_db.log, vec!["insert a", "insert b", "insert c"]);
+    }
+}
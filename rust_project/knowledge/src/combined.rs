@@ -0,0 +1,165 @@
+use crate::agent::{DocumentSource, LoadPolicy};
+use document::Document;
+use serde_json::json;
+
+/// `meta_data` key holding the name of the source a document came from.
+pub const SOURCE_NAME_KEY: &str = "source_name";
+
+/// A source of a [`CombinedKnowledgeBase`], with the name its documents are
+/// tagged with and the policy they are loaded with.
+pub struct NamedSource {
+    pub name: String,
+    pub source: Box<dyn DocumentSource + Send + Sync>,
+    pub policy: LoadPolicy,
+}
+
+/// Chains several sources into one, so a single knowledge base can draw on
+/// PDFs, CSVs and Markdown at once.
+///
+/// Mirrors Python's `agno.knowledge.combined.CombinedKnowledgeBase`. Every
+/// document records the name of its source under [`SOURCE_NAME_KEY`]; when
+/// combined sources are nested, the innermost name is kept. Wrap it in a
+/// [`KnowledgeBase`](crate::KnowledgeBase) to load it into a vector db, where
+/// each source follows its own [`LoadPolicy`].
+#[derive(Default)]
+pub struct CombinedKnowledgeBase {
+    pub sources: Vec<NamedSource>,
+}
+
+impl CombinedKnowledgeBase {
+    /// Creates a combined knowledge base without any sources.
+    pub fn new() -> Self {
+        CombinedKnowledgeBase::default()
+    }
+
+    /// Adds a source loaded as `load` is told to.
+    pub fn with_source(self, name: &str, source: impl DocumentSource + Send + Sync + 'static) -> Self {
+        self.with_source_policy(name, source, LoadPolicy::Inherit)
+    }
+
+    /// Adds a source loaded according to `policy`.
+    pub fn with_source_policy(
+        mut self,
+        name: &str,
+        source: impl DocumentSource + Send + Sync + 'static,
+        policy: LoadPolicy,
+    ) -> Self {
+        self.sources.push(NamedSource {
+            name: name.to_string(),
+            source: Box::new(source),
+            policy,
+        });
+        self
+    }
+
+    /// Returns the names of the sources, in order.
+    pub fn source_names(&self) -> Vec<&str> {
+        self.sources.iter().map(|source| source.name.as_str()).collect()
+    }
+
+    /// Yields the document lists of every source in turn, each document
+    /// tagged with its source name.
+    pub fn document_lists_iter(&self) -> impl Iterator<Item = Vec<Document>> + '_ {
+        self.policy_lists().map(|(_, documents)| documents)
+    }
+}
+
+fn tag_source(name: &str, mut documents: Vec<Document>) -> Vec<Document> {
+    for document in &mut documents {
+        document
+            .meta_data
+            .entry(SOURCE_NAME_KEY.to_string())
+            .or_insert_with(|| json!(name));
+    }
+    documents
+}
+
+impl DocumentSource for CombinedKnowledgeBase {
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_> {
+        Box::new(self.document_lists_iter())
+    }
+
+    fn policy_lists(&self) -> Box<dyn Iterator<Item = (LoadPolicy, Vec<Document>)> + '_> {
+        Box::new(self.sources.iter().flat_map(|named| {
+            named.source.policy_lists().map(move |(policy, documents)| {
+                // A nested source's own policy wins over the one it was added with.
+                let policy = if policy == LoadPolicy::Inherit { named.policy } else { policy };
+                (policy, tag_source(&named.name, documents))
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryDb;
+    use crate::{DocumentKnowledgeBase, Knowledge, KnowledgeBase};
+    use std::collections::HashMap;
+
+    fn source(ids: &[&str]) -> DocumentKnowledgeBase {
+        DocumentKnowledgeBase::new(
+            ids.iter()
+                .map(|id| Document {
+                    id: Some(id.to_string()),
+                    content: format!("content of {}", id),
+                    name: Some(id.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_chains_sources_and_tags_documents() {
+        let combined = CombinedKnowledgeBase::new()
+            .with_source("pdfs", source(&["p1", "p2"]))
+            .with_source("csvs", source(&["c1"]));
+        assert_eq!(combined.source_names(), vec!["pdfs", "csvs"]);
+
+        let tagged: Vec<(String, serde_json::Value)> = combined
+            .document_lists_iter()
+            .flatten()
+            .map(|d| (d.id.unwrap(), d.meta_data[SOURCE_NAME_KEY].clone()))
+            .collect();
+        assert_eq!(
+            tagged,
+            vec![
+                ("p1".to_string(), json!("pdfs")),
+                ("p2".to_string(), json!("pdfs")),
+                ("c1".to_string(), json!("csvs")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nested_sources_keep_inner_name_and_policy() {
+        let inner = CombinedKnowledgeBase::new().with_source_policy("docs", source(&["d1"]), LoadPolicy::Skip);
+        let combined = CombinedKnowledgeBase::new().with_source_policy("all", inner, LoadPolicy::Insert);
+        let (policy, documents) = combined.policy_lists().next().unwrap();
+        assert_eq!(policy, LoadPolicy::Skip);
+        assert_eq!(documents[0].meta_data[SOURCE_NAME_KEY], json!("docs"));
+    }
+
+    #[test]
+    fn test_load_follows_source_policies() {
+        let combined = CombinedKnowledgeBase::new()
+            .with_source("markdown", source(&["m1"]))
+            .with_source_policy("pdfs", source(&["p1"]), LoadPolicy::Upsert)
+            .with_source_policy("drafts", source(&["d1"]), LoadPolicy::Skip);
+        let mut kb = KnowledgeBase::new(combined, InMemoryDb::with_upsert());
+
+        assert_eq!(kb.load(false, false, true).unwrap(), 2);
+        assert_eq!(kb.vector_db.log, vec!["insert m1", "upsert p1"]);
+
+        // Inherited sources skip what is already there; upserting ones rewrite it.
+        kb.vector_db.log.clear();
+        assert_eq!(kb.load(false, false, true).unwrap(), 1);
+        assert_eq!(kb.vector_db.log, vec!["upsert p1"]);
+
+        let filters = HashMap::from([(SOURCE_NAME_KEY.to_string(), json!("pdfs"))]);
+        let found = kb.search("content", Some(10), Some(filters)).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id.as_deref(), Some("p1"));
+    }
+}
@@ -0,0 +1,476 @@
+use crate::sync::MODIFIED_AT_KEY;
+use crate::DocumentKnowledgeBase;
+use document::chunking::Language;
+use document::reader::{
+    CsvReader, DocxReader, HtmlReader, JsonReader, MarkdownReader, PdfReader, Reader, ReaderError,
+    SharedChunkingStrategy, TextReader,
+};
+use document::Document;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde_json::json;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// How a file is read, chosen from its extension or, failing that, its
+/// first bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Text,
+    Markdown,
+    Json,
+    Csv,
+    Tsv,
+    Pdf,
+    Docx,
+    Html,
+    Code(Language),
+}
+
+/// Bytes inspected when a file's extension does not identify it.
+const SNIFF_LEN: usize = 1024;
+
+impl FileKind {
+    /// Returns the kind for a (case-insensitive) file extension.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        let kind = match extension.as_str() {
+            "txt" | "text" | "log" | "rst" => FileKind::Text,
+            "md" | "markdown" | "mdx" => FileKind::Markdown,
+            "json" => FileKind::Json,
+            "csv" => FileKind::Csv,
+            "tsv" => FileKind::Tsv,
+            "pdf" => FileKind::Pdf,
+            "docx" => FileKind::Docx,
+            "html" | "htm" | "xhtml" => FileKind::Html,
+            other => return Language::from_extension(other).map(FileKind::Code),
+        };
+        Some(kind)
+    }
+
+    /// Guesses the kind from the first bytes of a file: PDF and zip (DOCX)
+    /// signatures, HTML markup, or otherwise UTF-8 text. Binary data has no
+    /// kind.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"%PDF-") {
+            return Some(FileKind::Pdf);
+        }
+        if bytes.starts_with(b"PK\x03\x04") {
+            return Some(FileKind::Docx);
+        }
+        let text = match std::str::from_utf8(bytes) {
+            Ok(text) => text,
+            // A multi-byte character cut off by the sniff window is fine.
+            Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok()?,
+            Err(_) => return None,
+        };
+        if text.contains('\0') {
+            return None;
+        }
+        let start = text.trim_start_matches('\u{feff}').trim_start().to_ascii_lowercase();
+        if start.starts_with("<!doctype html") || start.starts_with("<html") {
+            return Some(FileKind::Html);
+        }
+        Some(FileKind::Text)
+    }
+
+    /// The name recorded as `file_type` in `meta_data`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileKind::Text => "text",
+            FileKind::Markdown => "markdown",
+            FileKind::Json => "json",
+            FileKind::Csv => "csv",
+            FileKind::Tsv => "tsv",
+            FileKind::Pdf => "pdf",
+            FileKind::Docx => "docx",
+            FileKind::Html => "html",
+            FileKind::Code(_) => "code",
+        }
+    }
+
+    /// A reader for this kind of file, without a chunking strategy.
+    pub fn reader(&self) -> Box<dyn Reader + Send + Sync> {
+        match self {
+            FileKind::Text | FileKind::Code(_) => Box::new(TextReader::default()),
+            FileKind::Markdown => Box::new(MarkdownReader::default()),
+            FileKind::Json => Box::new(JsonReader::default()),
+            FileKind::Csv => Box::new(CsvReader::default()),
+            FileKind::Tsv => Box::new(CsvReader::default().with_dialect(b'\t', b'"')),
+            FileKind::Pdf => Box::new(PdfReader::default()),
+            FileKind::Docx => Box::new(DocxReader::default()),
+            FileKind::Html => Box::new(HtmlReader::default()),
+        }
+    }
+}
+
+// Define a custom error type for directory loader configuration
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoaderError {
+    InvalidPattern(String),
+}
+
+impl fmt::Display for LoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderError::InvalidPattern(msg) => write!(f, "Invalid glob pattern: {}", msg),
+        }
+    }
+}
+
+impl Error for LoaderError {}
+
+/// A file that could not be read, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileError {
+    pub path: PathBuf,
+    pub error: ReaderError,
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.error)
+    }
+}
+
+impl Error for FileError {}
+
+fn build_globs(patterns: &[&str]) -> Result<Option<GlobSet>, LoaderError> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| LoaderError::InvalidPattern(e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map(Some)
+        .map_err(|e| LoaderError::InvalidPattern(e.to_string()))
+}
+
+/// Loads a directory tree of mixed files as documents.
+///
+/// Mirrors the `path` handling of the Python knowledge bases, which glob a
+/// directory and read every matching file, but picks the reader for each
+/// file itself: by extension, or by sniffing the first bytes of files with
+/// an unknown or missing extension. Binary files nobody can read are
+/// skipped. Every document records the file's `file_path` (relative to the
+/// root, with `/` separators), `file_type` and `modified_at` (milliseconds
+/// since the Unix epoch), and source files their `language`, before the
+/// chunking strategy is applied.
+///
+/// Include and exclude globs match paths relative to the root; `*` also
+/// matches `/`, so `*.md` matches Markdown files at any depth. Excluded
+/// directories are not descended into, and neither are hidden ones unless
+/// `skip_hidden` is turned off.
+#[derive(Clone)]
+pub struct DirectoryLoader {
+    pub root: PathBuf,
+    pub chunking_strategy: Option<SharedChunkingStrategy>,
+    pub follow_links: bool,
+    pub skip_hidden: bool,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl DirectoryLoader {
+    /// Creates a loader for every file below `root`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DirectoryLoader {
+            root: root.into(),
+            chunking_strategy: None,
+            follow_links: false,
+            skip_hidden: true,
+            include: None,
+            exclude: None,
+        }
+    }
+
+    /// Only loads files matching one of `patterns`.
+    pub fn with_include(mut self, patterns: &[&str]) -> Result<Self, LoaderError> {
+        self.include = build_globs(patterns)?;
+        Ok(self)
+    }
+
+    /// Skips files and directories matching one of `patterns`.
+    pub fn with_exclude(mut self, patterns: &[&str]) -> Result<Self, LoaderError> {
+        self.exclude = build_globs(patterns)?;
+        Ok(self)
+    }
+
+    /// Chunks every document loaded with `strategy`.
+    pub fn with_chunking_strategy(mut self, strategy: SharedChunkingStrategy) -> Self {
+        self.chunking_strategy = Some(strategy);
+        self
+    }
+
+    /// Follows symbolic links while walking.
+    pub fn with_follow_links(mut self, follow_links: bool) -> Self {
+        self.follow_links = follow_links;
+        self
+    }
+
+    /// Walks into hidden (dot) files and directories too when `false`.
+    pub fn with_skip_hidden(mut self, skip_hidden: bool) -> Self {
+        self.skip_hidden = skip_hidden;
+        self
+    }
+
+    /// The path of `path` relative to the root, with `/` separators.
+    pub fn relative_path(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn is_excluded(&self, entry: &walkdir::DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return false;
+        }
+        if self.skip_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+            return true;
+        }
+        self.exclude
+            .as_ref()
+            .is_some_and(|exclude| exclude.is_match(self.relative_path(entry.path())))
+    }
+
+    /// Walks the tree in a stable (sorted) order, yielding the files that
+    /// pass the include and exclude globs.
+    pub fn files(&self) -> impl Iterator<Item = Result<PathBuf, FileError>> + '_ {
+        walkdir::WalkDir::new(&self.root)
+            .follow_links(self.follow_links)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|entry| !self.is_excluded(entry))
+            .filter_map(move |entry| match entry {
+                Ok(entry) if !entry.file_type().is_file() => None,
+                Ok(entry) => {
+                    let included = self
+                        .include
+                        .as_ref()
+                        .is_none_or(|include| include.is_match(self.relative_path(entry.path())));
+                    included.then(|| Ok(entry.into_path()))
+                }
+                Err(e) => {
+                    let path = e.path().map_or_else(|| self.root.clone(), Path::to_path_buf);
+                    let error = match e.into_io_error() {
+                        Some(io) => ReaderError::from(io),
+                        None => ReaderError::Io("filesystem loop".to_string()),
+                    };
+                    Some(Err(FileError { path, error }))
+                }
+            })
+    }
+
+    /// Detects how `path` should be read, or `None` for unreadable binaries.
+    pub fn file_kind(&self, path: &Path) -> Result<Option<FileKind>, ReaderError> {
+        if let Some(kind) = path.extension().and_then(|ext| FileKind::from_extension(&ext.to_string_lossy())) {
+            return Ok(Some(kind));
+        }
+        let mut head = Vec::with_capacity(SNIFF_LEN);
+        File::open(path)?.take(SNIFF_LEN as u64).read_to_end(&mut head)?;
+        if head.is_empty() {
+            return Ok(None);
+        }
+        Ok(FileKind::sniff(&head))
+    }
+
+    /// Reads and chunks one file. Returns an empty list for files that are
+    /// skipped.
+    pub fn load_file(&self, path: &Path) -> Result<Vec<Document>, FileError> {
+        let with_path = |error: ReaderError| FileError { path: path.to_path_buf(), error };
+        let Some(kind) = self.file_kind(path).map_err(with_path)? else {
+            return Ok(Vec::new());
+        };
+        let mut documents = kind.reader().read_path(path).map_err(with_path)?;
+        let file_path = self.relative_path(path);
+        let modified_at = fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map(|elapsed| elapsed.as_millis() as u64);
+        for document in &mut documents {
+            document.meta_data.insert("file_path".to_string(), json!(file_path));
+            document.meta_data.insert("file_type".to_string(), json!(kind.as_str()));
+            if let Some(modified_at) = modified_at {
+                document.meta_data.insert(MODIFIED_AT_KEY.to_string(), json!(modified_at));
+            }
+            if let FileKind::Code(language) = kind {
+                document.meta_data.insert("language".to_string(), json!(language.as_str()));
+            }
+        }
+        match &self.chunking_strategy {
+            Some(strategy) => {
+                let mut chunks = Vec::new();
+                for document in &documents {
+                    chunks.extend(strategy.chunk(document).map_err(|e| with_path(e.into()))?);
+                }
+                Ok(chunks)
+            }
+            None => Ok(documents),
+        }
+    }
+
+    /// Yields the documents of each file, one list per file. Files that
+    /// fail are skipped and collected in [`DocumentLists::errors`].
+    pub fn document_lists_iter(&self) -> DocumentLists<'_> {
+        DocumentLists {
+            loader: self,
+            files: Box::new(self.files()),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Loads the whole tree into a knowledge base, with the files that failed.
+    pub fn load(&self) -> (DocumentKnowledgeBase, Vec<FileError>) {
+        let mut lists = self.document_lists_iter();
+        let documents = lists.by_ref().flatten().collect();
+        (DocumentKnowledgeBase::new(documents), lists.errors)
+    }
+}
+
+/// Iterator over the document lists of a [`DirectoryLoader`].
+pub struct DocumentLists<'a> {
+    loader: &'a DirectoryLoader,
+    files: Box<dyn Iterator<Item = Result<PathBuf, FileError>> + 'a>,
+    errors: Vec<FileError>,
+}
+
+impl DocumentLists<'_> {
+    /// The files that failed so far.
+    pub fn errors(&self) -> &[FileError] {
+        &self.errors
+    }
+}
+
+impl Iterator for DocumentLists<'_> {
+    type Item = Vec<Document>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let result = self.files.next()?.and_then(|path| self.loader.load_file(&path));
+            match result {
+                Ok(documents) if documents.is_empty() => continue,
+                Ok(documents) => return Some(documents),
+                Err(error) => self.errors.push(error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use document::chunking::CodeChunking;
+    use std::fs;
+    use std::sync::Arc;
+
+    /// Creates a fresh directory tree under the system temp dir.
+    fn tree(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("knowledge_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn file_paths(lists: &[Vec<Document>]) -> Vec<String> {
+        lists
+            .iter()
+            .map(|documents| documents[0].meta_data["file_path"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_file_kind_detection() {
+        assert_eq!(FileKind::from_extension("MD"), Some(FileKind::Markdown));
+        assert_eq!(FileKind::from_extension("rs"), Some(FileKind::Code(Language::Rust)));
+        assert_eq!(FileKind::from_extension("png"), None);
+        assert_eq!(FileKind::sniff(b"%PDF-1.7\n"), Some(FileKind::Pdf));
+        assert_eq!(FileKind::sniff(b"  <!DOCTYPE html><p>x"), Some(FileKind::Html));
+        assert_eq!(FileKind::sniff("caf\u{e9}".as_bytes()[..4].as_ref()), Some(FileKind::Text));
+        assert_eq!(FileKind::sniff(b"\x89PNG\r\n\x1a\n\0\0"), None);
+    }
+
+    #[test]
+    fn test_walks_tree_with_globs_and_dispatches_readers() {
+        let root = tree(
+            "walk",
+            &[
+                ("README.md", b"# Project\n\nHello."),
+                ("docs/guide.txt", b"Guide"),
+                ("docs/data.csv", b"a,b\n1,2\n"),
+                ("docs/page", b"<html><body><p>Sniffed</p></body></html>"),
+                ("src/lib.rs", b"fn main() {}\n"),
+                ("target/out.txt", b"build output"),
+                (".git/HEAD", b"ref: main"),
+                ("logo.png", b"\x89PNG\r\n\x1a\n\0\0"),
+            ],
+        );
+        let loader = DirectoryLoader::new(&root).with_exclude(&["target"]).unwrap();
+        let lists: Vec<Vec<Document>> = loader.document_lists_iter().collect();
+        assert_eq!(
+            file_paths(&lists),
+            vec!["README.md", "docs/data.csv", "docs/guide.txt", "docs/page", "src/lib.rs"]
+        );
+        let types: Vec<&str> = lists.iter().map(|d| d[0].meta_data["file_type"].as_str().unwrap()).collect();
+        assert_eq!(types, vec!["markdown", "csv", "text", "html", "code"]);
+        assert_eq!(lists[3][0].content, "Sniffed");
+        assert_eq!(lists[4][0].meta_data["language"], json!("rust"));
+
+        let loader = DirectoryLoader::new(&root).with_include(&["*.txt"]).unwrap().with_skip_hidden(false);
+        let lists: Vec<Vec<Document>> = loader.document_lists_iter().collect();
+        assert_eq!(file_paths(&lists), vec!["docs/guide.txt", "target/out.txt"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_errors_are_reported_per_file() {
+        let root = tree(
+            "errors",
+            &[("a.json", b"{broken"), ("b.txt", b"fine"), ("c.txt", b"\xff\xfe bad")],
+        );
+        let loader = DirectoryLoader::new(&root);
+        let mut lists = loader.document_lists_iter();
+        let loaded: Vec<Vec<Document>> = lists.by_ref().collect();
+        assert_eq!(file_paths(&loaded), vec!["b.txt"]);
+        let failed: Vec<String> = lists.errors().iter().map(|e| loader.relative_path(&e.path)).collect();
+        assert_eq!(failed, vec!["a.json", "c.txt"]);
+        assert!(matches!(lists.errors()[1].error, ReaderError::InvalidEncoding(_)));
+
+        let (knowledge_base, errors) = loader.load();
+        assert_eq!(knowledge_base.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert!(DirectoryLoader::new(&root).with_include(&["[broken"]).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_chunking_uses_detected_language() {
+        let source = b"fn one() {\n    1\n}\n\nfn two() {\n    2\n}\n";
+        let root = tree("chunking", &[("src/numbers.rs", source)]);
+        let strategy = CodeChunking::new(20, 50, 0).unwrap();
+        let loader = DirectoryLoader::new(&root).with_chunking_strategy(Arc::new(strategy));
+        let (knowledge_base, errors) = loader.load();
+        assert!(errors.is_empty());
+        let symbols: Vec<&str> = knowledge_base
+            .iter_documents()
+            .map(|chunk| chunk.meta_data["symbol"].as_str().unwrap())
+            .collect();
+        assert_eq!(symbols, vec!["one", "two"]);
+        assert!(knowledge_base.iter_documents().all(|chunk| chunk.meta_data["file_path"] == json!("src/numbers.rs")));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
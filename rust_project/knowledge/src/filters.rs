@@ -0,0 +1,404 @@
+use crate::agent::{Knowledge, KnowledgeError};
+use crate::schema::{MetaDataIndex, META_DATA_PREFIX};
+use document::MetaDataType;
+use async_trait::async_trait;
+use document::Document;
+use serde_json::{Map, Value as JsonValue};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+/// Metadata filters, by key.
+pub type Filters = HashMap<String, JsonValue>;
+
+/// Most known values listed per key in a model prompt.
+const PROMPT_VALUES: usize = 10;
+
+// Define a custom error type for filter extraction
+#[derive(Debug)]
+pub enum FilterError {
+    Model(String),
+    InvalidResponse(String),
+    Knowledge(KnowledgeError),
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Model(msg) => write!(f, "Model error: {}", msg),
+            FilterError::InvalidResponse(msg) => write!(f, "Invalid filter response: {}", msg),
+            FilterError::Knowledge(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for FilterError {}
+
+impl From<KnowledgeError> for FilterError {
+    fn from(err: KnowledgeError) -> Self {
+        FilterError::Knowledge(err)
+    }
+}
+
+/// Turns a search query into metadata filters, using the keys and values a
+/// knowledge base is known to hold.
+#[async_trait]
+pub trait FilterExtractor: Send + Sync {
+    async fn extract(&self, query: &str, index: &MetaDataIndex) -> Result<Filters, FilterError>;
+}
+
+/// Extracts filters by matching the query against known metadata values.
+///
+/// Explicit `key:value` or `key=value` terms become filters on that key,
+/// with the value parsed as the key's type. Otherwise a known string value
+/// appearing in the query as a phrase, or a known integer appearing as a
+/// word, filters its key; when a key matches several values the filter is a
+/// list of them. A value known under several keys only filters the key named
+/// in the query, and is ignored if none or more than one is.
+#[derive(Debug, Clone, Default)]
+pub struct RuleBasedExtractor;
+
+impl RuleBasedExtractor {
+    pub fn new() -> Self {
+        RuleBasedExtractor
+    }
+
+    /// Extracts filters without awaiting anything.
+    pub fn extract_filters(&self, query: &str, index: &MetaDataIndex) -> Filters {
+        let mut filters = explicit_filters(query, index);
+        let lowered = query.to_lowercase();
+
+        // Which keys each matched value (by its text) was found under.
+        let mut matches: BTreeMap<String, Vec<(&str, &JsonValue)>> = BTreeMap::new();
+        for key in index.filter_keys() {
+            if filters.contains_key(key) {
+                continue;
+            }
+            for value in index.known_values(key) {
+                let text = match value {
+                    JsonValue::String(text) if text.trim().chars().count() > 1 => text.trim().to_lowercase(),
+                    JsonValue::Number(number) if number.is_i64() || number.is_u64() => number.to_string(),
+                    _ => continue,
+                };
+                if contains_phrase(&lowered, &text) {
+                    matches.entry(text).or_default().push((key, value));
+                }
+            }
+        }
+
+        let mut implicit: BTreeMap<&str, Vec<JsonValue>> = BTreeMap::new();
+        for candidates in matches.values() {
+            let chosen: Vec<&(&str, &JsonValue)> = if candidates.len() == 1 {
+                candidates.iter().collect()
+            } else {
+                let named: Vec<(String, &(&str, &JsonValue))> = candidates
+                    .iter()
+                    .map(|candidate| (key_phrase(candidate.0), candidate))
+                    .filter(|(phrase, _)| contains_phrase(&lowered, phrase))
+                    .collect();
+                // "fiscal year" names `fiscal_year`, not `year` as well.
+                named
+                    .iter()
+                    .filter(|(phrase, _)| {
+                        !named
+                            .iter()
+                            .any(|(other, _)| other != phrase && contains_phrase(other, phrase))
+                    })
+                    .map(|(_, candidate)| *candidate)
+                    .collect()
+            };
+            if let [(key, value)] = chosen.as_slice() {
+                implicit.entry(key).or_default().push((*value).clone());
+            }
+        }
+        for (key, mut values) in implicit {
+            let value = if values.len() == 1 { values.remove(0) } else { JsonValue::Array(values) };
+            filters.insert(key.to_string(), value);
+        }
+        filters
+    }
+}
+
+#[async_trait]
+impl FilterExtractor for RuleBasedExtractor {
+    async fn extract(&self, query: &str, index: &MetaDataIndex) -> Result<Filters, FilterError> {
+        Ok(self.extract_filters(query, index))
+    }
+}
+
+/// Collects `key:value` and `key=value` terms naming keys of the index.
+fn explicit_filters(query: &str, index: &MetaDataIndex) -> Filters {
+    let mut filters = Filters::new();
+    for term in query.split_whitespace() {
+        let Some((key, raw)) = term.split_once([':', '=']) else {
+            continue;
+        };
+        let key = key.strip_prefix(META_DATA_PREFIX).unwrap_or(key);
+        let raw = raw.trim_matches(|c: char| c == '"' || c == '\'' || c == ',' || c == '.');
+        let Some(kind) = index.field(key) else {
+            continue;
+        };
+        if let Some(value) = parse_as(kind, raw) {
+            filters.insert(key.to_string(), value);
+        }
+    }
+    filters
+}
+
+/// Parses `raw` as a value of type `kind`.
+fn parse_as(kind: MetaDataType, raw: &str) -> Option<JsonValue> {
+    if raw.is_empty() {
+        return None;
+    }
+    match kind {
+        MetaDataType::Integer => raw.parse::<i64>().ok().map(JsonValue::from),
+        MetaDataType::Float => raw.parse::<f64>().ok().map(JsonValue::from),
+        MetaDataType::Boolean => raw.to_lowercase().parse::<bool>().ok().map(JsonValue::Bool),
+        MetaDataType::String | MetaDataType::DateTime => Some(JsonValue::String(raw.to_string())),
+        MetaDataType::Any => Some(serde_json::from_str(raw).unwrap_or_else(|_| JsonValue::String(raw.to_string()))),
+        MetaDataType::Array | MetaDataType::Object => None,
+    }
+}
+
+/// How a query names `key`: `fiscal_year` as "fiscal year".
+fn key_phrase(key: &str) -> String {
+    key.replace(['_', '-'], " ").to_lowercase()
+}
+
+/// Checks if `needle` appears in `haystack` between word boundaries.
+fn contains_phrase(haystack: &str, needle: &str) -> bool {
+    if needle.is_empty() {
+        return false;
+    }
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// A language model that completes a prompt.
+#[async_trait]
+pub trait LanguageModel: Send + Sync {
+    async fn complete(&self, prompt: &str) -> Result<String, FilterError>;
+}
+
+/// Extracts filters by asking a language model.
+///
+/// The prompt lists the metadata keys with their types and some known
+/// values, and asks for a JSON object of filters. Keys the index does not
+/// know, and values of the wrong type, are dropped from the answer.
+pub struct LlmFilterExtractor<M> {
+    pub model: M,
+}
+
+impl<M: LanguageModel> LlmFilterExtractor<M> {
+    pub fn new(model: M) -> Self {
+        LlmFilterExtractor { model }
+    }
+
+    /// Builds the prompt asking the model for the filters of `query`.
+    pub fn prompt(&self, query: &str, index: &MetaDataIndex) -> String {
+        let mut prompt = String::from(
+            "Extract metadata filters from the search query below.\n\
+             Respond with a JSON object mapping metadata keys to the values to filter on, \
+             using only these keys. Respond with {} if the query implies no filter.\n\nKeys:\n",
+        );
+        for (key, kind) in index.fields() {
+            prompt.push_str(&format!("- {} ({})", key, kind));
+            let values: Vec<String> = index
+                .known_values(key)
+                .iter()
+                .take(PROMPT_VALUES)
+                .map(JsonValue::to_string)
+                .collect();
+            if !values.is_empty() {
+                prompt.push_str(&format!(", e.g. {}", values.join(", ")));
+            }
+            prompt.push('\n');
+        }
+        prompt.push_str(&format!("\nQuery: {}\n", query));
+        prompt
+    }
+}
+
+/// Parses the JSON object in a model's answer, which may be wrapped in prose
+/// or a code fence.
+fn parse_response(response: &str) -> Result<Map<String, JsonValue>, FilterError> {
+    let object = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => return Err(FilterError::InvalidResponse(format!("no JSON object in {:?}", response))),
+    };
+    serde_json::from_str(object).map_err(|e| FilterError::InvalidResponse(e.to_string()))
+}
+
+#[async_trait]
+impl<M: LanguageModel> FilterExtractor for LlmFilterExtractor<M> {
+    async fn extract(&self, query: &str, index: &MetaDataIndex) -> Result<Filters, FilterError> {
+        if index.is_empty() {
+            return Ok(Filters::new());
+        }
+        let response = self.model.complete(&self.prompt(query, index)).await?;
+        let mut filters = Filters::new();
+        for (key, value) in parse_response(&response)? {
+            if value.is_null() {
+                continue;
+            }
+            let candidate = Filters::from([(key, value)]);
+            if index.validate_filters(&candidate).is_ok() {
+                filters.extend(candidate);
+            }
+        }
+        Ok(filters)
+    }
+}
+
+/// Searches `knowledge` with the filters `extractor` finds in `query`.
+/// Explicit `filters` win over extracted ones on the same key.
+pub async fn agentic_search<K, E>(
+    knowledge: &K,
+    extractor: &E,
+    query: &str,
+    num_documents: Option<usize>,
+    filters: Option<Filters>,
+) -> Result<Vec<Document>, FilterError>
+where
+    K: Knowledge + Sync + ?Sized,
+    E: FilterExtractor + ?Sized,
+{
+    let mut effective = extractor.extract(query, knowledge.metadata_index()).await?;
+    effective.extend(filters.unwrap_or_default());
+    let effective = (!effective.is_empty()).then_some(effective);
+    Ok(knowledge.async_search(query, num_documents, effective).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryDb;
+    use crate::{DocumentKnowledgeBase, KnowledgeBase};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    fn index(documents: &[JsonValue]) -> MetaDataIndex {
+        let mut index = MetaDataIndex::inferred();
+        for meta in documents {
+            let meta: HashMap<String, JsonValue> = serde_json::from_value(meta.clone()).unwrap();
+            index.record(&meta).unwrap();
+        }
+        index
+    }
+
+    fn billing_index() -> MetaDataIndex {
+        index(&[
+            json!({"topic": "billing", "year": 2023, "author": "ann"}),
+            json!({"topic": "shipping", "year": 2024, "author": "bo"}),
+        ])
+    }
+
+    #[test]
+    fn test_rule_based_matches_known_values() {
+        let extractor = RuleBasedExtractor::new();
+        let filters = extractor.extract_filters("docs about Billing from 2024", &billing_index());
+        assert_eq!(filters, Filters::from([("topic".to_string(), json!("billing")), ("year".to_string(), json!(2024))]));
+
+        // Words containing a value are not matches.
+        assert!(extractor.extract_filters("rebilling in 20245", &billing_index()).is_empty());
+
+        let filters = extractor.extract_filters("billing and shipping", &billing_index());
+        assert_eq!(filters["topic"], json!(["billing", "shipping"]));
+    }
+
+    #[test]
+    fn test_rule_based_explicit_terms() {
+        let extractor = RuleBasedExtractor::new();
+        let filters = extractor.extract_filters("author:carl year=2022 meta_data.topic:refunds colour:red", &billing_index());
+        assert_eq!(
+            filters,
+            Filters::from([
+                ("author".to_string(), json!("carl")),
+                ("year".to_string(), json!(2022)),
+                ("topic".to_string(), json!("refunds")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rule_based_ambiguous_values_need_the_key() {
+        let index = index(&[json!({"year": 2024, "fiscal_year": 2024})]);
+        let extractor = RuleBasedExtractor::new();
+        assert!(extractor.extract_filters("reports from 2024", &index).is_empty());
+        assert_eq!(
+            extractor.extract_filters("reports for fiscal year 2024", &index),
+            Filters::from([("fiscal_year".to_string(), json!(2024))])
+        );
+    }
+
+    struct FakeModel {
+        answer: String,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl LanguageModel for FakeModel {
+        async fn complete(&self, prompt: &str) -> Result<String, FilterError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.answer.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llm_extractor_keeps_valid_filters() {
+        let model = FakeModel {
+            answer: "Sure:\n```json\n{\"year\": 2024, \"topic\": 7, \"colour\": \"red\", \"author\": null}\n```".to_string(),
+            prompts: Mutex::new(Vec::new()),
+        };
+        let extractor = LlmFilterExtractor::new(model);
+        let filters = extractor.extract("shipping news of 2024", &billing_index()).await.unwrap();
+        assert_eq!(filters, Filters::from([("year".to_string(), json!(2024))]));
+
+        let prompts = extractor.model.prompts.lock().unwrap();
+        assert!(prompts[0].contains("- year (int), e.g. 2023, 2024\n"));
+        assert!(prompts[0].ends_with("Query: shipping news of 2024\n"));
+    }
+
+    #[tokio::test]
+    async fn test_llm_extractor_rejects_prose() {
+        let model = FakeModel {
+            answer: "no filters apply".to_string(),
+            prompts: Mutex::new(Vec::new()),
+        };
+        let result = LlmFilterExtractor::new(model).extract("anything", &billing_index()).await;
+        assert!(matches!(result, Err(FilterError::InvalidResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn test_agentic_search() {
+        let document = |id: &str, content: &str, topic: &str, year: i64| Document {
+            id: Some(id.to_string()),
+            content: content.to_string(),
+            name: Some(id.to_string()),
+            meta_data: HashMap::from([("topic".to_string(), json!(topic)), ("year".to_string(), json!(year))]),
+            ..Default::default()
+        };
+        let source = DocumentKnowledgeBase::new(vec![
+            document("a", "billing docs: invoices", "billing", 2023),
+            document("b", "billing docs: refunds", "billing", 2024),
+            document("c", "shipping docs: carriers", "shipping", 2024),
+        ]);
+        let mut kb = KnowledgeBase::new(source, InMemoryDb::default());
+        kb.load(false, false, true).unwrap();
+
+        let extractor = RuleBasedExtractor::new();
+        let found = agentic_search(&kb, &extractor, "docs about billing from 2024", Some(10), None)
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|d| d.id.as_deref().unwrap()).collect::<Vec<_>>(), vec!["b"]);
+
+        let explicit = Filters::from([("year".to_string(), json!(2023))]);
+        let found = agentic_search(&kb, &extractor, "docs about billing from 2024", Some(10), Some(explicit))
+            .await
+            .unwrap();
+        assert_eq!(found.iter().map(|d| d.id.as_deref().unwrap()).collect::<Vec<_>>(), vec!["a"]);
+    }
+}
@@ -1,8 +1,35 @@
 // Import the Document struct from the document crate
 use document::Document; // Reverted
-
+use pyo3::prelude::*;
+
+pub mod agent;
+pub mod combined;
+pub mod directory;
+pub mod filters;
+pub mod python;
+pub mod schema;
+pub mod stream;
+pub mod sync;
+#[cfg(test)]
+mod testing;
+
+pub use agent::{DocumentSource, Knowledge, KnowledgeBase, KnowledgeError, LoadPolicy};
+pub use combined::{CombinedKnowledgeBase, NamedSource};
+pub use directory::{DirectoryLoader, FileError, FileKind, LoaderError};
+pub use filters::{agentic_search, FilterError, FilterExtractor, Filters, LanguageModel, LlmFilterExtractor, RuleBasedExtractor};
+pub use python::PyKnowledgeBase;
+pub use document::{MetaDataSchema, MetaDataType};
+pub use schema::{MetaDataIndex, SchemaError};
+pub use stream::load_batches;
+pub use sync::{DocumentRecord, SyncError, SyncPlan, SyncReport, SyncState};
+
+/// A knowledge base holding its documents in memory.
+///
+/// This struct is exposed to Python as the `DocumentKnowledgeBase` class.
+#[pyclass(name = "DocumentKnowledgeBase")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct DocumentKnowledgeBase {
+    #[pyo3(get, set)]
     pub documents: Vec<Document>,
 }
 
@@ -50,8 +77,11 @@ mod tests {
             content: content.to_string(),
             name: Some(format!("Doc {}", id)),
             meta_data: HashMap::new(),
+            embedding: None,
             usage: None,
             reranking_score: None,
+            extra: HashMap::new(),
+            schema_version: None,
         }
     }
 
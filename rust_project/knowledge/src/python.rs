@@ -0,0 +1,249 @@
+use crate::agent::{DocumentSource, Knowledge, KnowledgeBase, KnowledgeError};
+use crate::{DirectoryLoader, DocumentKnowledgeBase};
+use document::py_json::py_dict_to_json;
+use document::Document;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use reranker::python::reranker_from_py;
+use reranker::Reranker;
+use std::path::PathBuf;
+use std::sync::Arc;
+use vectordb::PyVectorDb;
+
+impl From<KnowledgeError> for PyErr {
+    fn from(err: KnowledgeError) -> PyErr {
+        match err {
+            KnowledgeError::Chunking(err) => err.into(),
+            KnowledgeError::Schema(err) => PyValueError::new_err(err.to_string()),
+            KnowledgeError::VectorDb(err) => err.into(),
+        }
+    }
+}
+
+#[pymethods]
+impl DocumentKnowledgeBase {
+    /// Creates a new `DocumentKnowledgeBase` instance.
+    ///
+    /// Args:
+    ///     documents (Optional[List[Document]]): The documents of the knowledge base. Defaults to None.
+    #[new]
+    #[pyo3(signature = (documents=None))]
+    fn py_new(documents: Option<Vec<Document>>) -> Self {
+        DocumentKnowledgeBase::new(documents.unwrap_or_default())
+    }
+
+    /// Adds a document to the knowledge base.
+    ///
+    /// Args:
+    ///     document (Document): The document to add.
+    #[pyo3(name = "add_document")]
+    fn add_document_py(&mut self, document: Document) {
+        self.add_document(document);
+    }
+
+    /// Returns the documents as lists of one document each, like Python's `document_lists`.
+    ///
+    /// Returns:
+    ///     List[List[Document]]: One single-document list per document.
+    #[pyo3(name = "document_lists")]
+    fn document_lists_py(&self) -> Vec<Vec<Document>> {
+        self.document_lists_iter().collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Where a Python `KnowledgeBase` reads its documents from.
+enum PySource {
+    Documents(DocumentKnowledgeBase),
+    Directory(DirectoryLoader),
+}
+
+impl DocumentSource for PySource {
+    fn document_lists(&self) -> Box<dyn Iterator<Item = Vec<Document>> + '_> {
+        match self {
+            PySource::Documents(source) => source.document_lists(),
+            PySource::Directory(source) => source.document_lists(),
+        }
+    }
+}
+
+/// A knowledge base that loads documents into a vector db and searches it.
+///
+/// Mirrors Python's `agno.knowledge.agent.AgentKnowledge`, with the vector db
+/// being any Python object implementing the `VectorDb` methods. Search
+/// results go through the reranker, if there is one.
+#[pyclass(name = "KnowledgeBase")]
+pub struct PyKnowledgeBase {
+    inner: KnowledgeBase<PySource, PyVectorDb>,
+    reranker: Option<(Py<PyAny>, Arc<dyn Reranker>)>,
+}
+
+#[pymethods]
+impl PyKnowledgeBase {
+    /// Creates a new `KnowledgeBase` instance.
+    ///
+    /// Args:
+    ///     source (Union[DocumentKnowledgeBase, List[Document], str, os.PathLike]): The documents to load,
+    ///         or a directory whose files are read with the native readers.
+    ///     vector_db (VectorDb): The vector db to load the documents into.
+    ///     num_documents (int): Number of documents a search returns by default. Defaults to 5.
+    ///     optimize_on (Optional[int]): Number of loaded documents after which the vector db is
+    ///         optimized, or None to never optimize. Defaults to 1000.
+    ///     reranker (Optional[Reranker]): Reranks search results: a `LexicalReranker`, an
+    ///         `HttpReranker`, or any object with a `rerank(query, documents)` method. Defaults to None.
+    ///
+    /// Raises:
+    ///     TypeError: If `source` is none of the accepted types.
+    #[new]
+    #[pyo3(signature = (source, vector_db, num_documents=5, optimize_on=Some(1000), reranker=None))]
+    fn py_new(
+        source: &Bound<'_, PyAny>,
+        vector_db: Py<PyAny>,
+        num_documents: usize,
+        optimize_on: Option<usize>,
+        reranker: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let source = if let Ok(source) = source.extract::<DocumentKnowledgeBase>() {
+            PySource::Documents(source)
+        } else if let Ok(documents) = source.extract::<Vec<Document>>() {
+            PySource::Documents(DocumentKnowledgeBase::new(documents))
+        } else if let Ok(root) = source.extract::<PathBuf>() {
+            PySource::Directory(DirectoryLoader::new(root))
+        } else {
+            return Err(PyTypeError::new_err(
+                "source must be a DocumentKnowledgeBase, a list of Documents or a directory path",
+            ));
+        };
+        let inner = KnowledgeBase::new(source, PyVectorDb::new(vector_db))
+            .with_num_documents(num_documents)
+            .with_optimize_on(optimize_on);
+        let reranker = reranker.map(|reranker| (reranker.clone().unbind(), reranker_from_py(reranker)));
+        Ok(PyKnowledgeBase { inner, reranker })
+    }
+
+    /// Number of documents a search returns by default.
+    #[getter]
+    fn num_documents(&self) -> usize {
+        self.inner.num_documents
+    }
+
+    #[setter]
+    fn set_num_documents(&mut self, num_documents: usize) {
+        self.inner.num_documents = num_documents;
+    }
+
+    /// Number of loaded documents after which the vector db is optimized.
+    #[getter]
+    fn optimize_on(&self) -> Option<usize> {
+        self.inner.optimize_on
+    }
+
+    #[setter]
+    fn set_optimize_on(&mut self, optimize_on: Option<usize>) {
+        self.inner.optimize_on = optimize_on;
+    }
+
+    /// The vector db the documents are loaded into.
+    #[getter]
+    fn vector_db(&self, py: Python<'_>) -> PyObject {
+        self.inner.vector_db.object().clone_ref(py)
+    }
+
+    /// The reranker of search results, if any.
+    #[getter]
+    fn reranker(&self, py: Python<'_>) -> Option<PyObject> {
+        self.reranker.as_ref().map(|(object, _)| object.clone_ref(py))
+    }
+
+    /// The metadata keys searches may filter on, sorted.
+    #[getter]
+    fn valid_metadata_filters(&self) -> Vec<String> {
+        self.inner.metadata_index.filter_keys().into_iter().map(str::to_string).collect()
+    }
+
+    /// Returns the documents of the source, one list at a time.
+    ///
+    /// Returns:
+    ///     List[List[Document]]: The document lists of the source.
+    fn document_lists(&self) -> Vec<Vec<Document>> {
+        Knowledge::document_lists(&self.inner).collect()
+    }
+
+    /// Loads the documents into the vector db.
+    ///
+    /// Args:
+    ///     recreate (bool): If True, drops the collection first. Defaults to False.
+    ///     upsert (bool): If True, upserts documents when the vector db supports it. Defaults to False.
+    ///     skip_existing (bool): If True, skips documents the vector db already holds when inserting.
+    ///         Defaults to True.
+    ///
+    /// Returns:
+    ///     int: The number of documents written.
+    ///
+    /// Raises:
+    ///     PyValueError: If a document does not fit the metadata schema.
+    ///     PyRuntimeError: If the vector db fails.
+    #[pyo3(signature = (recreate=false, upsert=false, skip_existing=true))]
+    fn load(&mut self, recreate: bool, upsert: bool, skip_existing: bool) -> PyResult<usize> {
+        Ok(self.inner.load(recreate, upsert, skip_existing)?)
+    }
+
+    /// Learns the metadata keys of the source without loading it.
+    ///
+    /// Raises:
+    ///     PyValueError: If a document does not fit the metadata schema.
+    fn initialize_valid_filters(&mut self) -> PyResult<()> {
+        Ok(self.inner.initialize_metadata_index()?)
+    }
+
+    /// Returns the documents most relevant to a query.
+    ///
+    /// Args:
+    ///     query (str): The search query.
+    ///     num_documents (Optional[int]): Number of documents to return. Defaults to `num_documents`.
+    ///     filters (Optional[Dict[str, Any]]): Metadata filters. Defaults to None.
+    ///
+    /// Returns:
+    ///     List[Document]: The matching documents, reranked with `reranking_score` set if there is
+    ///         a reranker.
+    ///
+    /// Raises:
+    ///     PyValueError: If a filter names an unknown metadata key.
+    ///     PyRuntimeError: If the vector db or the reranker fails.
+    #[pyo3(signature = (query, num_documents=None, filters=None))]
+    fn search(
+        &self,
+        py: Python<'_>,
+        query: &str,
+        num_documents: Option<usize>,
+        filters: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<Document>> {
+        let filters = filters.map(py_dict_to_json).transpose()?;
+        let documents = self.inner.search(query, num_documents, filters)?;
+        match &self.reranker {
+            Some((_, reranker)) => Ok(py.allow_threads(|| reranker.rerank(query, documents))?),
+            None => Ok(documents),
+        }
+    }
+
+    /// Checks if the vector db collection exists.
+    fn exists(&self) -> PyResult<bool> {
+        Ok(self.inner.exists()?)
+    }
+
+    /// Clears the vector db, returning whether it held anything.
+    fn delete(&mut self) -> PyResult<bool> {
+        Ok(self.inner.delete()?)
+    }
+}
+
+/// Adds the classes of the `knowledge` module to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<DocumentKnowledgeBase>()?;
+    m.add_class::<PyKnowledgeBase>()?;
+    Ok(())
+}
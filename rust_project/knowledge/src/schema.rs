@@ -0,0 +1,278 @@
+use document::meta_data::MetaDataError;
+use document::{MetaDataSchema, MetaDataType};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+/// Prefix filters may use to name a metadata key, as in `meta_data.year`.
+pub(crate) const META_DATA_PREFIX: &str = "meta_data.";
+/// Most distinct values remembered per key.
+pub const MAX_KNOWN_VALUES: usize = 64;
+
+/// The narrowest type accepting values of both types.
+fn merge(known: MetaDataType, other: MetaDataType) -> MetaDataType {
+    match (known, other) {
+        (a, b) if a == b => a,
+        (MetaDataType::Integer, MetaDataType::Float) | (MetaDataType::Float, MetaDataType::Integer) => MetaDataType::Float,
+        _ => MetaDataType::Any,
+    }
+}
+
+/// Checks if a key of type `kind` may hold `value`; null fits every type.
+fn accepts(kind: MetaDataType, value: &JsonValue) -> bool {
+    value.is_null() || kind.matches(value)
+}
+
+// Define a custom error type for metadata validation
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// Metadata that breaks a declared schema.
+    MetaData(MetaDataError),
+    TypeMismatch { key: String, expected: MetaDataType, found: JsonValue },
+    UnknownFilterKey { key: String, known: Vec<String> },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::MetaData(err) => write!(f, "{}", err),
+            SchemaError::TypeMismatch { key, expected, found } => {
+                write!(f, "Metadata key '{}' expects a value of type {}, got {}", key, expected, found)
+            }
+            SchemaError::UnknownFilterKey { key, known } if known.is_empty() => {
+                write!(f, "Cannot filter on '{}': the knowledge base has no metadata keys", key)
+            }
+            SchemaError::UnknownFilterKey { key, known } => write!(
+                f,
+                "Cannot filter on '{}': not a metadata key of the knowledge base (known keys: {})",
+                key,
+                known.join(", ")
+            ),
+        }
+    }
+}
+
+impl Error for SchemaError {}
+
+impl From<MetaDataError> for SchemaError {
+    fn from(err: MetaDataError) -> Self {
+        SchemaError::MetaData(err)
+    }
+}
+
+/// The metadata keys of a knowledge base, the types of their values and
+/// some of the values, kept in a [`MetaDataSchema`].
+///
+/// An inferred index (the default) learns keys and types from the
+/// documents it records and never rejects one; a key seen with values of
+/// different types becomes [`MetaDataType::Any`]. A declared index follows
+/// the schema it was given and rejects documents that break it.
+///
+/// Either way, the fields of the schema are the keys searches may filter
+/// on. The index also remembers the first [`MAX_KNOWN_VALUES`] distinct
+/// strings, integers and booleans recorded for each key, for turning
+/// queries into filters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetaDataIndex {
+    schema: MetaDataSchema,
+    values: BTreeMap<String, Vec<JsonValue>>,
+    declared: bool,
+}
+
+impl Default for MetaDataIndex {
+    fn default() -> Self {
+        MetaDataIndex::inferred()
+    }
+}
+
+impl MetaDataIndex {
+    /// Creates an empty index that infers its fields.
+    pub fn inferred() -> Self {
+        MetaDataIndex { schema: MetaDataSchema::new(), values: BTreeMap::new(), declared: false }
+    }
+
+    /// Creates an index holding exactly the fields of `schema`, which
+    /// recorded metadata must match.
+    pub fn declared(schema: MetaDataSchema) -> Self {
+        MetaDataIndex { schema, values: BTreeMap::new(), declared: true }
+    }
+
+    /// Infers an index from the metadata of `documents`.
+    pub fn infer<'a>(documents: impl IntoIterator<Item = &'a document::Document>) -> Self {
+        let mut index = MetaDataIndex::inferred();
+        for document in documents {
+            index.infer_from(&document.meta_data);
+            index.remember_values(&document.meta_data);
+        }
+        index
+    }
+
+    pub fn is_declared(&self) -> bool {
+        self.declared
+    }
+
+    /// The schema of the keys, declared or inferred.
+    pub fn schema(&self) -> &MetaDataSchema {
+        &self.schema
+    }
+
+    /// The type of each key, sorted by key.
+    pub fn fields(&self) -> Vec<(&str, MetaDataType)> {
+        let mut fields: Vec<(&str, MetaDataType)> = self.schema.fields().collect();
+        fields.sort_by_key(|(key, _)| *key);
+        fields
+    }
+
+    /// The type of `key`, if it is in the schema.
+    pub fn field(&self, key: &str) -> Option<MetaDataType> {
+        self.schema.field(key)
+    }
+
+    /// The distinct values recorded for `key`, in the order first seen.
+    pub fn known_values(&self, key: &str) -> &[JsonValue] {
+        self.values.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    /// The keys searches may filter on, sorted.
+    pub fn filter_keys(&self) -> Vec<&str> {
+        self.fields().into_iter().map(|(key, _)| key).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schema.is_empty()
+    }
+
+    fn infer_from(&mut self, meta_data: &HashMap<String, JsonValue>) {
+        for (key, value) in meta_data {
+            let Some(kind) = MetaDataType::of(value) else {
+                continue;
+            };
+            let kind = self.schema.field(key).map_or(kind, |known| merge(known, kind));
+            self.schema = std::mem::take(&mut self.schema).with_optional(key, kind);
+        }
+    }
+
+    fn remember_values(&mut self, meta_data: &HashMap<String, JsonValue>) {
+        for (key, value) in meta_data {
+            if !matches!(
+                MetaDataType::of(value),
+                Some(MetaDataType::String | MetaDataType::Integer | MetaDataType::Boolean)
+            ) {
+                continue;
+            }
+            let known = self.values.entry(key.clone()).or_default();
+            if known.len() < MAX_KNOWN_VALUES && !known.contains(value) {
+                known.push(value.clone());
+            }
+        }
+    }
+
+    /// Checks `meta_data` against a declared schema; any metadata is valid
+    /// for an inferred one.
+    pub fn validate(&self, meta_data: &HashMap<String, JsonValue>) -> Result<(), SchemaError> {
+        if !self.declared {
+            return Ok(());
+        }
+        Ok(self.schema.validate_meta_data(meta_data)?)
+    }
+
+    /// Validates `meta_data`, then learns its values, and its keys if the
+    /// index is inferred.
+    pub fn record(&mut self, meta_data: &HashMap<String, JsonValue>) -> Result<(), SchemaError> {
+        self.validate(meta_data)?;
+        if !self.declared {
+            self.infer_from(meta_data);
+        }
+        self.remember_values(meta_data);
+        Ok(())
+    }
+
+    /// Checks that every filter names a key of the schema, optionally
+    /// prefixed with `meta_data.`, and that its value, or each value of a
+    /// list, fits the key's type.
+    pub fn validate_filters(&self, filters: &HashMap<String, JsonValue>) -> Result<(), SchemaError> {
+        let mut keys: Vec<&String> = filters.keys().collect();
+        keys.sort();
+        for key in keys {
+            let value = &filters[key];
+            let field = key.strip_prefix(META_DATA_PREFIX).unwrap_or(key);
+            let Some(expected) = self.schema.field(field) else {
+                return Err(SchemaError::UnknownFilterKey {
+                    key: key.clone(),
+                    known: self.filter_keys().into_iter().map(str::to_string).collect(),
+                });
+            };
+            let fits = match value {
+                JsonValue::Array(values) if expected != MetaDataType::Array => {
+                    values.iter().all(|value| accepts(expected, value))
+                }
+                value => accepts(expected, value),
+            };
+            if !fits {
+                return Err(SchemaError::TypeMismatch { key: key.clone(), expected, found: value.clone() });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta(pairs: &[(&str, JsonValue)]) -> HashMap<String, JsonValue> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.clone())).collect()
+    }
+
+    #[test]
+    fn test_inferred_schema_merges_types() {
+        let mut index = MetaDataIndex::inferred();
+        index.record(&meta(&[("year", json!(2024)), ("tag", json!("billing"))])).unwrap();
+        index.record(&meta(&[("year", json!(2024.5)), ("tag", json!(3)), ("draft", json!(null))])).unwrap();
+
+        assert_eq!(index.field("year"), Some(MetaDataType::Float));
+        assert_eq!(index.field("tag"), Some(MetaDataType::Any));
+        assert_eq!(index.filter_keys(), vec!["tag", "year"]);
+        assert_eq!(index.known_values("year"), &[json!(2024)]);
+        assert_eq!(index.known_values("tag"), &[json!("billing"), json!(3)]);
+        assert!(index.known_values("draft").is_empty());
+    }
+
+    #[test]
+    fn test_declared_schema_rejects_unknown_keys_and_wrong_types() {
+        let schema = MetaDataSchema::new()
+            .with_optional("year", MetaDataType::Integer)
+            .with_optional("score", MetaDataType::Float)
+            .with_allow_unknown(false);
+        let mut index = MetaDataIndex::declared(schema);
+        index.record(&meta(&[("year", json!(2024)), ("score", json!(1))])).unwrap();
+        assert_eq!(
+            index.record(&meta(&[("year", json!("2024"))])),
+            Err(SchemaError::MetaData(MetaDataError::Schema(vec!["year is a str, expected int".to_string()])))
+        );
+        assert_eq!(
+            index.record(&meta(&[("author", json!("ann"))])),
+            Err(SchemaError::MetaData(MetaDataError::Schema(vec!["author is not in the schema".to_string()])))
+        );
+        assert_eq!(index.filter_keys(), vec!["score", "year"]);
+    }
+
+    #[test]
+    fn test_validate_filters() {
+        let index = MetaDataIndex::declared(MetaDataSchema::new().with_optional("year", MetaDataType::Integer));
+        assert!(index.validate_filters(&meta(&[("year", json!(2024))])).is_ok());
+        assert!(index.validate_filters(&meta(&[("meta_data.year", json!([2023, 2024]))])).is_ok());
+        assert!(matches!(
+            index.validate_filters(&meta(&[("year", json!(["2024"]))])),
+            Err(SchemaError::TypeMismatch { .. })
+        ));
+
+        let err = index.validate_filters(&meta(&[("topic", json!("billing"))])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Cannot filter on 'topic': not a metadata key of the knowledge base (known keys: year)"
+        );
+    }
+}
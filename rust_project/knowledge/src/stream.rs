@@ -0,0 +1,281 @@
+use crate::agent::{async_load_documents, chunk_documents};
+use crate::{DirectoryLoader, DocumentKnowledgeBase, DocumentSource, FileError, KnowledgeBase, KnowledgeError};
+use document::Document;
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::HashSet;
+use std::pin::pin;
+use tokio::sync::mpsc;
+use vectordb::{VectorDb, VectorDbError};
+
+/// Number of batches a directory stream reads ahead of its consumer.
+const READ_AHEAD_BATCHES: usize = 2;
+
+impl DocumentKnowledgeBase {
+    /// Streams the documents in batches of up to `batch_size` (at least one),
+    /// borrowing them from the knowledge base.
+    pub fn document_batches(&self, batch_size: usize) -> impl Stream<Item = &[Document]> + '_ {
+        stream::iter(self.documents.chunks(batch_size.max(1)))
+    }
+
+    /// Streams the documents in batches of up to `batch_size` (at least one),
+    /// moving them out of the knowledge base.
+    pub fn into_document_batches(self, batch_size: usize) -> impl Stream<Item = Vec<Document>> {
+        let batch_size = batch_size.max(1);
+        let mut documents = self.documents.into_iter();
+        stream::iter(std::iter::from_fn(move || {
+            let batch: Vec<Document> = documents.by_ref().take(batch_size).collect();
+            (!batch.is_empty()).then_some(batch)
+        }))
+    }
+}
+
+impl DirectoryLoader {
+    /// Streams the documents below the root in batches of up to
+    /// `batch_size` (at least one), yielding each failed file as an error.
+    ///
+    /// Files are read on a blocking thread that stays a few batches ahead of
+    /// the consumer, so writing one batch overlaps with reading the next.
+    /// Must be called from within a Tokio runtime.
+    pub fn document_batches(&self, batch_size: usize) -> impl Stream<Item = Result<Vec<Document>, FileError>> + Send {
+        let batch_size = batch_size.max(1);
+        let loader = self.clone();
+        let (sender, receiver) = mpsc::channel(READ_AHEAD_BATCHES);
+        tokio::task::spawn_blocking(move || {
+            let mut batch = Vec::with_capacity(batch_size);
+            for path in loader.files() {
+                let loaded = path.and_then(|path| loader.load_file(&path));
+                match loaded {
+                    Ok(documents) => {
+                        for document in documents {
+                            batch.push(document);
+                            if batch.len() == batch_size {
+                                let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                                if sender.blocking_send(Ok(full)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        if sender.blocking_send(Err(err)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            if !batch.is_empty() {
+                let _ = sender.blocking_send(Ok(batch));
+            }
+        });
+        stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+    }
+}
+
+impl<S: DocumentSource, D: VectorDb> KnowledgeBase<S, D> {
+    /// Loads every batch of `batches` into the vector db as it arrives,
+    /// returning how many documents were written.
+    ///
+    /// Batches go through the same steps as [`Knowledge::load`](crate::Knowledge::load):
+    /// they are chunked with the chunking strategy, their metadata is
+    /// recorded in the metadata index, and the vector db is optimized
+    /// afterwards once `optimize_on` documents were written. The collection
+    /// is created if missing. The source of the knowledge base is not read.
+    pub async fn load_batches<T, B>(&mut self, batches: T, upsert: bool, skip_existing: bool) -> Result<usize, KnowledgeError>
+    where
+        T: Stream<Item = B>,
+        B: Into<Vec<Document>>,
+    {
+        if !self.vector_db.async_db_exists().await? {
+            self.vector_db.async_create().await?;
+        }
+        let mut loaded = 0;
+        let mut batches = pin!(batches);
+        while let Some(batch) = batches.next().await {
+            let documents = chunk_documents(self.chunking_strategy.as_ref(), batch.into())?;
+            for document in &documents {
+                self.metadata_index.record(&document.meta_data)?;
+            }
+            loaded += async_load_documents(&mut self.vector_db, documents, upsert, skip_existing).await?;
+        }
+        if self.optimize_on.is_some_and(|optimize_on| loaded >= optimize_on) {
+            self.vector_db.async_optimize().await?;
+        }
+        Ok(loaded)
+    }
+}
+
+/// Writes every batch of `batches` to the vector db as it arrives, returning
+/// how many documents were written.
+///
+/// Batches are upserted when `upsert` is set and the vector db supports it;
+/// otherwise they are inserted, skipping documents the vector db already
+/// holds if `skip_existing` is set.
+///
+/// This is a raw vector db writer: documents are written as they are, with
+/// no chunking, metadata index or optimization. Use
+/// [`KnowledgeBase::load_batches`] for those.
+pub async fn load_batches<D, S, B>(
+    vector_db: &mut D,
+    batches: S,
+    upsert: bool,
+    skip_existing: bool,
+) -> Result<usize, VectorDbError>
+where
+    D: VectorDb + ?Sized,
+    S: Stream<Item = B>,
+    B: AsRef<[Document]>,
+{
+    let upsert = upsert && vector_db.upsert_available();
+    let mut loaded = 0;
+    let mut batches = pin!(batches);
+    while let Some(batch) = batches.next().await {
+        let batch = batch.as_ref();
+        if upsert {
+            vector_db.async_upsert(batch, None).await?;
+            loaded += batch.len();
+        } else if skip_existing {
+            loaded += async_insert_new_documents(vector_db, batch).await?;
+        } else {
+            vector_db.async_insert(batch, None).await?;
+            loaded += batch.len();
+        }
+    }
+    Ok(loaded)
+}
+
+/// Inserts the documents of `batch` the vector db does not hold yet, and
+/// not repeating earlier content, as [`crate::agent::async_filter_existing_documents`]
+/// would keep them. Each run of new documents is inserted as one slice of
+/// `batch`, so nothing is cloned.
+async fn async_insert_new_documents<D: VectorDb + ?Sized>(vector_db: &mut D, batch: &[Document]) -> Result<usize, VectorDbError> {
+    let mut seen_content = HashSet::new();
+    let mut is_new = Vec::with_capacity(batch.len());
+    for document in batch {
+        let new = !seen_content.contains(&document.content) && !vector_db.async_doc_exists(document).await?;
+        if new {
+            seen_content.insert(&document.content);
+        }
+        is_new.push(new);
+    }
+    let mut inserted = 0;
+    let mut start = 0;
+    while start < batch.len() {
+        if !is_new[start] {
+            start += 1;
+            continue;
+        }
+        let end = is_new[start..].iter().position(|new| !new).map_or(batch.len(), |run| start + run);
+        vector_db.async_insert(&batch[start..end], None).await?;
+        inserted += end - start;
+        start = end;
+    }
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{doc, with_meta_data, InMemoryDb};
+    use crate::{MetaDataSchema, MetaDataType};
+    use document::chunking::{ChunkingError, ChunkingStrategy};
+    use serde_json::{json, Value as JsonValue};
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn ids(batch: &[Document]) -> Vec<&str> {
+        batch.iter().filter_map(|d| d.id.as_deref()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_knowledge_base_batches() {
+        let kb = DocumentKnowledgeBase::new(vec![
+            doc("a", "content of a"),
+            doc("b", "content of b"),
+            doc("c", "content of c"),
+        ]);
+        let borrowed: Vec<&[Document]> = kb.document_batches(2).collect().await;
+        assert_eq!(borrowed.iter().map(|b| ids(b)).collect::<Vec<_>>(), vec![vec!["a", "b"], vec!["c"]]);
+
+        let moved: Vec<Vec<Document>> = kb.clone().into_document_batches(0).collect().await;
+        assert_eq!(moved.len(), 3);
+        assert_eq!(moved[2], vec![doc("c", "content of c")]);
+    }
+
+    #[tokio::test]
+    async fn test_load_batches_skips_existing() {
+        let kb = DocumentKnowledgeBase::new(vec![
+            doc("a", "content of a"),
+            doc("b", "content of b"),
+            doc("c", "content of c"),
+        ]);
+        let mut db = InMemoryDb::default();
+        db.insert(&[doc("b", "content of b")], None).unwrap();
+
+        assert_eq!(load_batches(&mut db, kb.document_batches(2), false, true).await.unwrap(), 2);
+        assert_eq!(db.log, vec!["insert b", "insert a", "insert c"]);
+        assert_eq!(db.blocking_lookups.load(Ordering::Relaxed), 0);
+
+        let mut db = InMemoryDb::with_upsert();
+        assert_eq!(load_batches(&mut db, kb.into_document_batches(2), true, true).await.unwrap(), 3);
+        assert_eq!(db.log, vec!["upsert a", "upsert b", "upsert c"]);
+    }
+
+    #[tokio::test]
+    async fn test_knowledge_base_load_batches() {
+        struct SplitWords;
+        impl ChunkingStrategy for SplitWords {
+            fn chunk(&self, document: &Document) -> Result<Vec<Document>, ChunkingError> {
+                Ok(document
+                    .content
+                    .split(' ')
+                    .map(|word| Document { id: Some(word.to_string()), content: word.to_string(), ..document.clone() })
+                    .collect())
+            }
+        }
+        let tagged = |id: &str, topic: JsonValue| with_meta_data(doc(id, &format!("content of {}", id)), "topic", topic);
+
+        let mut kb = KnowledgeBase::new(DocumentKnowledgeBase::new(Vec::new()), InMemoryDb::default())
+            .with_chunking_strategy(Arc::new(SplitWords))
+            .with_optimize_on(Some(4))
+            .with_metadata_schema(MetaDataSchema::new().with_optional("topic", MetaDataType::String));
+        let batches = vec![vec![tagged("a", json!("rust"))], vec![tagged("b", json!("go"))]];
+        assert_eq!(kb.load_batches(stream::iter(batches.clone()), false, true).await.unwrap(), 4);
+        assert!(kb.vector_db.created);
+        assert_eq!(kb.vector_db.log, vec!["insert content", "insert of", "insert a", "insert b"]);
+        assert_eq!(kb.metadata_index.filter_keys(), vec!["topic"]);
+        assert_eq!(kb.vector_db.optimized, 1);
+        assert_eq!(kb.vector_db.blocking_lookups.load(Ordering::Relaxed), 0);
+
+        // Nothing is written twice, and documents that break the schema fail the load.
+        assert_eq!(kb.load_batches(stream::iter(batches), false, true).await.unwrap(), 0);
+        let bad = vec![vec![tagged("c", json!(1))]];
+        let err = kb.load_batches(stream::iter(bad), false, true).await.unwrap_err();
+        assert!(matches!(err, KnowledgeError::Schema(_)), "{}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_directory_batches() {
+        let root: PathBuf = std::env::temp_dir().join(format!("knowledge_stream_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(root.join(name), format!("text of {}", name)).unwrap();
+        }
+        fs::write(root.join("bad.json"), "{ not json").unwrap();
+
+        let loader = DirectoryLoader::new(&root);
+        let items: Vec<_> = loader.document_batches(2).collect().await;
+        let errors = items.iter().filter(|item| item.is_err()).count();
+        let sizes: Vec<usize> = items.iter().filter_map(|item| item.as_ref().ok()).map(Vec::len).collect();
+        assert_eq!(errors, 1);
+        assert_eq!(sizes, vec![2, 1]);
+
+        let mut db = InMemoryDb::default();
+        let batches = loader.document_batches(2).filter_map(|item| async move { item.ok() });
+        assert_eq!(load_batches(&mut db, batches, false, true).await.unwrap(), 3);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,444 @@
+use crate::DocumentKnowledgeBase;
+use document::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use vectordb::{VectorDb, VectorDbError};
+
+/// `meta_data` key holding the path of the file a document was read from.
+pub const SOURCE_PATH_KEY: &str = "source_path";
+/// `meta_data` key holding the source's modification time, in milliseconds
+/// since the Unix epoch.
+pub const MODIFIED_AT_KEY: &str = "modified_at";
+/// `meta_data` key holding the SHA-256 of the document content.
+pub const CONTENT_HASH_KEY: &str = "content_hash";
+
+/// `meta_data` keys recording where a chunk sits in its source. Editing one
+/// part of a file moves every later chunk, so these are left out of the
+/// metadata a sync compares; the db keeps the values of the last write.
+pub const POSITIONAL_KEYS: [&str; 5] = ["provenance", "chunk", "chunk_size", "start_line", "end_line"];
+
+// Define a custom error type for sync operations
+#[derive(Debug)]
+pub enum SyncError {
+    Io(String),
+    InvalidState(String),
+    VectorDb(VectorDbError),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Io(msg) => write!(f, "I/O error: {}", msg),
+            SyncError::InvalidState(msg) => write!(f, "Invalid sync state: {}", msg),
+            SyncError::VectorDb(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for SyncError {}
+
+impl From<io::Error> for SyncError {
+    fn from(err: io::Error) -> Self {
+        SyncError::Io(err.to_string())
+    }
+}
+
+impl From<VectorDbError> for SyncError {
+    fn from(err: VectorDbError) -> Self {
+        SyncError::VectorDb(err)
+    }
+}
+
+/// What was last written to the vector db for one document.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DocumentRecord {
+    pub source_path: String,
+    pub modified_at: Option<u64>,
+    pub content_hash: String,
+    pub metadata_hash: String,
+}
+
+/// The documents a vector db holds, by id, as of the last sync.
+///
+/// Kept between runs (see [`SyncState::load`] and [`SyncState::save`]) so
+/// that a sync only has to write what changed since.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    pub documents: BTreeMap<String, DocumentRecord>,
+}
+
+impl SyncState {
+    /// Loads the state saved at `path`, or an empty state if there is none.
+    pub fn load(path: &Path) -> Result<Self, SyncError> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| SyncError::InvalidState(e.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(SyncState::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Saves the state to `path`, replacing it atomically.
+    pub fn save(&self, path: &Path) -> Result<(), SyncError> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| SyncError::InvalidState(e.to_string()))?;
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
+
+/// The delta between a knowledge base and a vector db.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncPlan {
+    /// Documents the vector db does not have yet.
+    pub added: Vec<Document>,
+    /// Documents whose metadata differ from what was written. A document
+    /// whose content changed gets a new id, so it is added instead and its
+    /// old version removed.
+    pub changed: Vec<Document>,
+    /// Ids of documents whose source no longer produces them.
+    pub removed: Vec<String>,
+    /// Ids of documents that are already up to date.
+    pub unchanged: Vec<String>,
+}
+
+impl SyncPlan {
+    /// Keeps the documents of `sources` out of `removed`, e.g. for files
+    /// that failed to load this time.
+    pub fn keep_sources(&mut self, state: &SyncState, sources: &[&str]) {
+        self.removed.retain(|id| {
+            state
+                .documents
+                .get(id)
+                .is_none_or(|record| !sources.contains(&record.source_path.as_str()))
+        });
+    }
+}
+
+/// How many documents a sync wrote, deleted or left alone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Hashes the metadata a caller controls, leaving out the sync and
+/// positional keys.
+fn metadata_hash(document: &Document) -> String {
+    let metadata: BTreeMap<&String, &JsonValue> = document
+        .meta_data
+        .iter()
+        .filter(|(key, _)| {
+            let key = key.as_str();
+            ![SOURCE_PATH_KEY, MODIFIED_AT_KEY, CONTENT_HASH_KEY].contains(&key) && !POSITIONAL_KEYS.contains(&key)
+        })
+        .collect();
+    sha256(serde_json::to_string(&metadata).unwrap_or_default().as_bytes())
+}
+
+fn record(document: &Document) -> DocumentRecord {
+    let text = |key: &str| document.meta_data.get(key).and_then(JsonValue::as_str).map(str::to_string);
+    DocumentRecord {
+        source_path: text(SOURCE_PATH_KEY).unwrap_or_default(),
+        modified_at: document.meta_data.get(MODIFIED_AT_KEY).and_then(JsonValue::as_u64),
+        content_hash: text(CONTENT_HASH_KEY).unwrap_or_else(|| sha256(document.content.as_bytes())),
+        metadata_hash: metadata_hash(document),
+    }
+}
+
+fn document_id(document: &Document) -> Result<&str, SyncError> {
+    document
+        .id
+        .as_deref()
+        .ok_or_else(|| SyncError::InvalidState("document has no id; call track_sources first".to_string()))
+}
+
+impl DocumentKnowledgeBase {
+    /// Stamps every document with what a sync tracks.
+    ///
+    /// The source path comes from `meta_data["source_path"]`, else the
+    /// `file_path` recorded by [`DirectoryLoader`](crate::DirectoryLoader),
+    /// else the document name. Documents get the SHA-256 of their content
+    /// under `content_hash`, and an id made of the source path and the start
+    /// of that hash: `{source_path}#{hash}`, with `-{n}` appended to the
+    /// `n`-th repeat of the same content within a source. Ids do not depend
+    /// on where a document sits in its source, so editing one part of a file
+    /// leaves the ids of the other parts alone.
+    pub fn track_sources(&mut self) {
+        let mut counters: HashMap<(String, String), usize> = HashMap::new();
+        for document in &mut self.documents {
+            let source_path = [SOURCE_PATH_KEY, "file_path"]
+                .iter()
+                .find_map(|key| document.meta_data.get(*key).and_then(JsonValue::as_str))
+                .map(str::to_string)
+                .or_else(|| document.name.clone())
+                .unwrap_or_default();
+            let hash = sha256(document.content.as_bytes());
+            let counter = counters.entry((source_path.clone(), hash.clone())).or_default();
+            *counter += 1;
+            document.id = Some(match *counter {
+                1 => format!("{}#{}", source_path, &hash[..16]),
+                n => format!("{}#{}-{}", source_path, &hash[..16], n),
+            });
+            document.meta_data.insert(SOURCE_PATH_KEY.to_string(), json!(source_path));
+            document.meta_data.insert(CONTENT_HASH_KEY.to_string(), json!(hash));
+        }
+    }
+
+    /// Computes what has to change in `db` to match this knowledge base.
+    ///
+    /// Documents missing from `state` are checked against the vector db, so
+    /// a lost state file leads to upserts rather than duplicate inserts.
+    pub fn plan_sync<D: VectorDb + ?Sized>(&self, state: &SyncState, db: &D) -> Result<SyncPlan, SyncError> {
+        let mut plan = SyncPlan::default();
+        let mut seen = HashSet::new();
+        for document in &self.documents {
+            let id = document_id(document)?;
+            if !seen.insert(id) {
+                return Err(SyncError::InvalidState(format!("duplicate document id {}", id)));
+            }
+            let current = record(document);
+            match state.documents.get(id) {
+                Some(previous)
+                    if previous.content_hash == current.content_hash
+                        && previous.metadata_hash == current.metadata_hash =>
+                {
+                    plan.unchanged.push(id.to_string())
+                }
+                Some(_) => plan.changed.push(document.clone()),
+                None if db.id_exists(id)? => plan.changed.push(document.clone()),
+                None => plan.added.push(document.clone()),
+            }
+        }
+        plan.removed = state
+            .documents
+            .keys()
+            .filter(|id| !seen.contains(id.as_str()))
+            .cloned()
+            .collect();
+        Ok(plan)
+    }
+
+    /// Applies a plan to `db`: deletes removed documents, upserts changed
+    /// ones (or deletes and re-inserts them when the db has no upsert) and
+    /// inserts new ones. `state` is updated after every step, so it stays
+    /// accurate if a later step fails.
+    pub fn apply_sync<D: VectorDb + ?Sized>(
+        &self,
+        plan: SyncPlan,
+        db: &mut D,
+        state: &mut SyncState,
+    ) -> Result<SyncReport, SyncError> {
+        for id in &plan.removed {
+            db.delete_by_id(id)?;
+            state.documents.remove(id);
+        }
+        if !plan.changed.is_empty() {
+            if db.upsert_available() {
+                db.upsert(&plan.changed, None)?;
+            } else {
+                for document in &plan.changed {
+                    db.delete_by_id(document_id(document)?)?;
+                }
+                db.insert(&plan.changed, None)?;
+            }
+            for document in &plan.changed {
+                state.documents.insert(document_id(document)?.to_string(), record(document));
+            }
+        }
+        if !plan.added.is_empty() {
+            db.insert(&plan.added, None)?;
+            for document in &plan.added {
+                state.documents.insert(document_id(document)?.to_string(), record(document));
+            }
+        }
+        // Unchanged documents may still have a newer modification time.
+        for document in &self.documents {
+            if let Some(previous) = state.documents.get_mut(document_id(document)?) {
+                previous.modified_at = record(document).modified_at;
+            }
+        }
+        Ok(SyncReport {
+            added: plan.added.len(),
+            changed: plan.changed.len(),
+            removed: plan.removed.len(),
+            unchanged: plan.unchanged.len(),
+        })
+    }
+
+    /// Brings `db` in line with this knowledge base, writing only the delta
+    /// since `state` was recorded.
+    ///
+    /// Documents whose source is no longer in the knowledge base are
+    /// deleted; use [`plan_sync`](Self::plan_sync),
+    /// [`SyncPlan::keep_sources`] and [`apply_sync`](Self::apply_sync) to
+    /// spare sources that only failed to load.
+    pub fn sync<D: VectorDb + ?Sized>(&mut self, db: &mut D, state: &mut SyncState) -> Result<SyncReport, SyncError> {
+        self.track_sources();
+        let plan = self.plan_sync(state, db)?;
+        self.apply_sync(plan, db, state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::InMemoryDb;
+    use crate::DirectoryLoader;
+    use document::chunking::CodeChunking;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    fn write_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("knowledge_sync_{}_{}", name, std::process::id()));
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    /// The id `track_sources` gives the first document of `source` with `content`.
+    fn id(source: &str, content: &str) -> String {
+        format!("{}#{}", source, &sha256(content.as_bytes())[..16])
+    }
+
+    fn load(root: &Path) -> DocumentKnowledgeBase {
+        let (knowledge_base, errors) = DirectoryLoader::new(root).load();
+        assert!(errors.is_empty());
+        knowledge_base
+    }
+
+    #[test]
+    fn test_sync_applies_only_the_delta() {
+        let root = write_tree("delta", &[("a.txt", "alpha"), ("b.txt", "beta"), ("c.txt", "gamma")]);
+        let mut db = InMemoryDb::with_upsert();
+        let mut state = SyncState::default();
+
+        let report = load(&root).sync(&mut db, &mut state).unwrap();
+        assert_eq!(report, SyncReport { added: 3, ..Default::default() });
+        let (a, b, c) = (id("a.txt", "alpha"), id("b.txt", "beta"), id("c.txt", "gamma"));
+        assert_eq!(db.documents.keys().collect::<Vec<_>>(), vec![&a, &b, &c]);
+        assert!(state.documents[&a].modified_at.is_some());
+
+        // An edited document is a new document replacing the old one.
+        fs::write(root.join("b.txt"), "beta, revised").unwrap();
+        fs::remove_file(root.join("c.txt")).unwrap();
+        fs::write(root.join("d.txt"), "delta").unwrap();
+        db.log.clear();
+        let report = load(&root).sync(&mut db, &mut state).unwrap();
+        assert_eq!(report, SyncReport { added: 2, changed: 0, removed: 2, unchanged: 1 });
+        let revised = id("b.txt", "beta, revised");
+        let log = [
+            format!("delete {}", b),
+            format!("delete {}", c),
+            format!("insert {}", revised),
+            format!("insert {}", id("d.txt", "delta")),
+        ];
+        assert_eq!(db.log, log);
+        assert_eq!(db.documents[&revised].content, "beta, revised");
+
+        db.log.clear();
+        let report = load(&root).sync(&mut db, &mut state).unwrap();
+        assert_eq!(report, SyncReport { unchanged: 3, ..Default::default() });
+        assert!(db.log.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_edit_at_the_top_of_a_chunked_file_writes_one_document() {
+        let functions: Vec<String> = (1..=5)
+            .map(|n| format!("/// Returns {}.\nfn value_{}() -> u32 {{\n    {}\n}}\n", n, n, n))
+            .collect();
+        let root = write_tree("edit_top", &[("src/values.rs", &functions.join("\n"))]);
+        let load = |root: &Path| {
+            let strategy = CodeChunking::new(60, 50, 5).unwrap();
+            let (knowledge_base, errors) = DirectoryLoader::new(root).with_chunking_strategy(Arc::new(strategy)).load();
+            assert!(errors.is_empty());
+            knowledge_base
+        };
+        let mut db = InMemoryDb::with_upsert();
+        let mut state = SyncState::default();
+        let report = load(&root).sync(&mut db, &mut state).unwrap();
+        assert_eq!(report, SyncReport { added: 5, ..Default::default() });
+
+        // Every chunk moves down, shifting its offsets, line numbers and chunk number.
+        let added = "/// Returns 0.\nfn value_0() -> u32 {\n    0\n}\n\n";
+        fs::write(root.join("src/values.rs"), format!("{}{}", added, functions.join("\n"))).unwrap();
+        db.log.clear();
+        let report = load(&root).sync(&mut db, &mut state).unwrap();
+        assert_eq!(report, SyncReport { added: 1, unchanged: 5, ..Default::default() });
+        assert_eq!(db.log, [format!("insert {}", id("src/values.rs", added.trim_end()))]);
+
+        // Repeated content within a source gets numbered ids.
+        let mut knowledge_base = load(&root);
+        knowledge_base.documents.push(knowledge_base.documents[0].clone());
+        knowledge_base.track_sources();
+        let first = id("src/values.rs", added.trim_end());
+        assert_eq!(knowledge_base.documents[0].id, Some(first.clone()));
+        assert_eq!(knowledge_base.documents[6].id, Some(format!("{}-2", first)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_changes_and_db_without_upsert() {
+        let root = write_tree("metadata", &[("notes.txt", "hello")]);
+        let mut db = InMemoryDb::default();
+        let mut state = SyncState::default();
+        load(&root).sync(&mut db, &mut state).unwrap();
+
+        let mut knowledge_base = load(&root);
+        knowledge_base.documents[0].meta_data.insert("team".to_string(), json!("ops"));
+        db.log.clear();
+        let report = knowledge_base.sync(&mut db, &mut state).unwrap();
+        assert_eq!(report.changed, 1);
+        let notes = id("notes.txt", "hello");
+        assert_eq!(db.log, [format!("delete {}", notes), format!("insert {}", notes)]);
+        assert_eq!(db.documents[&notes].meta_data["team"], json!("ops"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_lost_state_and_kept_sources() {
+        let root = write_tree("state", &[("a.txt", "alpha"), ("b.txt", "beta")]);
+        let mut db = InMemoryDb::with_upsert();
+        let mut state = SyncState::default();
+        load(&root).sync(&mut db, &mut state).unwrap();
+
+        let path = std::env::temp_dir().join(format!("knowledge_sync_state_{}.json", std::process::id()));
+        state.save(&path).unwrap();
+        assert_eq!(SyncState::load(&path).unwrap(), state);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(SyncState::load(&root.join("missing.json")).unwrap(), SyncState::default());
+
+        // Without its state, a sync upserts what the db already holds.
+        let mut fresh = SyncState::default();
+        let report = load(&root).sync(&mut db, &mut fresh).unwrap();
+        assert_eq!(report.changed, 2);
+        assert_eq!(fresh, state);
+
+        // A source that failed to load is not deleted.
+        let mut knowledge_base = load(&root);
+        knowledge_base.documents.retain(|d| d.meta_data["file_path"] == json!("a.txt"));
+        knowledge_base.track_sources();
+        let mut plan = knowledge_base.plan_sync(&state, &db).unwrap();
+        assert_eq!(plan.removed, vec![id("b.txt", "beta")]);
+        plan.keep_sources(&state, &["b.txt"]);
+        assert!(plan.removed.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
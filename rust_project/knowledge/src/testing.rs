@@ -0,0 +1,175 @@
+//! Documents and an in-memory `VectorDb` for the knowledge base tests.
+
+use async_trait::async_trait;
+use document::Document;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use vectordb::{VectorDb, VectorDbError};
+
+test_support::document_fixtures!(document::Document);
+
+/// Stores documents by id and records every write it receives, and how
+/// often `doc_exists` was called rather than `async_doc_exists`. Search
+/// ranks documents by how many query words they contain.
+#[derive(Debug, Default)]
+pub struct InMemoryDb {
+    pub documents: BTreeMap<String, Document>,
+    pub upsert: bool,
+    pub log: Vec<String>,
+    pub optimized: usize,
+    pub created: bool,
+    pub blocking_lookups: AtomicUsize,
+}
+
+impl InMemoryDb {
+    pub fn with_upsert() -> Self {
+        InMemoryDb {
+            upsert: true,
+            ..Default::default()
+        }
+    }
+
+    fn key(document: &Document) -> String {
+        document.id.clone().or_else(|| document.name.clone()).unwrap_or_default()
+    }
+
+    fn matches(document: &Document, filters: &Option<HashMap<String, JsonValue>>) -> bool {
+        filters.as_ref().is_none_or(|filters| {
+            filters
+                .iter()
+                .all(|(key, value)| document.meta_data.get(key) == Some(value))
+        })
+    }
+}
+
+#[async_trait]
+impl VectorDb for InMemoryDb {
+    fn create(&mut self) -> Result<(), VectorDbError> {
+        self.created = true;
+        Ok(())
+    }
+    async fn async_create(&mut self) -> Result<(), VectorDbError> {
+        self.create()
+    }
+
+    fn doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.blocking_lookups.fetch_add(1, Ordering::Relaxed);
+        Ok(self.documents.values().any(|stored| stored.content == document.content))
+    }
+    async fn async_doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        Ok(self.documents.values().any(|stored| stored.content == document.content))
+    }
+
+    fn name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        Ok(self.documents.values().any(|stored| stored.name.as_deref() == Some(name)))
+    }
+    async fn async_name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.name_exists(name)
+    }
+
+    fn id_exists(&self, id: &str) -> Result<bool, VectorDbError> {
+        Ok(self.documents.contains_key(id))
+    }
+
+    fn delete_by_id(&mut self, id: &str) -> Result<bool, VectorDbError> {
+        self.log.push(format!("delete {}", id));
+        Ok(self.documents.remove(id).is_some())
+    }
+
+    fn insert(&mut self, documents: &[Document], _filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        for document in documents {
+            let key = Self::key(document);
+            if self.documents.contains_key(&key) {
+                return Err(VectorDbError::OperationFailed(format!("duplicate id {}", key)));
+            }
+            self.log.push(format!("insert {}", key));
+            self.documents.insert(key, document.clone());
+        }
+        Ok(())
+    }
+    async fn async_insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.insert(documents, filters)
+    }
+
+    fn upsert_available(&self) -> bool {
+        self.upsert
+    }
+
+    fn upsert(&mut self, documents: &[Document], _filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        if !self.upsert {
+            return Err(VectorDbError::NotImplemented);
+        }
+        for document in documents {
+            let key = Self::key(document);
+            self.log.push(format!("upsert {}", key));
+            self.documents.insert(key, document.clone());
+        }
+        Ok(())
+    }
+    async fn async_upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.upsert(documents, filters)
+    }
+
+    fn search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut scored: Vec<(usize, &Document)> = self
+            .documents
+            .values()
+            .filter(|document| Self::matches(document, &filters))
+            .map(|document| {
+                let content = document.content.to_lowercase();
+                (words.iter().filter(|word| content.contains(word.as_str())).count(), document)
+            })
+            .filter(|(score, _)| *score > 0)
+            .collect();
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        Ok(scored.into_iter().take(limit as usize).map(|(_, document)| document.clone()).collect())
+    }
+    async fn async_search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, filters)
+    }
+
+    fn vector_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn keyword_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn hybrid_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.documents.clear();
+        self.created = false;
+        self.log.push("drop".to_string());
+        Ok(())
+    }
+    async fn async_drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.drop_db()
+    }
+
+    fn db_exists(&self) -> Result<bool, VectorDbError> {
+        Ok(self.created)
+    }
+    async fn async_db_exists(&self) -> Result<bool, VectorDbError> {
+        self.db_exists()
+    }
+
+    fn optimize(&mut self) -> Result<(), VectorDbError> {
+        self.optimized += 1;
+        Ok(())
+    }
+    async fn async_optimize(&mut self) -> Result<(), VectorDbError> {
+        self.optimize()
+    }
+
+    fn delete(&mut self) -> Result<bool, VectorDbError> {
+        let existed = !self.documents.is_empty();
+        self.documents.clear();
+        Ok(existed)
+    }
+}
@@ -1,32 +1,184 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub mod versioning;
+
+pub use versioning::MEMORY_SCHEMA_VERSION;
+
+/// How memories are picked for an agent's context.
+///
+/// This enum is exposed to Python as the `MemoryRetrieval` class, with the
+/// variants `last_n`, `first_n` and `semantic`.
+#[pyclass(name = "MemoryRetrieval")]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum MemoryRetrieval {
+    #[pyo3(name = "last_n")]
     LastN,
+    #[pyo3(name = "first_n")]
     FirstN,
+    #[pyo3(name = "semantic")]
     Semantic,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl MemoryRetrieval {
+    /// The snake_case name used by Python and in serialized form.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemoryRetrieval::LastN => "last_n",
+            MemoryRetrieval::FirstN => "first_n",
+            MemoryRetrieval::Semantic => "semantic",
+        }
+    }
+}
+
+#[pymethods]
+impl MemoryRetrieval {
+    /// The string value of the variant, as in Python's `MemoryRetrieval` str enum.
+    ///
+    /// Returns:
+    ///     str: "last_n", "first_n" or "semantic".
+    #[getter]
+    fn value(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// Looks up a variant by its string value.
+    ///
+    /// Args:
+    ///     value (str): "last_n", "first_n" or "semantic".
+    ///
+    /// Returns:
+    ///     MemoryRetrieval: The matching variant.
+    ///
+    /// Raises:
+    ///     PyValueError: If `value` names no variant.
+    #[staticmethod]
+    fn from_value(value: &str) -> PyResult<Self> {
+        serde_json::from_value(serde_json::Value::String(value.to_string()))
+            .map_err(|_| PyValueError::new_err(format!("'{}' is not a valid MemoryRetrieval", value)))
+    }
+
+    fn __str__(&self) -> &'static str {
+        self.as_str()
+    }
+}
+
+/// A memory an agent keeps about a user.
+///
+/// This struct is exposed to Python as the `Memory` class.
+///
+/// Serialization is implemented in the `versioning` module.
+#[pyclass(name = "Memory")]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Memory {
+    #[pyo3(get, set)]
     pub memory: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[pyo3(get, set)]
     pub id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[pyo3(get, set)]
     pub topic: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[pyo3(get, set)]
     pub input: Option<String>,
+    /// Serialized fields this version does not know, e.g. from a newer
+    /// version. They are written back unchanged.
+    pub extra: HashMap<String, serde_json::Value>,
+    /// The schema version of a memory read from a newer version, which it
+    /// is written back with. None for memories of this version or older.
+    pub schema_version: Option<u32>,
+}
+
+#[pymethods]
+impl Memory {
+    /// Creates a new `Memory` instance.
+    ///
+    /// Args:
+    ///     memory (str): The text of the memory.
+    ///     id (Optional[str]): An optional unique identifier. Defaults to None.
+    ///     topic (Optional[str]): An optional topic. Defaults to None.
+    ///     input (Optional[str]): The input the memory was made from. Defaults to None.
+    #[new]
+    #[pyo3(signature = (memory, id=None, topic=None, input=None))]
+    fn py_new(memory: String, id: Option<String>, topic: Option<String>, input: Option<String>) -> Self {
+        Memory { memory, id, topic, input, extra: HashMap::new(), schema_version: None }
+    }
+
+    /// Returns the fields that are set as a dict, like the Python model's `to_dict`.
+    ///
+    /// Returns:
+    ///     dict: `memory`, plus `id`, `topic` and `input` when not None.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("memory", &self.memory)?;
+        for (key, value) in [("id", &self.id), ("topic", &self.topic), ("input", &self.input)] {
+            if let Some(value) = value {
+                dict.set_item(key, value)?;
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Serializes the `Memory` instance to a JSON string.
+    ///
+    /// Returns:
+    ///     str: The JSON string representation of the memory.
+    ///
+    /// Raises:
+    ///     PyValueError: If serialization fails.
+    #[pyo3(name = "to_json")]
+    fn to_json_py(&self) -> PyResult<String> {
+        serde_json::to_string(self).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Deserializes a `Memory` instance from a JSON string.
+    ///
+    /// Args:
+    ///     json_str (str): The JSON string representation of a memory.
+    ///
+    /// Returns:
+    ///     Memory: A new instance of the Memory.
+    ///
+    /// Raises:
+    ///     PyValueError: If deserialization fails.
+    #[staticmethod]
+    #[pyo3(name = "from_json")]
+    fn from_json_py(json_str: &str) -> PyResult<Self> {
+        serde_json::from_str(json_str).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        let optional = |value: &Option<String>| match value {
+            Some(value) => format!("{:?}", value),
+            None => "None".to_string(),
+        };
+        format!(
+            "Memory(memory={:?}, id={}, topic={}, input={})",
+            self.memory,
+            optional(&self.id),
+            optional(&self.topic),
+            optional(&self.input)
+        )
+    }
+}
+
+/// Adds the classes of the `memory` module to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Memory>()?;
+    m.add_class::<MemoryRetrieval>()?;
+    m.add("MEMORY_SCHEMA_VERSION", MEMORY_SCHEMA_VERSION)?;
+    Ok(())
 }
 
 // The Pydantic `to_dict()` with `exclude_none=True` functionality
-// is achieved by `#[serde(skip_serializing_if = "Option::is_none")]` on Option fields
-// and serde's default behavior for serializing structs to map-like structures (e.g., JSON objects).
+// is achieved by `#[serde(skip_serializing_if = "Option::is_none")]` on the Option fields
+// of the serialized form in `versioning`.
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json;
 
     #[test]
     fn test_memory_retrieval_serialization() {
@@ -35,6 +187,13 @@ mod tests {
         assert_eq!(serde_json::to_string(&MemoryRetrieval::Semantic).unwrap(), r#""semantic""#);
     }
 
+    #[test]
+    fn test_memory_retrieval_as_str_matches_serde() {
+        for retrieval in [MemoryRetrieval::LastN, MemoryRetrieval::FirstN, MemoryRetrieval::Semantic] {
+            assert_eq!(serde_json::to_string(&retrieval).unwrap(), format!("\"{}\"", retrieval.as_str()));
+        }
+    }
+
     #[test]
     fn test_memory_retrieval_deserialization() {
         assert_eq!(serde_json::from_str::<MemoryRetrieval>(r#""last_n""#).unwrap(), MemoryRetrieval::LastN);
@@ -49,6 +208,8 @@ mod tests {
             id: Some("mem_123".to_string()),
             topic: Some("event_recollection".to_string()),
             input: Some("User query about event X".to_string()),
+            extra: HashMap::new(),
+            schema_version: None,
         };
         let json = serde_json::to_string(&mem).unwrap();
         // Order of fields in JSON is not guaranteed, so check for presence
@@ -65,10 +226,12 @@ mod tests {
             id: None,
             topic: None,
             input: None,
+            extra: HashMap::new(),
+            schema_version: None,
         };
         let json = serde_json::to_string(&mem).unwrap();
-        // Only 'memory' should be present, others are None and skipped
-        assert_eq!(json, r#"{"memory":"Recall this basic fact."}"#);
+        // Only 'memory' should be present besides the version, others are None and skipped
+        assert_eq!(json, r#"{"schema_version":2,"memory":"Recall this basic fact."}"#);
     }
 
     #[test]
@@ -0,0 +1,127 @@
+//! Schema versions of serialized memories, with the same versioning and
+//! unknown-field handling as `document::versioning`.
+
+use crate::Memory;
+use document::versioning::{migrate, written_version, Migration, VersionError, Versioned, VersionedVisitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::HashMap;
+
+/// The schema version `Memory` is serialized with.
+///
+/// - 1: the original format, without `schema_version`.
+/// - 2: adds `schema_version`.
+pub const MEMORY_SCHEMA_VERSION: u32 = 2;
+
+const MEMORY_MIGRATIONS: &[Migration] = &[memory_v1_to_v2];
+
+fn memory_v1_to_v2(_object: &mut Map<String, JsonValue>) -> Result<(), String> {
+    // Version 2 only adds the version key; null optional fields still read as None.
+    Ok(())
+}
+
+/// Migrates a serialized memory to [`MEMORY_SCHEMA_VERSION`], returning it
+/// without the version key.
+pub fn migrate_memory(value: JsonValue) -> Result<JsonValue, VersionError> {
+    let JsonValue::Object(mut object) = value else {
+        return Err(VersionError::NotAnObject);
+    };
+    migrate(&mut object, MEMORY_MIGRATIONS)?;
+    Ok(JsonValue::Object(object))
+}
+
+/// Serialized form of a `Memory`, borrowing its fields.
+#[derive(Serialize)]
+struct MemoryOut<'a> {
+    schema_version: u32,
+    memory: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topic: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input: Option<&'a str>,
+    #[serde(flatten)]
+    extra: &'a HashMap<String, JsonValue>,
+}
+
+impl Serialize for Memory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MemoryOut {
+            schema_version: written_version(self.schema_version, MEMORY_SCHEMA_VERSION),
+            memory: &self.memory,
+            id: self.id.as_deref(),
+            topic: self.topic.as_deref(),
+            input: self.input.as_deref(),
+            extra: &self.extra,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Versioned for Memory {
+    const VERSION: u32 = MEMORY_SCHEMA_VERSION;
+    const MIGRATIONS: &'static [Migration] = MEMORY_MIGRATIONS;
+    const REQUIRED: &'static [&'static str] = &["memory"];
+
+    fn read_field<'de, D: Deserializer<'de>>(&mut self, key: String, value: D) -> Result<(), D::Error> {
+        match key.as_str() {
+            "memory" => self.memory = String::deserialize(value)?,
+            "id" => self.id = Option::deserialize(value)?,
+            "topic" => self.topic = Option::deserialize(value)?,
+            "input" => self.input = Option::deserialize(value)?,
+            _ => {
+                self.extra.insert(key, JsonValue::deserialize(value)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_newer_version(&mut self, version: Option<u32>) {
+        self.schema_version = version;
+    }
+}
+
+impl<'de> Deserialize<'de> for Memory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(VersionedVisitor::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use document::versioning::SCHEMA_VERSION_KEY;
+
+    /// Memories in the format written before versioning.
+    const MEMORIES_V1: &str = include_str!("../fixtures/memories_v1.json");
+
+    #[test]
+    fn test_v1_fixtures_are_migrated() {
+        let memories: Vec<Memory> = serde_json::from_str(MEMORIES_V1).unwrap();
+        assert_eq!(memories.len(), 3);
+        assert_eq!(memories[0].id.as_deref(), Some("mem_123"));
+        assert_eq!(memories[1].topic, None);
+        assert_eq!(memories[2].id, None);
+        assert_eq!(memories[2].topic.as_deref(), Some("general_knowledge"));
+        assert!(memories.iter().all(|memory| memory.extra.is_empty()));
+
+        let json = serde_json::to_value(&memories[2]).unwrap();
+        assert_eq!(json[SCHEMA_VERSION_KEY], serde_json::json!(MEMORY_SCHEMA_VERSION));
+        assert!(json.get("id").is_none());
+    }
+
+    #[test]
+    fn test_newer_versions_keep_unknown_fields() {
+        let json = r#"{"schema_version": 5, "memory": "Later", "importance": 0.9}"#;
+        let memory: Memory = serde_json::from_str(json).unwrap();
+        assert_eq!(memory.extra["importance"], serde_json::json!(0.9));
+        assert_eq!(memory.schema_version, Some(5));
+
+        let rewritten = serde_json::to_value(&memory).unwrap();
+        assert_eq!(rewritten["importance"], serde_json::json!(0.9));
+        assert_eq!(rewritten[SCHEMA_VERSION_KEY], serde_json::json!(5));
+        assert_eq!(serde_json::from_value::<Memory>(rewritten).unwrap(), memory);
+        assert_eq!(migrate_memory(serde_json::json!("text")), Err(VersionError::NotAnObject));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{Reranker, RerankerError};
+use async_trait::async_trait;
+use document::Document;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use vectordb::{VectorDb, VectorDbError};
+
+impl From<RerankerError> for VectorDbError {
+    fn from(err: RerankerError) -> VectorDbError {
+        VectorDbError::OperationFailed(format!("reranking: {}", err))
+    }
+}
+
+/// A vector db whose search results are reranked before being returned.
+///
+/// Searches fetch `candidates` documents from the wrapped vector db, or
+/// the limit if that is more, and return the best `limit` of them after
+/// reranking. Async searches rerank with [`Reranker::async_rerank`], and
+/// use the sync search of a wrapped vector db without an async one. Every
+/// other method goes straight to the wrapped vector db, so a `RerankingDb`
+/// can stand in for it, e.g. in a knowledge base.
+pub struct RerankingDb<D, R> {
+    pub vector_db: D,
+    pub reranker: R,
+    pub candidates: Option<u32>,
+}
+
+impl<D: VectorDb, R: Reranker> RerankingDb<D, R> {
+    pub fn new(vector_db: D, reranker: R) -> Self {
+        RerankingDb { vector_db, reranker, candidates: None }
+    }
+
+    /// Sets how many documents to fetch for reranking.
+    pub fn with_candidates(mut self, candidates: Option<u32>) -> Self {
+        self.candidates = candidates;
+        self
+    }
+
+    fn fetch_limit(&self, limit: u32) -> u32 {
+        self.candidates.map_or(limit, |candidates| candidates.max(limit))
+    }
+
+    fn rerank(&self, query: &str, limit: u32, documents: Vec<Document>) -> Result<Vec<Document>, VectorDbError> {
+        let mut documents = self.reranker.rerank(query, documents)?;
+        documents.truncate(limit as usize);
+        Ok(documents)
+    }
+}
+
+#[async_trait]
+impl<D, R> VectorDb for RerankingDb<D, R>
+where
+    D: VectorDb + Send + Sync,
+    R: Reranker,
+{
+    fn create(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.create()
+    }
+    async fn async_create(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.async_create().await
+    }
+
+    fn doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.vector_db.doc_exists(document)
+    }
+    async fn async_doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.vector_db.async_doc_exists(document).await
+    }
+
+    fn name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.vector_db.name_exists(name)
+    }
+    async fn async_name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.vector_db.async_name_exists(name).await
+    }
+
+    fn id_exists(&self, id: &str) -> Result<bool, VectorDbError> {
+        self.vector_db.id_exists(id)
+    }
+
+    fn insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.vector_db.insert(documents, filters)
+    }
+    async fn async_insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.vector_db.async_insert(documents, filters).await
+    }
+
+    fn delete_by_id(&mut self, id: &str) -> Result<bool, VectorDbError> {
+        self.vector_db.delete_by_id(id)
+    }
+
+    fn upsert_available(&self) -> bool {
+        self.vector_db.upsert_available()
+    }
+
+    fn upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.vector_db.upsert(documents, filters)
+    }
+    async fn async_upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.vector_db.async_upsert(documents, filters).await
+    }
+
+    fn search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        let documents = self.vector_db.search(query, self.fetch_limit(limit), filters)?;
+        self.rerank(query, limit, documents)
+    }
+    async fn async_search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        let documents = match self.vector_db.async_search(query, self.fetch_limit(limit), filters.clone()).await {
+            Err(VectorDbError::NotImplemented) => self.vector_db.search(query, self.fetch_limit(limit), filters)?,
+            result => result?,
+        };
+        let mut documents = self.reranker.async_rerank(query, documents).await?;
+        documents.truncate(limit as usize);
+        Ok(documents)
+    }
+
+    fn vector_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        let documents = self.vector_db.vector_search(query, self.fetch_limit(limit))?;
+        self.rerank(query, limit, documents)
+    }
+
+    fn keyword_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        let documents = self.vector_db.keyword_search(query, self.fetch_limit(limit))?;
+        self.rerank(query, limit, documents)
+    }
+
+    fn hybrid_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        let documents = self.vector_db.hybrid_search(query, self.fetch_limit(limit))?;
+        self.rerank(query, limit, documents)
+    }
+
+    fn drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.drop_db()
+    }
+    async fn async_drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.async_drop_db().await
+    }
+
+    fn db_exists(&self) -> Result<bool, VectorDbError> {
+        self.vector_db.db_exists()
+    }
+    async fn async_db_exists(&self) -> Result<bool, VectorDbError> {
+        self.vector_db.async_db_exists().await
+    }
+
+    fn optimize(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.optimize()
+    }
+    async fn async_optimize(&mut self) -> Result<(), VectorDbError> {
+        self.vector_db.async_optimize().await
+    }
+
+    fn delete(&mut self) -> Result<bool, VectorDbError> {
+        self.vector_db.delete()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{doc, FixedDb};
+    use crate::LexicalReranker;
+
+    fn db() -> FixedDb {
+        FixedDb::new(vec![
+            doc("a", "python packaging"),
+            doc("b", "rust traits and generics"),
+            doc("c", "gardening tips"),
+            doc("d", "rust async and tokio"),
+        ])
+    }
+
+    fn ids(documents: &[Document]) -> Vec<&str> {
+        documents.iter().map(|d| d.id.as_deref().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_results_are_reranked() {
+        let reranked = RerankingDb::new(db(), LexicalReranker::new()).with_candidates(Some(4));
+        let found = reranked.search("rust tokio", 2, None).unwrap();
+        assert_eq!(ids(&found), ["d", "b"]);
+        assert!(found[0].reranking_score.unwrap() > found[1].reranking_score.unwrap());
+        assert_eq!(*reranked.vector_db.limits.lock().unwrap(), [4]);
+
+        let found = reranked.async_search("rust tokio", 2, None).await.unwrap();
+        assert_eq!(ids(&found), ["d", "b"]);
+        assert_eq!(ids(&reranked.hybrid_search("gardening", 1).unwrap()), ["c"]);
+
+        // Without candidates, only the first `limit` results are reranked.
+        let reranked = RerankingDb::new(db(), LexicalReranker::new());
+        assert_eq!(ids(&reranked.search("rust tokio", 2, None).unwrap()), ["b", "a"]);
+    }
+
+    #[test]
+    fn test_reranker_errors_fail_the_search() {
+        struct Failing;
+        impl Reranker for Failing {
+            fn rerank(&self, _query: &str, _documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+                Err(RerankerError::Reranker("model not loaded".to_string()))
+            }
+        }
+        let err = RerankingDb::new(db(), Failing).search("rust", 2, None).unwrap_err();
+        assert_eq!(err.to_string(), "Operation failed: reranking: Reranker error: model not loaded");
+        assert!(RerankingDb::new(db(), Failing).db_exists().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_async_search_never_reranks_blocking() {
+        /// Reranks asynchronously only, like `HttpReranker` on a current-thread runtime.
+        struct AsyncOnly;
+        #[async_trait]
+        impl Reranker for AsyncOnly {
+            fn rerank(&self, _query: &str, _documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+                Err(RerankerError::Reranker("blocking".to_string()))
+            }
+            async fn async_rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+                LexicalReranker::new().rerank(query, documents)
+            }
+        }
+        let sync_only = FixedDb { sync_only: true, ..db() };
+        let reranked = RerankingDb::new(sync_only, AsyncOnly).with_candidates(Some(4));
+        assert_eq!(ids(&reranked.async_search("rust tokio", 2, None).await.unwrap()), ["d", "b"]);
+        assert!(reranked.search("rust tokio", 2, None).is_err());
+    }
+}
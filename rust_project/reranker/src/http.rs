@@ -0,0 +1,288 @@
+use crate::{rank, Reranker, RerankerError};
+use async_trait::async_trait;
+use document::Document;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::runtime::{Handle, Runtime, RuntimeFlavor};
+
+/// The Cohere API, the default base URL.
+pub const COHERE_BASE_URL: &str = "https://api.cohere.com/v2";
+
+/// A reranker for servers with a Cohere-style `/rerank` endpoint, which
+/// score documents with a cross-encoder: Cohere itself, Infinity, Jina,
+/// Voyage and others.
+///
+/// The API key goes in the `Authorization` header as a bearer token by
+/// default; [`HttpReranker::with_auth_header`] names another header, which
+/// then carries the bare key.
+///
+/// [`Reranker::rerank`] blocks on the request. Outside Tokio it runs on a
+/// runtime shared by every `HttpReranker`; on a multi-thread runtime it
+/// blocks in place; on a current-thread runtime, which it would stall, it
+/// fails. Use [`Reranker::async_rerank`] in async code.
+#[derive(Debug, Clone)]
+pub struct HttpReranker {
+    client: reqwest::Client,
+    base_url: String,
+    model: String,
+    top_n: Option<usize>,
+    api_key: Option<String>,
+    auth_header: String,
+    timeout: Duration,
+}
+
+/// The runtime blocking calls run on outside Tokio, built on first use and
+/// kept so that pooled connections outlive a call.
+fn blocking_runtime() -> Result<&'static Runtime, RerankerError> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("reranker-http")
+        .enable_all()
+        .build()
+        .map_err(|e| RerankerError::Reranker(e.to_string()))?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+#[derive(Serialize)]
+struct RerankRequest<'a> {
+    model: &'a str,
+    query: &'a str,
+    documents: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_n: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct RerankResponse {
+    results: Vec<RerankResult>,
+}
+
+#[derive(Deserialize)]
+struct RerankResult {
+    index: usize,
+    relevance_score: f64,
+}
+
+impl HttpReranker {
+    /// A reranker for `model` on the Cohere API, without an API key and
+    /// with a 60 second timeout.
+    pub fn new(model: &str) -> Self {
+        HttpReranker {
+            client: reqwest::Client::new(),
+            base_url: COHERE_BASE_URL.to_string(),
+            model: model.to_string(),
+            top_n: None,
+            api_key: None,
+            auth_header: "Authorization".to_string(),
+            timeout: Duration::from_secs(60),
+        }
+    }
+
+    /// Sets the URL the `/rerank` path is appended to, e.g.
+    /// `http://localhost:7997`.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Asks for the `top_n` best documents only.
+    pub fn with_top_n(mut self, top_n: Option<usize>) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Sets the header carrying the API key.
+    pub fn with_auth_header(mut self, auth_header: &str) -> Self {
+        self.auth_header = auth_header.to_string();
+        self
+    }
+
+    /// Sets how long a request may take, connection included.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn top_n(&self) -> Option<usize> {
+        self.top_n
+    }
+
+    /// The relevance score of each document; `None` for documents the
+    /// server left out.
+    async fn scores(&self, query: &str, documents: &[Document]) -> Result<Vec<Option<f64>>, RerankerError> {
+        let request_error = |e: reqwest::Error| {
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message = format!("{}: {}", message, cause);
+                source = cause.source();
+            }
+            RerankerError::Request(message)
+        };
+        let request = self.client.post(format!("{}/rerank", self.base_url)).timeout(self.timeout).json(&RerankRequest {
+            model: &self.model,
+            query,
+            documents: documents.iter().map(|document| document.content.as_str()).collect(),
+            top_n: self.top_n,
+        });
+        let request = match &self.api_key {
+            Some(key) if self.auth_header.eq_ignore_ascii_case("authorization") => request.bearer_auth(key),
+            Some(key) => request.header(self.auth_header.as_str(), key),
+            None => request,
+        };
+
+        let response = request.send().await.map_err(request_error)?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(request_error)?;
+        if !status.is_success() {
+            let body = String::from_utf8_lossy(&body).to_string();
+            // Cohere reports errors as {"message": ...}, Infinity and OpenAI-style servers
+            // as {"error": {"message": ...}} or {"detail": ...}.
+            let message = serde_json::from_str::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|error| {
+                    let message = error.get("message").or(error.pointer("/error/message")).or(error.get("detail"));
+                    message.map(|message| message.as_str().map(str::to_string).unwrap_or_else(|| message.to_string()))
+                })
+                .unwrap_or(body);
+            return Err(RerankerError::Server { status: status.as_u16(), message });
+        }
+
+        let response: RerankResponse =
+            serde_json::from_slice(&body).map_err(|e| RerankerError::InvalidResponse(e.to_string()))?;
+        let mut scores = vec![None; documents.len()];
+        for result in response.results {
+            match scores.get_mut(result.index) {
+                Some(score @ None) => *score = Some(result.relevance_score),
+                _ => {
+                    return Err(RerankerError::InvalidResponse(format!(
+                        "unexpected result index {} for {} documents",
+                        result.index,
+                        documents.len()
+                    )))
+                }
+            }
+        }
+        Ok(scores)
+    }
+}
+
+#[async_trait]
+impl Reranker for HttpReranker {
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        match Handle::try_current() {
+            Err(_) => blocking_runtime()?.block_on(self.async_rerank(query, documents)),
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(self.async_rerank(query, documents)))
+            }
+            Ok(_) => Err(RerankerError::Reranker(
+                "HttpReranker::rerank cannot block a current-thread runtime, use async_rerank".to_string(),
+            )),
+        }
+    }
+
+    async fn async_rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        if documents.is_empty() {
+            return Ok(documents);
+        }
+        let scores = self.scores(query, &documents).await?;
+        Ok(rank(documents, &scores, self.top_n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{closed_url, doc, StubResponse, StubServer};
+
+    fn documents() -> Vec<Document> {
+        vec![doc("a", "first"), doc("b", "second"), doc("c", "third")]
+    }
+
+    #[tokio::test]
+    async fn test_request_and_response() {
+        let body = r#"{"id": "x", "results": [{"index": 2, "relevance_score": 0.8}, {"index": 0, "relevance_score": 0.3}]}"#;
+        let server = StubServer::always(StubResponse::status(200).with_body(body)).await;
+        let reranker = HttpReranker::new("rerank-v3.5")
+            .with_base_url(&format!("{}/v2/", server.url()))
+            .with_api_key(Some("key".to_string()))
+            .with_top_n(Some(2));
+        let reranked = reranker.async_rerank("query", documents()).await.unwrap();
+        let ranked: Vec<_> = reranked.iter().map(|d| (d.id.as_deref().unwrap(), d.reranking_score.unwrap())).collect();
+        assert_eq!(ranked, [("c", 0.8), ("a", 0.3)]);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v2/rerank");
+        assert_eq!(request.headers["authorization"], "Bearer key");
+        assert_eq!(
+            request.body,
+            serde_json::json!({"model": "rerank-v3.5", "query": "query", "documents": ["first", "second", "third"], "top_n": 2})
+        );
+
+        assert!(reranker.async_rerank("query", Vec::new()).await.unwrap().is_empty());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors() {
+        let server = StubServer::always(StubResponse::status(422).with_body(r#"{"detail": "query is empty"}"#)).await;
+        let reranker = HttpReranker::new("m").with_base_url(&server.url()).with_auth_header("api-key");
+        let err = reranker.async_rerank("", documents()).await.unwrap_err();
+        assert_eq!(err, RerankerError::Server { status: 422, message: "query is empty".to_string() });
+
+        let body = r#"{"results": [{"index": 5, "relevance_score": 1.0}]}"#;
+        let server = StubServer::always(StubResponse::status(200).with_body(body)).await;
+        let err = HttpReranker::new("m").with_base_url(&server.url()).async_rerank("q", documents()).await.unwrap_err();
+        assert!(matches!(&err, RerankerError::InvalidResponse(msg) if msg.contains("index 5")), "{}", err);
+
+        let url = closed_url().await;
+        let err = HttpReranker::new("m").with_base_url(&url).async_rerank("q", documents()).await.unwrap_err();
+        assert!(matches!(err, RerankerError::Request(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_blocking_rerank() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let body = r#"{"results": [{"index": 0, "relevance_score": 0.1}, {"index": 1, "relevance_score": 0.2}, {"index": 2, "relevance_score": 0.3}]}"#;
+        let server = runtime.block_on(StubServer::always(StubResponse::status(200).with_body(body)));
+        let reranker = HttpReranker::new("m").with_base_url(&server.url());
+        for _ in 0..2 {
+            let reranked = reranker.rerank("q", documents()).unwrap();
+            assert_eq!(reranked.iter().map(|d| d.id.as_deref().unwrap()).collect::<Vec<_>>(), ["c", "b", "a"]);
+        }
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_blocking_rerank_inside_a_runtime() {
+        let body = r#"{"results": [{"index": 1, "relevance_score": 0.5}]}"#;
+        let server = StubServer::always(StubResponse::status(200).with_body(body)).await;
+        let reranker = HttpReranker::new("m").with_base_url(&server.url());
+        let reranked = reranker.rerank("q", documents()).unwrap();
+        assert_eq!(reranked.iter().map(|d| d.id.as_deref().unwrap()).collect::<Vec<_>>(), ["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_rerank_fails_on_a_current_thread_runtime() {
+        let err = HttpReranker::new("m").with_base_url("http://127.0.0.1:9").rerank("q", documents()).unwrap_err();
+        assert!(matches!(&err, RerankerError::Reranker(msg) if msg.contains("async_rerank")), "{}", err);
+    }
+}
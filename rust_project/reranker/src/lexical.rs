@@ -0,0 +1,138 @@
+use crate::{rank, Reranker, RerankerError};
+use document::Document;
+use std::collections::{HashMap, HashSet};
+
+/// Ranks documents by the query words they contain, with Okapi BM25: rare
+/// words count for more, repeats for less and less, and long documents
+/// are not favored for being long. Word rarity is measured over the
+/// documents being reranked, so no index is needed.
+///
+/// Words are lowercase runs of letters and digits. A document sharing no
+/// word with the query scores 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicalReranker {
+    k1: f64,
+    b: f64,
+    top_n: Option<usize>,
+}
+
+impl Default for LexicalReranker {
+    fn default() -> Self {
+        LexicalReranker { k1: 1.2, b: 0.75, top_n: None }
+    }
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_lowercase)
+}
+
+impl LexicalReranker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how quickly repeats of a word stop adding to the score, and how
+    /// much document length is normalized, from 0 (not at all) to 1.
+    pub fn with_parameters(mut self, k1: f64, b: f64) -> Self {
+        self.k1 = k1;
+        self.b = b.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Keeps only the `top_n` best documents.
+    pub fn with_top_n(mut self, top_n: Option<usize>) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    /// The BM25 score of each document.
+    pub fn scores(&self, query: &str, documents: &[Document]) -> Vec<f64> {
+        let query: HashSet<String> = words(query).collect();
+        let counts: Vec<(HashMap<String, usize>, usize)> = documents
+            .iter()
+            .map(|document| {
+                let mut counts = HashMap::new();
+                let mut length = 0;
+                for word in words(&document.content) {
+                    length += 1;
+                    if query.contains(&word) {
+                        *counts.entry(word).or_insert(0) += 1;
+                    }
+                }
+                (counts, length)
+            })
+            .collect();
+        let total = documents.len() as f64;
+        let average_length = counts.iter().map(|(_, length)| *length as f64).sum::<f64>() / total.max(1.0);
+        let idf: HashMap<&String, f64> = query
+            .iter()
+            .map(|word| {
+                let containing = counts.iter().filter(|(counts, _)| counts.contains_key(word)).count() as f64;
+                (word, (1.0 + (total - containing + 0.5) / (containing + 0.5)).ln())
+            })
+            .collect();
+        counts
+            .iter()
+            .map(|(counts, length)| {
+                let norm = self.k1 * (1.0 - self.b + self.b * *length as f64 / average_length.max(1.0));
+                counts
+                    .iter()
+                    .map(|(word, &count)| {
+                        let count = count as f64;
+                        idf[word] * count * (self.k1 + 1.0) / (count + norm)
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl Reranker for LexicalReranker {
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        let scores: Vec<Option<f64>> = self.scores(query, &documents).into_iter().map(Some).collect();
+        Ok(rank(documents, &scores, self.top_n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::doc;
+
+    fn ids(documents: &[Document]) -> Vec<&str> {
+        documents.iter().map(|d| d.id.as_deref().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_ranks_by_overlap() {
+        let documents = vec![
+            doc("cooking", "Slow cooking brings out the flavour of beans."),
+            doc("borrow", "The Rust borrow checker enforces ownership rules."),
+            doc("rust", "Rust is a systems language; Rust is fast."),
+            doc("python", "Python is a dynamic language."),
+        ];
+        let reranked = LexicalReranker::new().rerank("rust OWNERSHIP", documents.clone()).unwrap();
+        assert_eq!(ids(&reranked), ["borrow", "rust", "cooking", "python"]);
+        assert!(reranked[0].reranking_score.unwrap() > reranked[1].reranking_score.unwrap());
+        assert_eq!(reranked[2].reranking_score, Some(0.0));
+
+        let top = LexicalReranker::new().with_top_n(Some(1)).rerank("python", documents).unwrap();
+        assert_eq!(ids(&top), ["python"]);
+    }
+
+    #[test]
+    fn test_repeats_saturate_and_length_is_normalized() {
+        let documents = vec![
+            doc("once", "tokio runtime"),
+            doc("twice", "tokio tokio runtime"),
+            doc("long", "tokio and many other words that make this document much longer than the rest"),
+        ];
+        let scores = LexicalReranker::new().scores("tokio", &documents);
+        assert!(scores[1] > scores[0] && scores[1] < 2.0 * scores[0]);
+        assert!(scores[2] < scores[0]);
+
+        let scores = LexicalReranker::new().with_parameters(1.2, 0.0).scores("tokio", &documents);
+        assert_eq!(scores[0], scores[2]);
+        assert!(LexicalReranker::new().scores("anything", &[]).is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+//! Rerankers score search results against the query and reorder them,
+//! writing `Document.reranking_score`: offline by word overlap
+//! (`LexicalReranker`), or with a cross-encoder served over HTTP
+//! (`HttpReranker`). `RerankingDb` passes the results of a vector db
+//! through a reranker.
+
+use async_trait::async_trait;
+use document::Document;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+pub mod db;
+pub mod http;
+pub mod lexical;
+pub mod python;
+#[cfg(test)]
+mod testing;
+
+pub use db::RerankingDb;
+pub use http::HttpReranker;
+pub use lexical::LexicalReranker;
+pub use python::{PyHttpReranker, PyLexicalReranker, PyReranker};
+
+// Define a custom error type for reranking
+#[derive(Debug, Clone, PartialEq)]
+pub enum RerankerError {
+    /// The request did not get a response, e.g. a refused connection or a timeout.
+    Request(String),
+    /// The provider answered with an error status.
+    Server { status: u16, message: String },
+    InvalidResponse(String),
+    /// Any other failure of the reranker.
+    Reranker(String),
+}
+
+impl fmt::Display for RerankerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RerankerError::Request(msg) => write!(f, "Request error: {}", msg),
+            RerankerError::Server { status, message } => write!(f, "Server error {}: {}", status, message),
+            RerankerError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            RerankerError::Reranker(msg) => write!(f, "Reranker error: {}", msg),
+        }
+    }
+}
+
+impl Error for RerankerError {}
+
+/// Mirrors Python's `agno.reranker.base.Reranker`.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Scores `documents` against `query` and returns them most relevant
+    /// first, with `reranking_score` set. Rerankers keeping only the top
+    /// results drop the others.
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError>;
+    async fn async_rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        self.rerank(query, documents)
+    }
+}
+
+#[async_trait]
+impl<R: Reranker + ?Sized> Reranker for Arc<R> {
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        (**self).rerank(query, documents)
+    }
+    async fn async_rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        (**self).async_rerank(query, documents).await
+    }
+}
+
+/// Sets the score of each document, by position, and sorts them by
+/// descending score, keeping the original order between equal scores.
+/// Documents without a score are dropped, and only the first `top_n` kept.
+pub fn rank(documents: Vec<Document>, scores: &[Option<f64>], top_n: Option<usize>) -> Vec<Document> {
+    let mut ranked: Vec<Document> = documents
+        .into_iter()
+        .zip(scores)
+        .filter_map(|(mut document, score)| {
+            document.reranking_score = Some((*score)?);
+            Some(document)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.reranking_score.partial_cmp(&a.reranking_score).unwrap_or(std::cmp::Ordering::Equal));
+    ranked.truncate(top_n.unwrap_or(usize::MAX));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::doc;
+
+    #[test]
+    fn test_rank() {
+        let documents = vec![doc("a", "a"), doc("b", "b"), doc("c", "c"), doc("d", "d")];
+        let scores = [Some(0.1), Some(0.9), None, Some(0.1)];
+        let ranked = rank(documents.clone(), &scores, None);
+        let ids: Vec<_> = ranked.iter().map(|d| d.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["b", "a", "d"]);
+        assert_eq!(ranked[0].reranking_score, Some(0.9));
+
+        assert_eq!(rank(documents, &scores, Some(1)).len(), 1);
+    }
+}
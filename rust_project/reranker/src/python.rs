@@ -0,0 +1,184 @@
+use crate::{HttpReranker, LexicalReranker, Reranker, RerankerError};
+use document::Document;
+use pyo3::exceptions::{PyConnectionError, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+
+impl From<RerankerError> for PyErr {
+    fn from(err: RerankerError) -> PyErr {
+        match err {
+            RerankerError::Request(_) => PyConnectionError::new_err(err.to_string()),
+            RerankerError::InvalidResponse(_) => PyValueError::new_err(err.to_string()),
+            _ => PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// A `Reranker` backed by a Python object with the `rerank(query,
+/// documents)` method of Python's `agno.reranker.base.Reranker`, such as
+/// `CohereReranker`. Exceptions surface as [`RerankerError::Reranker`].
+#[derive(Debug)]
+pub struct PyReranker {
+    object: Py<PyAny>,
+}
+
+impl PyReranker {
+    pub fn new(object: Py<PyAny>) -> Self {
+        PyReranker { object }
+    }
+
+    /// The wrapped Python object.
+    pub fn object(&self) -> &Py<PyAny> {
+        &self.object
+    }
+}
+
+impl Reranker for PyReranker {
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> Result<Vec<Document>, RerankerError> {
+        Python::with_gil(|py| {
+            self.object
+                .bind(py)
+                .call_method1("rerank", (query, documents))
+                .and_then(|result| result.extract())
+                .map_err(|err| RerankerError::Reranker(err.to_string()))
+        })
+    }
+}
+
+/// Wraps a Python reranker object. Rerankers implemented in Rust are used
+/// directly, without going through Python.
+pub fn reranker_from_py(object: &Bound<'_, PyAny>) -> Arc<dyn Reranker> {
+    if let Ok(reranker) = object.downcast::<PyLexicalReranker>() {
+        return reranker.borrow().inner.clone();
+    }
+    if let Ok(reranker) = object.downcast::<PyHttpReranker>() {
+        return reranker.borrow().inner.clone();
+    }
+    Arc::new(PyReranker::new(object.clone().unbind()))
+}
+
+/// Ranks documents by the query words they contain, with Okapi BM25.
+///
+/// This struct is exposed to Python as the `LexicalReranker` class.
+#[pyclass(name = "LexicalReranker")]
+pub struct PyLexicalReranker {
+    inner: Arc<LexicalReranker>,
+}
+
+#[pymethods]
+impl PyLexicalReranker {
+    /// Creates a new `LexicalReranker` instance.
+    ///
+    /// Args:
+    ///     top_n (Optional[int]): Number of documents to keep, or None to keep all. Defaults to None.
+    ///     k1 (float): How quickly repeats of a word stop adding to the score. Defaults to 1.2.
+    ///     b (float): How much document length is normalized, from 0 to 1. Defaults to 0.75.
+    #[new]
+    #[pyo3(signature = (top_n=None, k1=1.2, b=0.75))]
+    fn py_new(top_n: Option<usize>, k1: f64, b: f64) -> Self {
+        let inner = LexicalReranker::new().with_parameters(k1, b).with_top_n(top_n);
+        PyLexicalReranker { inner: Arc::new(inner) }
+    }
+
+    /// Scores documents against a query and sorts them, most relevant first.
+    ///
+    /// Args:
+    ///     query (str): The search query.
+    ///     documents (List[Document]): The documents to rerank.
+    ///
+    /// Returns:
+    ///     List[Document]: Copies of the documents with `reranking_score` set, most relevant first.
+    fn rerank(&self, query: &str, documents: Vec<Document>) -> PyResult<Vec<Document>> {
+        Ok(self.inner.rerank(query, documents)?)
+    }
+}
+
+/// A reranker for servers with a Cohere-style `/rerank` endpoint.
+///
+/// This struct is exposed to Python as the `HttpReranker` class.
+#[pyclass(name = "HttpReranker")]
+pub struct PyHttpReranker {
+    inner: Arc<HttpReranker>,
+}
+
+#[pymethods]
+impl PyHttpReranker {
+    /// Creates a new `HttpReranker` instance.
+    ///
+    /// Args:
+    ///     model (str): The reranking model, e.g. "rerank-v3.5".
+    ///     base_url (Optional[str]): The URL `/rerank` is appended to. Defaults to the Cohere v2 API.
+    ///     api_key (Optional[str]): The API key. Defaults to None, for servers without authentication.
+    ///     top_n (Optional[int]): Number of documents to keep, or None to keep all. Defaults to None.
+    ///     auth_header (str): The header carrying the API key, as a bearer token for
+    ///         "Authorization" and as is otherwise. Defaults to "Authorization".
+    ///     timeout (float): Seconds a request may take. Defaults to 60.
+    ///
+    /// Raises:
+    ///     PyValueError: If the timeout is not a positive number.
+    #[new]
+    #[pyo3(signature = (model, base_url=None, api_key=None, top_n=None, auth_header="Authorization", timeout=60.0))]
+    fn py_new(
+        model: &str,
+        base_url: Option<&str>,
+        api_key: Option<String>,
+        top_n: Option<usize>,
+        auth_header: &str,
+        timeout: f64,
+    ) -> PyResult<Self> {
+        let timeout = Duration::try_from_secs_f64(timeout)
+            .ok()
+            .filter(|timeout| !timeout.is_zero())
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid timeout: {}", timeout)))?;
+        let mut inner = HttpReranker::new(model)
+            .with_api_key(api_key)
+            .with_top_n(top_n)
+            .with_auth_header(auth_header)
+            .with_timeout(timeout);
+        if let Some(base_url) = base_url {
+            inner = inner.with_base_url(base_url);
+        }
+        Ok(PyHttpReranker { inner: Arc::new(inner) })
+    }
+
+    #[getter]
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+
+    #[getter]
+    fn base_url(&self) -> &str {
+        self.inner.base_url()
+    }
+
+    #[getter]
+    fn top_n(&self) -> Option<usize> {
+        self.inner.top_n()
+    }
+
+    /// Scores documents against a query, releasing the GIL while waiting on the server.
+    ///
+    /// Args:
+    ///     query (str): The search query.
+    ///     documents (List[Document]): The documents to rerank.
+    ///
+    /// Returns:
+    ///     List[Document]: Copies of the documents with `reranking_score` set, most relevant first.
+    ///
+    /// Raises:
+    ///     PyConnectionError: If the server cannot be reached or times out.
+    ///     PyValueError: If the response is malformed.
+    ///     PyRuntimeError: If the server answers with an error.
+    fn rerank(&self, py: Python<'_>, query: &str, documents: Vec<Document>) -> PyResult<Vec<Document>> {
+        let inner = &self.inner;
+        Ok(py.allow_threads(|| inner.rerank(query, documents))?)
+    }
+}
+
+/// Adds the classes of the `reranker` module to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyLexicalReranker>()?;
+    m.add_class::<PyHttpReranker>()?;
+    Ok(())
+}
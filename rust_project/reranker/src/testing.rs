@@ -0,0 +1,119 @@
+//! Documents, a stub rerank server and a fixed-results `VectorDb` for the
+//! reranker tests.
+
+use async_trait::async_trait;
+use document::Document;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use vectordb::{VectorDb, VectorDbError};
+
+pub use test_support::{closed_url, StubResponse, StubServer};
+
+test_support::document_fixtures!(document::Document);
+
+/// A vector db whose searches return its documents in order, up to the
+/// limit, recording each limit asked for. Writes are not implemented, and
+/// neither is `async_search` when `sync_only` is set.
+#[derive(Debug, Default)]
+pub struct FixedDb {
+    pub documents: Vec<Document>,
+    pub limits: Mutex<Vec<u32>>,
+    pub sync_only: bool,
+}
+
+impl FixedDb {
+    pub fn new(documents: Vec<Document>) -> Self {
+        FixedDb { documents, ..Default::default() }
+    }
+}
+
+#[async_trait]
+impl VectorDb for FixedDb {
+    fn create(&mut self) -> Result<(), VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+    async fn async_create(&mut self) -> Result<(), VectorDbError> {
+        self.create()
+    }
+
+    fn doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        Ok(self.documents.iter().any(|stored| stored.content == document.content))
+    }
+    async fn async_doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.doc_exists(document)
+    }
+
+    fn name_exists(&self, _name: &str) -> Result<bool, VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+    async fn async_name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.name_exists(name)
+    }
+
+    fn id_exists(&self, _id: &str) -> Result<bool, VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+
+    fn insert(&mut self, _documents: &[Document], _filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+    async fn async_insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.insert(documents, filters)
+    }
+
+    fn upsert(&mut self, _documents: &[Document], _filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+    async fn async_upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.upsert(documents, filters)
+    }
+
+    fn search(&self, _query: &str, limit: u32, _filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        self.limits.lock().unwrap().push(limit);
+        Ok(self.documents.iter().take(limit as usize).cloned().collect())
+    }
+    async fn async_search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        if self.sync_only {
+            return Err(VectorDbError::NotImplemented);
+        }
+        self.search(query, limit, filters)
+    }
+
+    fn vector_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn keyword_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn hybrid_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, None)
+    }
+
+    fn drop_db(&mut self) -> Result<(), VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+    async fn async_drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.drop_db()
+    }
+
+    fn db_exists(&self) -> Result<bool, VectorDbError> {
+        Ok(true)
+    }
+    async fn async_db_exists(&self) -> Result<bool, VectorDbError> {
+        self.db_exists()
+    }
+
+    fn optimize(&mut self) -> Result<(), VectorDbError> {
+        Ok(())
+    }
+    async fn async_optimize(&mut self) -> Result<(), VectorDbError> {
+        self.optimize()
+    }
+
+    fn delete(&mut self) -> Result<bool, VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+}
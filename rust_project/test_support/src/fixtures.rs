@@ -0,0 +1,36 @@
+/// Defines the document fixtures for the `Document` type `$document`,
+/// e.g. `test_support::document_fixtures!(document::Document);` in the
+/// `testing` module of a crate.
+#[macro_export]
+macro_rules! document_fixtures {
+    ($document:ty) => {
+        /// A document with `id` and `content`.
+        #[allow(dead_code)]
+        pub fn doc(id: &str, content: &str) -> $document {
+            let mut document = <$document>::default();
+            document.id = Some(id.to_string());
+            document.content = content.to_string();
+            document
+        }
+
+        /// Documents with `contents` and no ids.
+        #[allow(dead_code)]
+        pub fn documents(contents: &[&str]) -> Vec<$document> {
+            contents
+                .iter()
+                .map(|content| {
+                    let mut document = <$document>::default();
+                    document.content = content.to_string();
+                    document
+                })
+                .collect()
+        }
+
+        /// `document` with `value` under `key` in its meta data.
+        #[allow(dead_code)]
+        pub fn with_meta_data(mut document: $document, key: &str, value: ::serde_json::Value) -> $document {
+            document.meta_data.insert(key.to_string(), value);
+            document
+        }
+    };
+}
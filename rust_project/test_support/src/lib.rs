@@ -0,0 +1,12 @@
+//! Helpers shared by the tests of the workspace crates: a stub HTTP server
+//! and the document fixtures.
+//!
+//! This crate does not depend on `document`, so that `document` can use it
+//! too: a dev-dependency back on `document` would hand its tests a second,
+//! different `Document` type. The fixtures are generated in each crate by
+//! [`document_fixtures!`] instead.
+
+mod fixtures;
+pub mod server;
+
+pub use server::{closed_url, StubRequest, StubResponse, StubServer};
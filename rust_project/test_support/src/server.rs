@@ -0,0 +1,160 @@
+//! A stub HTTP server on a local port, for testing HTTP clients.
+
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request received by the `StubServer`. Header names are lowercase.
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: JsonValue,
+}
+
+/// A canned answer of the `StubServer`.
+#[derive(Debug, Clone)]
+pub struct StubResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    delay: Duration,
+}
+
+impl StubResponse {
+    pub fn status(status: u16) -> Self {
+        StubResponse { status, headers: Vec::new(), body: String::new(), delay: Duration::ZERO }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = body.to_string();
+        self
+    }
+
+    /// Waits `delay` before answering.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+type Fallback = dyn Fn(&StubRequest) -> StubResponse + Send + Sync;
+
+/// An HTTP server on a local port answering with the queued responses in
+/// order, then with its fallback. Records every request.
+pub struct StubServer {
+    url: String,
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl StubServer {
+    /// Answers with `responses`, then with `fallback` of the request.
+    pub async fn start<F>(responses: Vec<StubResponse>, fallback: F) -> Self
+    where
+        F: Fn(&StubRequest) -> StubResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(responses));
+        let fallback: Arc<Fallback> = Arc::new(fallback);
+        let task = tokio::spawn({
+            let requests = requests.clone();
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(StubServer::serve(stream, requests.clone(), responses.clone(), fallback.clone()));
+                }
+            }
+        });
+        StubServer { url, requests, task }
+    }
+
+    /// Answers every request with `response`.
+    pub async fn always(response: StubResponse) -> Self {
+        StubServer::start(Vec::new(), move |_| response.clone()).await
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    async fn serve(
+        mut stream: TcpStream,
+        requests: Arc<Mutex<Vec<StubRequest>>>,
+        responses: Arc<Mutex<Vec<StubResponse>>>,
+        fallback: Arc<Fallback>,
+    ) {
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 4096];
+        let head_end = loop {
+            if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end;
+            }
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            }
+        };
+        let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+        let mut lines = head.split("\r\n");
+        let path = lines.next().and_then(|line| line.split(' ').nth(1)).unwrap_or_default().to_string();
+        let headers: HashMap<String, String> = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+        let length: usize = headers.get("content-length").and_then(|length| length.parse().ok()).unwrap_or(0);
+        while buffer.len() < head_end + 4 + length {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+            }
+        }
+        let body = serde_json::from_slice(&buffer[head_end + 4..]).unwrap_or(JsonValue::Null);
+        let request = StubRequest { path, headers, body };
+        requests.lock().unwrap().push(request.clone());
+
+        let response = {
+            let mut responses = responses.lock().unwrap();
+            (!responses.is_empty()).then(|| responses.remove(0))
+        };
+        let response = response.unwrap_or_else(|| fallback(&request));
+        tokio::time::sleep(response.delay).await;
+        let mut message = format!(
+            "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            response.status,
+            response.body.len()
+        );
+        for (name, value) in &response.headers {
+            message.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        message.push_str("\r\n");
+        message.push_str(&response.body);
+        let _ = stream.write_all(message.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+}
+
+impl Drop for StubServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A local URL that refuses connections.
+pub async fn closed_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
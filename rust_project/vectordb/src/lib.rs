@@ -5,6 +5,10 @@ use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 
+pub mod python;
+
+pub use python::{PyVectorDb, PyVectorDbBase};
+
 // Define a custom error type for VectorDb operations
 #[derive(Debug)]
 pub enum VectorDbError {
@@ -48,6 +52,11 @@ pub trait VectorDb {
     fn insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError>;
     async fn async_insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError>;
 
+    /// Deletes the document with the given ID, returning whether it existed.
+    fn delete_by_id(&mut self, _id: &str) -> Result<bool, VectorDbError> {
+        Err(VectorDbError::NotImplemented)
+    }
+
     /// Indicates if the database supports an upsert operation.
     fn upsert_available(&self) -> bool {
         false
@@ -80,6 +89,7 @@ pub trait VectorDb {
 
     /// Optimizes the database (e.g., indexing).
     fn optimize(&mut self) -> Result<(), VectorDbError>;
+    async fn async_optimize(&mut self) -> Result<(), VectorDbError>;
 
     /// Deletes the database or collection.
     fn delete(&mut self) -> Result<bool, VectorDbError>;
@@ -0,0 +1,299 @@
+use crate::{VectorDb, VectorDbError};
+use async_trait::async_trait;
+use document::py_json::json_map_to_py;
+use document::Document;
+use pyo3::exceptions::{PyNotImplementedError, PyRuntimeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+impl From<VectorDbError> for PyErr {
+    fn from(err: VectorDbError) -> PyErr {
+        match err {
+            VectorDbError::NotImplemented => PyNotImplementedError::new_err(err.to_string()),
+            _ => PyRuntimeError::new_err(err.to_string()),
+        }
+    }
+}
+
+fn not_implemented(method: &str) -> PyErr {
+    PyNotImplementedError::new_err(format!("VectorDb.{} is not implemented", method))
+}
+
+/// Base class for vector databases written in Python.
+///
+/// Mirrors Python's `agno.vectordb.base.VectorDb`: subclasses override the
+/// methods their backend supports, and the Rust knowledge base calls them
+/// through `PyVectorDb`. Methods left alone raise `NotImplementedError`,
+/// except `upsert_available`, which returns False.
+#[pyclass(name = "VectorDb", subclass)]
+pub struct PyVectorDbBase;
+
+#[pymethods]
+impl PyVectorDbBase {
+    /// Accepts any arguments, so subclasses may define their own `__init__`.
+    #[new]
+    #[pyo3(signature = (*_args, **_kwargs))]
+    fn py_new(_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>) -> Self {
+        PyVectorDbBase
+    }
+
+    /// Creates the collection.
+    fn create(&self) -> PyResult<()> {
+        Err(not_implemented("create"))
+    }
+
+    /// Checks if a document with the same content is stored.
+    fn doc_exists(&self, _document: Document) -> PyResult<bool> {
+        Err(not_implemented("doc_exists"))
+    }
+
+    /// Checks if a document with the given name is stored.
+    fn name_exists(&self, _name: &str) -> PyResult<bool> {
+        Err(not_implemented("name_exists"))
+    }
+
+    /// Checks if a document with the given id is stored.
+    fn id_exists(&self, _id: &str) -> PyResult<bool> {
+        Err(not_implemented("id_exists"))
+    }
+
+    /// Inserts documents.
+    #[pyo3(signature = (documents, filters=None))]
+    fn insert(&self, documents: Vec<Document>, filters: Option<PyObject>) -> PyResult<()> {
+        let _ = (documents, filters);
+        Err(not_implemented("insert"))
+    }
+
+    /// Whether `upsert` is supported.
+    fn upsert_available(&self) -> bool {
+        false
+    }
+
+    /// Inserts documents, replacing stored ones with the same id.
+    #[pyo3(signature = (documents, filters=None))]
+    fn upsert(&self, documents: Vec<Document>, filters: Option<PyObject>) -> PyResult<()> {
+        let _ = (documents, filters);
+        Err(not_implemented("upsert"))
+    }
+
+    /// Returns the documents most relevant to `query`.
+    #[pyo3(signature = (query, limit=5, filters=None))]
+    fn search(&self, query: &str, limit: u32, filters: Option<PyObject>) -> PyResult<Vec<Document>> {
+        let _ = (query, limit, filters);
+        Err(not_implemented("search"))
+    }
+
+    #[pyo3(signature = (query, limit=5))]
+    fn vector_search(&self, query: &str, limit: u32) -> PyResult<Vec<Document>> {
+        let _ = (query, limit);
+        Err(not_implemented("vector_search"))
+    }
+
+    #[pyo3(signature = (query, limit=5))]
+    fn keyword_search(&self, query: &str, limit: u32) -> PyResult<Vec<Document>> {
+        let _ = (query, limit);
+        Err(not_implemented("keyword_search"))
+    }
+
+    #[pyo3(signature = (query, limit=5))]
+    fn hybrid_search(&self, query: &str, limit: u32) -> PyResult<Vec<Document>> {
+        let _ = (query, limit);
+        Err(not_implemented("hybrid_search"))
+    }
+
+    /// Drops the collection.
+    fn drop(&self) -> PyResult<()> {
+        Err(not_implemented("drop"))
+    }
+
+    /// Checks if the collection exists.
+    fn exists(&self) -> PyResult<bool> {
+        Err(not_implemented("exists"))
+    }
+
+    /// Optimizes the collection, e.g. by building indexes.
+    fn optimize(&self) -> PyResult<()> {
+        Err(not_implemented("optimize"))
+    }
+
+    /// Deletes every document, returning whether there were any.
+    fn delete(&self) -> PyResult<bool> {
+        Err(not_implemented("delete"))
+    }
+
+    /// Deletes the document with the given id, returning whether it existed.
+    fn delete_by_id(&self, _id: &str) -> PyResult<bool> {
+        Err(not_implemented("delete_by_id"))
+    }
+}
+
+/// A `VectorDb` backed by a Python object with the methods of the Python
+/// `VectorDb` class (`drop` and `exists` stand for `drop_db` and
+/// `db_exists`). Missing methods and `NotImplementedError` surface as
+/// [`VectorDbError::NotImplemented`], other exceptions as
+/// [`VectorDbError::OperationFailed`].
+///
+/// The async methods call the synchronous Python ones.
+#[derive(Debug, Clone)]
+pub struct PyVectorDb {
+    object: Py<PyAny>,
+}
+
+impl PyVectorDb {
+    pub fn new(object: Py<PyAny>) -> Self {
+        PyVectorDb { object }
+    }
+
+    /// The wrapped Python object.
+    pub fn object(&self) -> &Py<PyAny> {
+        &self.object
+    }
+
+    fn call<T>(&self, method: &str, args: impl FnOnce(Python<'_>) -> Py<PyTuple>) -> Result<T, VectorDbError>
+    where
+        T: for<'py> FromPyObject<'py>,
+    {
+        Python::with_gil(|py| {
+            let object = self.object.bind(py);
+            if !object.hasattr(method).unwrap_or(false) {
+                return Err(VectorDbError::NotImplemented);
+            }
+            object
+                .call_method1(method, args(py).bind(py))
+                .and_then(|result| result.extract::<T>())
+                .map_err(|err| {
+                    if err.is_instance_of::<PyNotImplementedError>(py) {
+                        VectorDbError::NotImplemented
+                    } else {
+                        VectorDbError::OperationFailed(format!("{}: {}", method, err))
+                    }
+                })
+        })
+    }
+
+    fn write(
+        &self,
+        method: &str,
+        documents: &[Document],
+        filters: Option<HashMap<String, JsonValue>>,
+    ) -> Result<(), VectorDbError> {
+        self.call::<PyObject>(method, |py| {
+            let filters = filters.as_ref().map(|filters| json_map_to_py(py, filters));
+            (documents.to_vec(), filters).into_py(py)
+        })
+        .map(|_| ())
+    }
+
+    fn query(&self, method: &str, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.call(method, |py| (query, limit).into_py(py))
+    }
+}
+
+#[async_trait]
+impl VectorDb for PyVectorDb {
+    fn create(&mut self) -> Result<(), VectorDbError> {
+        self.call::<PyObject>("create", |py| PyTuple::empty_bound(py).unbind())
+            .map(|_| ())
+    }
+    async fn async_create(&mut self) -> Result<(), VectorDbError> {
+        self.create()
+    }
+
+    fn doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.call("doc_exists", |py| (document.clone(),).into_py(py))
+    }
+    async fn async_doc_exists(&self, document: &Document) -> Result<bool, VectorDbError> {
+        self.doc_exists(document)
+    }
+
+    fn name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.call("name_exists", |py| (name,).into_py(py))
+    }
+    async fn async_name_exists(&self, name: &str) -> Result<bool, VectorDbError> {
+        self.name_exists(name)
+    }
+
+    fn id_exists(&self, id: &str) -> Result<bool, VectorDbError> {
+        self.call("id_exists", |py| (id,).into_py(py))
+    }
+
+    fn insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.write("insert", documents, filters)
+    }
+    async fn async_insert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.insert(documents, filters)
+    }
+
+    fn delete_by_id(&mut self, id: &str) -> Result<bool, VectorDbError> {
+        self.call("delete_by_id", |py| (id,).into_py(py))
+    }
+
+    fn upsert_available(&self) -> bool {
+        self.call("upsert_available", |py| PyTuple::empty_bound(py).unbind())
+            .unwrap_or(false)
+    }
+
+    fn upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.write("upsert", documents, filters)
+    }
+    async fn async_upsert(&mut self, documents: &[Document], filters: Option<HashMap<String, JsonValue>>) -> Result<(), VectorDbError> {
+        self.upsert(documents, filters)
+    }
+
+    fn search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        self.call("search", |py| {
+            let filters = filters.as_ref().map(|filters| json_map_to_py(py, filters));
+            (query, limit, filters).into_py(py)
+        })
+    }
+    async fn async_search(&self, query: &str, limit: u32, filters: Option<HashMap<String, JsonValue>>) -> Result<Vec<Document>, VectorDbError> {
+        self.search(query, limit, filters)
+    }
+
+    fn vector_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.query("vector_search", query, limit)
+    }
+
+    fn keyword_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.query("keyword_search", query, limit)
+    }
+
+    fn hybrid_search(&self, query: &str, limit: u32) -> Result<Vec<Document>, VectorDbError> {
+        self.query("hybrid_search", query, limit)
+    }
+
+    fn drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.call::<PyObject>("drop", |py| PyTuple::empty_bound(py).unbind())
+            .map(|_| ())
+    }
+    async fn async_drop_db(&mut self) -> Result<(), VectorDbError> {
+        self.drop_db()
+    }
+
+    fn db_exists(&self) -> Result<bool, VectorDbError> {
+        self.call("exists", |py| PyTuple::empty_bound(py).unbind())
+    }
+    async fn async_db_exists(&self) -> Result<bool, VectorDbError> {
+        self.db_exists()
+    }
+
+    fn optimize(&mut self) -> Result<(), VectorDbError> {
+        self.call::<PyObject>("optimize", |py| PyTuple::empty_bound(py).unbind())
+            .map(|_| ())
+    }
+    async fn async_optimize(&mut self) -> Result<(), VectorDbError> {
+        self.optimize()
+    }
+
+    fn delete(&mut self) -> Result<bool, VectorDbError> {
+        self.call("delete", |py| PyTuple::empty_bound(py).unbind())
+    }
+}
+
+/// Adds the classes of the `vectordb` module to `m`.
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVectorDbBase>()?;
+    Ok(())
+}
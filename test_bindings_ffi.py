@@ -0,0 +1,366 @@
+import unittest
+import json
+import os
+import random
+import shutil
+import struct
+import sys
+import tempfile
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+# The agno_rs extension module (libagno_rs.so renamed to agno_rs.so) must be on PYTHONPATH
+try:
+    import agno_rs
+except ImportError:
+    sys.path.append("/app/rust_project/target/release")
+    import agno_rs
+
+from agno_rs.document import Document
+from agno_rs.embedder import EmbeddingPipeline, HttpEmbedder, LocalEmbedder
+from agno_rs.knowledge import DocumentKnowledgeBase, KnowledgeBase
+from agno_rs.memory import Memory, MemoryRetrieval
+from agno_rs.reranker import HttpReranker, LexicalReranker
+from agno_rs.vectordb import VectorDb
+
+
+class DictDb(VectorDb):
+    """A Python vector db keeping documents in a dict."""
+
+    def __init__(self, upsert=False):
+        self.rows = {}
+        self.created = False
+        self.optimized = 0
+        self.upsert_enabled = upsert
+
+    def create(self):
+        self.created = True
+
+    def exists(self):
+        return self.created
+
+    def drop(self):
+        self.rows.clear()
+        self.created = False
+
+    def doc_exists(self, document):
+        return any(row.content == document.content for row, _ in self.rows.values())
+
+    def insert(self, documents, filters=None):
+        for document in documents:
+            self.rows[document.id] = (document, filters)
+
+    def upsert_available(self):
+        return self.upsert_enabled
+
+    def upsert(self, documents, filters=None):
+        self.insert(documents, filters)
+
+    def search(self, query, limit=5, filters=None):
+        found = [
+            row
+            for row, row_filters in self.rows.values()
+            if query in row.content and all((row_filters or {}).get(k) == v for k, v in (filters or {}).items())
+        ]
+        return found[:limit]
+
+    def optimize(self):
+        self.optimized += 1
+
+
+TINY_BERT = os.path.join(os.path.dirname(os.path.abspath(__file__)), "rust_project/embedder/fixtures/tiny_bert")
+
+
+def write_tiny_bert(directory, seed=0):
+    """Copies the tiny BERT config and tokenizer to `directory`, with random weights in safetensors format."""
+    for name in ("config.json", "tokenizer.json"):
+        shutil.copy(os.path.join(TINY_BERT, name), directory)
+    with open(os.path.join(TINY_BERT, "config.json")) as f:
+        config = json.load(f)
+    h, i = config["hidden_size"], config["intermediate_size"]
+    shapes = {
+        "embeddings.word_embeddings.weight": [config["vocab_size"], h],
+        "embeddings.position_embeddings.weight": [config["max_position_embeddings"], h],
+        "embeddings.token_type_embeddings.weight": [config["type_vocab_size"], h],
+        "embeddings.LayerNorm.weight": [h],
+        "embeddings.LayerNorm.bias": [h],
+    }
+    for layer in range(config["num_hidden_layers"]):
+        prefix = f"encoder.layer.{layer}."
+        for name in ("attention.self.query", "attention.self.key", "attention.self.value", "attention.output.dense"):
+            shapes[prefix + name + ".weight"] = [h, h]
+            shapes[prefix + name + ".bias"] = [h]
+        shapes[prefix + "intermediate.dense.weight"] = [i, h]
+        shapes[prefix + "intermediate.dense.bias"] = [i]
+        shapes[prefix + "output.dense.weight"] = [h, i]
+        shapes[prefix + "output.dense.bias"] = [h]
+        for name in ("attention.output.LayerNorm", "output.LayerNorm"):
+            shapes[prefix + name + ".weight"] = [h]
+            shapes[prefix + name + ".bias"] = [h]
+
+    rng = random.Random(seed)
+    header, data = {}, b""
+    for name, shape in shapes.items():
+        count = shape[0] * (shape[1] if len(shape) > 1 else 1)
+        values = [1.0] * count if name.endswith("LayerNorm.weight") else [rng.gauss(0, 0.5) for _ in range(count)]
+        header[name] = {"dtype": "F32", "shape": shape, "data_offsets": [len(data), len(data) + 4 * count]}
+        data += struct.pack(f"<{count}f", *values)
+    header = json.dumps(header).encode()
+    with open(os.path.join(directory, "model.safetensors"), "wb") as f:
+        f.write(struct.pack("<Q", len(header)) + header + data)
+
+
+class TestBindingsFFI(unittest.TestCase):
+
+    def documents(self):
+        return [Document("rust is fast", id="a"), Document("python is easy", id="b"), Document("rust is safe", id="c")]
+
+    def test_01_document_knowledge_base(self):
+        kb = DocumentKnowledgeBase(self.documents())
+        self.assertEqual(len(kb), 3)
+        kb.add_document(Document("go is simple", id="d"))
+        self.assertEqual([docs[0].id for docs in kb.document_lists()], ["a", "b", "c", "d"])
+
+    def test_02_load_into_python_vector_db(self):
+        db = DictDb()
+        kb = KnowledgeBase(DocumentKnowledgeBase(self.documents()), db, num_documents=1, optimize_on=3)
+        self.assertEqual(kb.load(), 3)
+        self.assertTrue(db.created)
+        self.assertEqual(db.optimized, 1)
+        self.assertIs(kb.vector_db, db)
+        # Everything is there already.
+        self.assertEqual(kb.load(), 0)
+        self.assertEqual(kb.load(recreate=True), 3)
+
+    def test_03_search_returns_documents(self):
+        kb = KnowledgeBase(self.documents(), DictDb())
+        kb.load()
+        found = kb.search("rust")
+        self.assertEqual([d.id for d in found], ["a", "c"])
+        self.assertIsInstance(found[0], Document)
+        self.assertEqual(len(kb.search("rust", num_documents=1)), 1)
+
+    def test_04_unknown_filter_keys_are_rejected(self):
+        kb = KnowledgeBase(self.documents(), DictDb())
+        kb.load()
+        self.assertEqual(kb.valid_metadata_filters, [])
+        with self.assertRaises(ValueError):
+            kb.search("rust", filters={"topic": "lang"})
+
+    def test_05_vector_db_errors(self):
+        kb = KnowledgeBase(self.documents(), DictDb())
+        with self.assertRaises(NotImplementedError):
+            kb.delete()
+
+        class Broken(DictDb):
+            def insert(self, documents, filters=None):
+                raise IOError("disk full")
+
+        with self.assertRaises(RuntimeError):
+            KnowledgeBase(self.documents(), Broken()).load()
+        with self.assertRaises(TypeError):
+            KnowledgeBase(42, DictDb())
+
+    def test_06_memory(self):
+        memory = Memory("likes tea", topic="preferences")
+        self.assertEqual(memory.to_dict(), {"memory": "likes tea", "topic": "preferences"})
+        self.assertEqual(Memory.from_json(memory.to_json()).topic, "preferences")
+        self.assertEqual(repr(memory), 'Memory(memory="likes tea", id=None, topic="preferences", input=None)')
+        self.assertEqual(json.loads(memory.to_json())["schema_version"], agno_rs.memory.MEMORY_SCHEMA_VERSION)
+        legacy = Memory.from_json('{"memory": "old", "id": null, "importance": 2}')
+        self.assertIsNone(legacy.id)
+        self.assertEqual(json.loads(legacy.to_json())["importance"], 2)
+        self.assertEqual(MemoryRetrieval.last_n.value, "last_n")
+        self.assertEqual(MemoryRetrieval.from_value("semantic"), MemoryRetrieval.semantic)
+        with self.assertRaises(ValueError):
+            MemoryRetrieval.from_value("random")
+
+    def test_07_embedding_pipeline(self):
+        class CountingEmbedder:
+            id = "counting"
+            dimensions = 2
+
+            def __init__(self, failures=0):
+                self.calls = []
+                self.failures = failures
+
+            def get_embedding_and_usage(self, text):
+                self.calls.append(text)
+                if self.failures:
+                    self.failures -= 1
+                    raise ConnectionError("reset by peer")
+                return [float(len(text)), 1.0], {"prompt_tokens": 3, "total_tokens": 3}
+
+        embedder = CountingEmbedder(failures=1)
+        with tempfile.TemporaryDirectory() as cache_dir:
+            pipeline = EmbeddingPipeline(embedder, batch_size=2, cache_dir=cache_dir)
+            docs = pipeline.embed_documents(self.documents() + [Document("rust is fast", id="d")])
+            self.assertEqual([d.embedding for d in docs][:2], [[12.0, 1.0], [14.0, 1.0]])
+            self.assertEqual(docs[0].usage, {"prompt_tokens": 3, "total_tokens": 3})
+            self.assertEqual(docs[3].embedding, docs[0].embedding)
+            report = pipeline.last_report
+            self.assertEqual((report.embedded, report.cached, report.retries), (4, 0, 1))
+            self.assertEqual(report.prompt_tokens, 9)
+
+            pipeline = EmbeddingPipeline(embedder, cache_dir=cache_dir)
+            pipeline.embed_documents(self.documents())
+            self.assertEqual(pipeline.last_report.cached, 3)
+            # One failed call, the retried batch of two, then the last batch; nothing after.
+            self.assertEqual(len(embedder.calls), 4)
+
+        class Broken(CountingEmbedder):
+            def get_embedding_and_usage(self, text):
+                raise KeyError("no model")
+
+        with self.assertRaises(RuntimeError):
+            EmbeddingPipeline(Broken(), max_retries=5).embed_documents(self.documents())
+
+        class WrongSize(CountingEmbedder):
+            dimensions = 3
+
+        with self.assertRaisesRegex(ValueError, "expected 3 dimensions"):
+            EmbeddingPipeline(WrongSize()).embed_documents([Document("x")])
+
+    def test_08_http_embedder(self):
+        requests = []
+
+        class Handler(BaseHTTPRequestHandler):
+            def do_POST(self):
+                body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                requests.append((self.path, self.headers.get("Authorization"), body))
+                if body["model"] == "missing":
+                    status, reply = 404, {"error": {"message": "model not found"}}
+                else:
+                    data = [{"index": i, "embedding": [float(len(text)), 0.5]} for i, text in enumerate(body["input"])]
+                    status, reply = 200, {"data": data, "usage": {"prompt_tokens": 5, "total_tokens": 5}}
+                payload = json.dumps(reply).encode()
+                self.send_response(status)
+                self.send_header("Content-Type", "application/json")
+                self.send_header("Content-Length", str(len(payload)))
+                self.end_headers()
+                self.wfile.write(payload)
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Handler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        self.addCleanup(server.server_close)
+        self.addCleanup(server.shutdown)
+        base_url = f"http://127.0.0.1:{server.server_port}/v1"
+
+        embedder = HttpEmbedder("small", base_url=base_url, api_key="sk-test", dimensions=2)
+        self.assertEqual((embedder.id, embedder.dimensions), ("small", 2))
+        self.assertEqual(embedder.get_embedding_and_usage("hello"), ([5.0, 0.5], {"prompt_tokens": 5, "total_tokens": 5}))
+        self.assertEqual(requests[0][:2], ("/v1/embeddings", "Bearer sk-test"))
+        self.assertEqual(requests[0][2]["dimensions"], 2)
+
+        docs = EmbeddingPipeline(embedder, batch_size=10).embed_documents(self.documents())
+        self.assertEqual([d.embedding[0] for d in docs], [12.0, 14.0, 12.0])
+        self.assertEqual(len(requests), 2)
+        self.assertEqual(len(requests[1][2]["input"]), 3)
+
+        with self.assertRaisesRegex(RuntimeError, "model not found"):
+            HttpEmbedder("missing", base_url=base_url).get_embedding("x")
+        with self.assertRaises(ValueError):
+            HttpEmbedder("small", timeout=0)
+
+    def test_09_local_embedder(self):
+        with tempfile.TemporaryDirectory() as model_dir:
+            write_tiny_bert(model_dir)
+            embedder = LocalEmbedder(model_dir)
+            self.assertEqual(embedder.dimensions, 16)
+            self.assertTrue(embedder.id.startswith("local:"))
+
+            vector, usage = embedder.get_embedding_and_usage("rust is fast")
+            self.assertAlmostEqual(sum(v * v for v in vector), 1.0, places=4)
+            self.assertEqual(usage, {"prompt_tokens": 5, "total_tokens": 5})
+
+            docs = EmbeddingPipeline(embedder, batch_size=2).embed_documents(self.documents())
+            for expected, actual in zip(vector, docs[0].embedding):
+                self.assertAlmostEqual(expected, actual, places=5)
+            self.assertNotEqual(docs[0].embedding, docs[1].embedding)
+
+            raw = LocalEmbedder(model_dir, normalize=False)
+            self.assertNotEqual(raw.id, embedder.id)
+            self.assertNotAlmostEqual(sum(v * v for v in raw.get_embedding("rust is fast")), 1.0, places=2)
+
+            os.remove(os.path.join(model_dir, "model.safetensors"))
+            with self.assertRaisesRegex(IOError, "model.safetensors"):
+                LocalEmbedder(model_dir)
+
+
+    def test_10_reranking(self):
+        docs = [Document("rust is fast and rust is safe", id="x")] + self.documents()
+        reranked = LexicalReranker().rerank("python safe", docs)
+        # "c" and "x" both mention "safe" once; the shorter one ranks higher.
+        self.assertEqual([d.id for d in reranked], ["b", "c", "x", "a"])
+        self.assertGreater(reranked[0].reranking_score, reranked[1].reranking_score)
+        self.assertEqual(reranked[-1].reranking_score, 0.0)
+        self.assertEqual(len(LexicalReranker(top_n=2).rerank("python", docs)), 2)
+
+        class Reverse:
+            def rerank(self, query, documents):
+                return list(reversed(documents))
+
+        kb = KnowledgeBase(self.documents(), DictDb(), reranker=Reverse())
+        kb.load()
+        self.assertEqual([d.id for d in kb.search("rust")], ["c", "a"])
+        self.assertIsInstance(kb.reranker, Reverse)
+        kb = KnowledgeBase(self.documents(), DictDb(), reranker=LexicalReranker())
+        kb.load()
+        self.assertIsNotNone(kb.search("rust")[0].reranking_score)
+
+        class Broken:
+            def rerank(self, query, documents):
+                raise KeyError("no model")
+
+        kb = KnowledgeBase(self.documents(), DictDb(), reranker=Broken())
+        kb.load()
+        with self.assertRaisesRegex(RuntimeError, "no model"):
+            kb.search("rust")
+
+    def test_11_http_reranker(self):
+        requests = []
+
+        class Handler(BaseHTTPRequestHandler):
+            def do_POST(self):
+                body = json.loads(self.rfile.read(int(self.headers["Content-Length"])))
+                requests.append((self.path, self.headers.get("Authorization"), body))
+                scores = [len(document) / 100 for document in body["documents"]]
+                results = sorted(
+                    ({"index": i, "relevance_score": score} for i, score in enumerate(scores)),
+                    key=lambda result: -result["relevance_score"],
+                )[: body.get("top_n")]
+                payload = json.dumps({"results": results}).encode()
+                self.send_response(200)
+                self.send_header("Content-Type", "application/json")
+                self.send_header("Content-Length", str(len(payload)))
+                self.end_headers()
+                self.wfile.write(payload)
+
+            def log_message(self, *args):
+                pass
+
+        server = HTTPServer(("127.0.0.1", 0), Handler)
+        threading.Thread(target=server.serve_forever, daemon=True).start()
+        self.addCleanup(server.server_close)
+        self.addCleanup(server.shutdown)
+
+        reranker = HttpReranker("bge-reranker", base_url=f"http://127.0.0.1:{server.server_port}", api_key="k", top_n=2)
+        self.assertEqual((reranker.model, reranker.top_n), ("bge-reranker", 2))
+        reranked = reranker.rerank("which is easy?", self.documents())
+        self.assertEqual([(d.id, d.reranking_score) for d in reranked], [("b", 0.14), ("a", 0.12)])
+        self.assertEqual(requests[0][:2], ("/rerank", "Bearer k"))
+        self.assertEqual(requests[0][2]["query"], "which is easy?")
+
+        kb = KnowledgeBase(self.documents(), DictDb(), reranker=reranker)
+        kb.load()
+        self.assertEqual([d.id for d in kb.search("is")], ["b", "a"])
+
+        with self.assertRaises(ConnectionError):
+            HttpReranker("m", base_url="http://127.0.0.1:9", timeout=1).rerank("q", self.documents())
+
+
+if __name__ == "__main__":
+    unittest.main()
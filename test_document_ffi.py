@@ -2,6 +2,10 @@ import unittest
 import sys
 import os
 import json
+import copy
+import pickle
+import tempfile
+from datetime import datetime, timedelta, timezone
 
 # Add the directory containing document.so to Python's search path
 try:
@@ -108,5 +112,250 @@ class TestDocumentFFI(unittest.TestCase):
         self.assertNotIn("name", py_dict)
         self.assertNotIn("reranking_score", py_dict)
 
+    def test_06_native_meta_data_and_usage(self):
+        print("Running test_06_native_meta_data_and_usage")
+        meta = {"source": "native", "page": 3, "score": 0.5, "ok": True, "tags": ["a", "b"], "nested": {"x": None}}
+        doc = document.Document("Dict content", meta_data=meta, usage={"tokens": 7}, embedding=[0.5, -1.0])
+        self.assertEqual(doc.meta_data, meta)
+        self.assertIs(doc.meta_data["ok"], True)
+        self.assertEqual(doc.usage, {"tokens": 7})
+        self.assertEqual(doc.embedding, [0.5, -1.0])
+        self.assertEqual(json.loads(doc.to_json())["embedding"], [0.5, -1.0])
+
+        doc.meta_data = {"key": "value"}
+        self.assertEqual(json.loads(doc.get_meta_data_as_json()), {"key": "value"})
+        doc.usage = None
+        self.assertIsNone(doc.usage)
+        self.assertNotIn("usage", json.loads(doc.to_json()))
+
+        with self.assertRaises(TypeError):
+            doc.meta_data = {"bad": object()}
+        with self.assertRaises(TypeError):
+            document.Document("x", meta_data={1: "non-str key"})
+
+    def test_07_python_protocols(self):
+        print("Running test_07_python_protocols")
+        doc = document.Document("Protocol content", id="p1", name="proto", meta_data={"page": 1}, embedding=[0.25])
+        self.assertEqual(
+            repr(doc),
+            "Document(content='Protocol content', id='p1', name='proto', meta_data={'page': 1}, "
+            "embedding=[0.25], usage=None, reranking_score=None)",
+        )
+
+        same = document.Document("Protocol content", id="p1", name="proto", meta_data={"page": 1}, embedding=[0.25])
+        self.assertEqual(doc, same)
+        self.assertEqual(hash(doc), hash(same))
+        self.assertEqual(len({doc, same}), 1)
+        same.meta_data = {"page": 2}
+        self.assertNotEqual(doc, same)
+        self.assertNotEqual(doc, "Protocol content")
+        with self.assertRaises(TypeError):
+            doc < same
+
+        shallow, deep = copy.copy(doc), copy.deepcopy(doc)
+        self.assertEqual(shallow, doc)
+        self.assertEqual(deep, doc)
+        deep.id = "p2"
+        self.assertEqual(doc.id, "p1")
+
+        restored = pickle.loads(pickle.dumps(doc))
+        self.assertEqual(restored, doc)
+        self.assertIs(type(restored), document.Document)
+
+        self.assertEqual(doc.to_dict(), {"content": "Protocol content", "name": "proto", "meta_data": {"page": 1}})
+        self.assertEqual(document.Document("bare").to_dict(), {"content": "bare", "meta_data": {}})
+        rebuilt = document.Document.from_dict(doc.to_dict())
+        self.assertEqual(rebuilt.name, "proto")
+        self.assertEqual(rebuilt.meta_data, {"page": 1})
+        with self.assertRaises(TypeError):
+            document.Document.from_dict({"content": "x", "unknown": 1})
+
+        # `to_json` output carries schema_version, which is not a constructor argument.
+        full = document.Document("full", id="f", meta_data={"a": [1]}, usage={"t": 2}, embedding=[0.5], reranking_score=0.1)
+        self.assertEqual(document.Document.from_dict(json.loads(full.to_json())), full)
+        legacy = document.Document.from_dict({"content": "old", "meta_data": None, "embedder": None})
+        self.assertEqual(legacy, document.Document("old"))
+
+    def test_08_bulk_json(self):
+        print("Running test_08_bulk_json")
+        docs = [document.Document(f"bulk {i}", id=str(i), meta_data={"i": i}) for i in range(3)]
+        for lines in (False, True):
+            data = document.documents_to_json(docs, lines=lines)
+            self.assertEqual(document.documents_from_json(data, lines=lines), docs)
+        self.assertEqual(len(document.documents_to_json(docs, lines=True).splitlines()), 3)
+
+        with self.assertRaisesRegex(ValueError, "line 2, column"):
+            document.documents_from_json('{"content": "ok"}\n{"content": }\n', lines=True)
+
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "docs.jsonl")
+            document.write_documents(path, docs)
+            with open(path) as f:
+                self.assertEqual(json.loads(f.readline())["id"], "0")
+            self.assertEqual(document.read_documents(path), docs)
+            with self.assertRaises(FileNotFoundError):
+                document.read_documents(os.path.join(tmp, "missing.json"))
+
+    def test_09_binary_encodings(self):
+        print("Running test_09_binary_encodings")
+        doc = document.Document("Binary", id="b1", meta_data={"k": [1, 2]}, usage={"t": 3}, embedding=[i / 7 for i in range(64)])
+        for fmt in ("msgpack", "bincode"):
+            data = doc.to_bytes(fmt)
+            self.assertIsInstance(data, bytes)
+            self.assertLess(len(data), len(doc.to_json()))
+            self.assertEqual(document.Document.from_bytes(data), doc)
+        self.assertEqual(document.Document.from_bytes(doc.to_bytes()), doc)
+
+        with self.assertRaises(ValueError):
+            doc.to_bytes("json")
+        with self.assertRaisesRegex(ValueError, "version"):
+            document.Document.from_bytes(b"\x09\x00")
+        with self.assertRaises(ValueError):
+            document.Document.from_bytes(b"\x01")
+
+    def test_10_arrow_and_parquet(self):
+        print("Running test_10_arrow_and_parquet")
+        docs = [
+            document.Document("a", id="1", meta_data={"page": 1}, embedding=[0.5, 1.5]),
+            document.Document("b", id="2", meta_data={"page": 2}, usage={"tokens": 3}),
+        ]
+        for layout in ("json", "struct"):
+            batch = document.documents_to_arrow(docs, meta_data=layout)
+            self.assertEqual(len(batch), 2)
+            self.assertEqual(batch.column_names[:2], ["content", "id"])
+            self.assertEqual(batch.to_documents(), docs)
+            # The stream export, as pyarrow.table(batch) would read it.
+            self.assertEqual(document.documents_from_arrow(batch), docs)
+
+        class ArrayOnly:
+            # Exposes only the array export, like a pyarrow RecordBatch.
+            def __init__(self, batch):
+                self.batch = batch
+
+            def __arrow_c_array__(self, requested_schema=None):
+                return self.batch.__arrow_c_array__(requested_schema)
+
+        self.assertEqual(document.documents_from_arrow(ArrayOnly(document.documents_to_arrow(docs))), docs)
+        with self.assertRaises(TypeError):
+            document.documents_from_arrow(docs)
+        with self.assertRaises(ValueError):
+            document.documents_to_arrow(docs, meta_data="yaml")
+
+        with tempfile.TemporaryDirectory() as tmp:
+            path = os.path.join(tmp, "docs.parquet")
+            document.write_parquet(path, docs, meta_data="struct")
+            self.assertEqual(document.read_parquet(path), docs)
+            with self.assertRaises(FileNotFoundError):
+                document.read_parquet(os.path.join(tmp, "missing.parquet"))
+    def test_11_schema_versions(self):
+        print("Running test_11_schema_versions")
+        self.assertEqual(json.loads(document.Document("v").to_json())["schema_version"], document.DOCUMENT_SCHEMA_VERSION)
+
+        legacy = document.Document.from_json('{"content": "old", "id": null, "meta_data": null, "embedder": null}')
+        self.assertEqual(legacy, document.Document("old"))
+
+        newer = document.Document.from_json('{"schema_version": 99, "content": "new", "created_at": 1700000000}')
+        self.assertEqual(newer.content, "new")
+        self.assertEqual(json.loads(newer.to_json())["created_at"], 1700000000)
+        self.assertEqual(json.loads(newer.to_json())["schema_version"], 99)
+        self.assertEqual(document.Document.from_bytes(newer.to_bytes("bincode")), newer)
+        self.assertEqual(pickle.loads(pickle.dumps(newer)), newer)
+
+        with self.assertRaisesRegex(ValueError, "schema_version"):
+            document.Document.from_json('{"schema_version": "two", "content": "x"}')
+    def test_12_typed_meta_data(self):
+        print("Running test_12_typed_meta_data")
+        doc = document.Document("typed", meta_data={"source": {"url": "u", "page": 3}, "tags": ["a"], "at": 1700000000})
+        self.assertEqual(doc.get_str("source.url"), "u")
+        self.assertEqual(doc.get_i64("source.page"), 3)
+        self.assertEqual(doc.get_f64("source.page"), 3.0)
+        self.assertIsNone(doc.get_bool("draft"))
+        self.assertEqual(doc.get_path("tags.0"), "a")
+        self.assertEqual(doc.get_path("tags.9", "none"), "none")
+        self.assertEqual(doc.get_datetime("at"), datetime.fromtimestamp(1700000000, timezone.utc))
+        with self.assertRaises(TypeError):
+            doc.get_str("source.page")
+        with self.assertRaises(ValueError):
+            doc.get_str("source.")
+
+        when = datetime(2024, 5, 1, 12, 30, tzinfo=timezone(timedelta(hours=2)))
+        doc.set_datetime("source.fetched", when)
+        self.assertEqual(doc.get_str("source.fetched"), "2024-05-01T12:30:00+02:00")
+        self.assertEqual(doc.get_datetime("source.fetched"), when)
+        self.assertEqual(doc.set_path("source.page", 4), 3)
+        self.assertEqual(doc.remove_path("tags"), ["a"])
+
+        doc.merge_meta_data({"source": {"page": None, "lang": "en"}, "at": None})
+        self.assertEqual(doc.meta_data, {"source": {"url": "u", "lang": "en", "fetched": "2024-05-01T12:30:00+02:00"}})
+
+        schema = document.MetaDataSchema({"source.url": "str", "source.page": "int"}, required=["source.url", "source.page"])
+        self.assertEqual(schema.violations(doc), ["source.page is required"])
+        with self.assertRaisesRegex(ValueError, "source.page is required"):
+            doc.validate_meta_data(schema)
+        doc.set_path("source.page", 1)
+        doc.validate_meta_data(schema)
+        with self.assertRaises(ValueError):
+            document.MetaDataSchema({"a": "blob"})
+
+    def test_13_meta_data_and_usage_write_through(self):
+        print("Running test_13_meta_data_and_usage_write_through")
+        doc = document.Document("Mutable", meta_data={"a": 1}, usage={"tokens": 1})
+        self.assertIsInstance(doc.meta_data, dict)
+        doc.meta_data["k"] = "v"
+        meta = doc.meta_data
+        meta.update({"b": 2}, c=3)
+        meta |= {"d": 4}
+        self.assertEqual(meta.setdefault("e", [5]), [5])
+        self.assertEqual(meta.pop("a"), 1)
+        self.assertIsNone(meta.pop("missing", None))
+        del meta["b"]
+        self.assertEqual(doc.meta_data, {"k": "v", "c": 3, "d": 4, "e": [5]})
+        self.assertEqual(json.loads(doc.get_meta_data_as_json()), {"k": "v", "c": 3, "d": 4, "e": [5]})
+
+        # Invalid items are rejected without changing anything.
+        with self.assertRaises(TypeError):
+            doc.meta_data["bad"] = object()
+        with self.assertRaises(TypeError):
+            doc.meta_data.update({"ok": 1, "bad": object()})
+        with self.assertRaises(TypeError):
+            doc.meta_data[1] = "non-str key"
+        self.assertEqual(set(doc.meta_data), {"k", "c", "d", "e"})
+
+        doc.usage["tokens"] += 2
+        self.assertEqual(doc.usage, {"tokens": 3})
+        self.assertEqual(json.loads(doc.to_json())["usage"], {"tokens": 3})
+        doc.meta_data.clear()
+        self.assertEqual(doc.meta_data, {})
+
+        # Copies are plain dicts, detached from the document.
+        copied = copy.copy(doc.usage)
+        self.assertIs(type(copied), dict)
+        self.assertIs(type(pickle.loads(pickle.dumps(doc.usage))), dict)
+        copied["tokens"] = 0
+        self.assertEqual(doc.usage, {"tokens": 3})
+
+    def test_14_two_meta_data_views(self):
+        print("Running test_14_two_meta_data_views")
+        doc = document.Document("Views", meta_data={"a": 1})
+        stale = doc.meta_data
+        doc.meta_data["x"] = 1
+        stale["y"] = 2
+        self.assertEqual(doc.meta_data, {"a": 1, "x": 1, "y": 2})
+        stale.pop("a")
+        stale.setdefault("z", 3)
+        self.assertEqual(doc.meta_data, {"x": 1, "y": 2, "z": 3})
+
+        # A later assignment is not undone by writes through an older view.
+        doc.meta_data = {"fresh": True}
+        stale["w"] = 4
+        self.assertEqual(doc.meta_data, {"fresh": True, "w": 4})
+        stale.clear()
+        self.assertEqual(doc.meta_data, {"fresh": True})
+
+        doc.usage = {"t": 1, "u": 2}
+        key, _ = doc.usage.popitem()
+        self.assertNotIn(key, doc.usage)
+        self.assertEqual(len(doc.usage), 1)
+
 if __name__ == "__main__":
     unittest.main()